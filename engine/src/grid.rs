@@ -0,0 +1,145 @@
+use crate::{Rule, State, Topology};
+
+// Which cells count as neighbors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    // The eight surrounding cells of a square grid.
+    Moore,
+    // The six surrounding cells of a hexagonal grid, with odd rows shifted
+    // half a cell to the right.
+    Hex,
+}
+
+// Neighbor offsets as (row, column) deltas for even and odd rows of a hex
+// grid laid out with odd rows shifted right.
+const HEX_EVEN_ROW: [(i32, i32); 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)];
+const HEX_ODD_ROW: [(i32, i32); 6] = [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+// A width x height grid of cells laid out row by row, borrowed from whoever
+// owns them. The edges wrap around, so the grid is really a torus, unless
+// it's given another topology. Walls stay as they are and don't count as live
+// neighbors unless `walls_alive` is set.
+#[derive(Clone, Copy, Debug)]
+pub struct Grid<'a, C> {
+    pub cells: &'a [C],
+    pub width: u32,
+    pub height: u32,
+    pub topology: Topology,
+    pub walls_alive: bool,
+}
+
+impl<'a, C: State> Grid<'a, C> {
+    pub fn new(cells: &'a [C], width: u32, height: u32) -> Grid<'a, C> {
+        assert_eq!(cells.len(), (width * height) as usize);
+        Grid {
+            cells,
+            width,
+            height,
+            topology: Topology::TORUS,
+            walls_alive: false,
+        }
+    }
+
+    // The same grid with its edges joined up as `topology` says.
+    pub fn with_topology(self, topology: Topology) -> Grid<'a, C> {
+        Grid { topology, ..self }
+    }
+
+    // The same grid with walls counted as live neighbors, or not.
+    pub fn with_walls_alive(self, walls_alive: bool) -> Grid<'a, C> {
+        Grid {
+            walls_alive,
+            ..self
+        }
+    }
+
+    pub fn index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+
+    fn alive(&self, row: u32, column: u32) -> u8 {
+        let cell = self.cells[self.index(row, column)];
+        (cell.is_alive() || self.walls_alive && cell.is_wall()) as u8
+    }
+
+    // How many of the cell's neighbors are alive.
+    pub fn live_neighbors(&self, row: u32, column: u32, neighborhood: Neighborhood) -> u8 {
+        match neighborhood {
+            Neighborhood::Moore => self.moore_neighbors(row, column),
+            Neighborhood::Hex => self.hex_neighbors(row, column),
+        }
+    }
+
+    // Whether the neighbor `delta_row`, `delta_col` away from (row, column)
+    // is alive, following the topology past the edges.
+    fn neighbor_alive(&self, row: u32, column: u32, delta_row: i32, delta_col: i32) -> u8 {
+        self.topology
+            .resolve(
+                row as i32 + delta_row,
+                column as i32 + delta_col,
+                self.width,
+                self.height,
+            )
+            .map_or(0, |(row, column)| self.alive(row, column))
+    }
+
+    fn moore_neighbors(&self, row: u32, column: u32) -> u8 {
+        if self.topology != Topology::TORUS {
+            let mut count = 0;
+            for delta_row in -1..=1 {
+                for delta_col in -1..=1 {
+                    if delta_row != 0 || delta_col != 0 {
+                        count += self.neighbor_alive(row, column, delta_row, delta_col);
+                    }
+                }
+            }
+            return count;
+        }
+
+        let mut count = 0;
+        for &delta_row in &[self.height - 1, 0, 1] {
+            for &delta_col in &[self.width - 1, 0, 1] {
+                if delta_row == 0 && delta_col == 0 {
+                    continue;
+                }
+
+                let neighbor_row = (row + delta_row) % self.height;
+                let neighbor_col = (column + delta_col) % self.width;
+                count += self.alive(neighbor_row, neighbor_col);
+            }
+        }
+        count
+    }
+
+    fn hex_neighbors(&self, row: u32, column: u32) -> u8 {
+        let offsets = if row.is_multiple_of(2) {
+            &HEX_EVEN_ROW
+        } else {
+            &HEX_ODD_ROW
+        };
+
+        offsets
+            .iter()
+            .map(|&(delta_row, delta_col)| self.neighbor_alive(row, column, delta_row, delta_col))
+            .sum()
+    }
+
+    // Writes the next generation into `next`, which must be the same size as
+    // the grid. Doesn't allocate, so `next` can be a buffer kept from the
+    // generation before.
+    pub fn step_into(&self, rule: &Rule, neighborhood: Neighborhood, next: &mut [C]) {
+        assert_eq!(next.len(), self.cells.len());
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.index(row, col);
+                if self.cells[idx].is_wall() {
+                    next[idx] = self.cells[idx];
+                    continue;
+                }
+                let neighbors = self.live_neighbors(row, col, neighborhood);
+                next[idx] = rule.next(self.cells[idx], neighbors);
+            }
+        }
+    }
+}
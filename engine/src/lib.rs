@@ -0,0 +1,47 @@
+// The Game of Life engine: rules, neighbor counting, stepping a whole grid,
+// nutrient fields and the well known patterns. Only needs `core` and `alloc`,
+// so it runs anywhere with an allocator, from the browser build to an LED
+// matrix on a microcontroller.
+
+#![no_std]
+
+extern crate alloc;
+
+mod grid;
+mod nutrients;
+pub mod patterns;
+mod rule;
+mod topology;
+
+pub use grid::{Grid, Neighborhood};
+pub use nutrients::Nutrients;
+pub use rule::{Rule, Rule3D};
+pub use topology::{Edge, Topology};
+
+// A cell state the engine can read and write. Implemented for `bool` and
+// `u8`, so plain buffers work as grids, and by whatever cell type a front end
+// keeps its cells in.
+pub trait State: Copy + PartialEq {
+    const DEAD: Self;
+    const ALIVE: Self;
+
+    fn is_alive(self) -> bool {
+        self == Self::ALIVE
+    }
+
+    // Whether the cell is a wall, which the rules never change. Plain
+    // buffers have no walls.
+    fn is_wall(self) -> bool {
+        false
+    }
+}
+
+impl State for bool {
+    const DEAD: bool = false;
+    const ALIVE: bool = true;
+}
+
+impl State for u8 {
+    const DEAD: u8 = 0;
+    const ALIVE: u8 = 1;
+}
@@ -0,0 +1,76 @@
+use crate::State;
+
+// A field of nutrient levels under a grid, one per cell, that the cells live
+// off. A cell can only be born where there's enough to pay for it, which is
+// taken out of the field, and only survives where there's enough left. Each
+// generation the field spreads out and grows back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Nutrients {
+    // Taken from a cell's level when it's born. Cells with less can't be.
+    pub birth_cost: f32,
+    // The level a live cell needs to survive.
+    pub survival_min: f32,
+    // How much of the way each level moves towards the average of its four
+    // neighbors each generation, from 0 to 1.
+    pub diffusion: f32,
+    // Added to every level each generation, up to `capacity`.
+    pub regrowth: f32,
+    pub capacity: f32,
+}
+
+impl Default for Nutrients {
+    fn default() -> Self {
+        Nutrients {
+            birth_cost: 0.5,
+            survival_min: 0.1,
+            diffusion: 0.2,
+            regrowth: 0.02,
+            capacity: 1.0,
+        }
+    }
+}
+
+impl Nutrients {
+    // Goes over a generation stepped from `cells` into `next` by the rules,
+    // undoing births and survivals `field` can't support and paying for the
+    // births that go ahead.
+    pub fn feed<C: State>(&self, cells: &[C], next: &mut [C], field: &mut [f32]) {
+        for ((&cell, next), level) in cells.iter().zip(next.iter_mut()).zip(field.iter_mut()) {
+            if !next.is_alive() {
+                continue;
+            }
+            if cell.is_alive() {
+                if *level < self.survival_min {
+                    *next = C::DEAD;
+                }
+            } else if *level >= self.birth_cost {
+                *level -= self.birth_cost;
+            } else {
+                *next = C::DEAD;
+            }
+        }
+    }
+
+    // Writes `field`, spread out and grown back by a generation, into `out`.
+    // Both are width x height levels laid out row by row, and the field
+    // wraps around the edges.
+    pub fn diffuse(&self, field: &[f32], width: u32, height: u32, out: &mut [f32]) {
+        assert_eq!(field.len(), (width * height) as usize);
+        assert_eq!(out.len(), field.len());
+
+        let at = |row: u32, column: u32| field[(row * width + column) as usize];
+        for row in 0..height {
+            for col in 0..width {
+                let mean = (at((row + height - 1) % height, col)
+                    + at((row + 1) % height, col)
+                    + at(row, (col + width - 1) % width)
+                    + at(row, (col + 1) % width))
+                    / 4.0;
+                let level = at(row, col);
+                let spread = level + (mean - level) * self.diffusion;
+                out[(row * width + col) as usize] =
+                    (spread + self.regrowth).min(self.capacity).max(0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Well known patterns as the (row, column) offsets of their live cells from
+// their top-left corner, and ways to turn them.
+
+// The smallest spaceship, heading south east.
+pub fn glider() -> Vec<(u32, u32)> {
+    vec![(0, 2), (1, 0), (1, 2), (2, 1), (2, 2)]
+}
+
+// A period 3 oscillator, 13 cells across.
+pub fn pulsar() -> Vec<(u32, u32)> {
+    // Each side of the pulsar is three cells long and sits one cell in from
+    // the edge, mirrored about the middle row and column.
+    let mut cells = Vec::new();
+    for &edge in &[0, 5, 7, 12] {
+        for &side in &[2, 3, 4, 8, 9, 10] {
+            cells.push((edge, side));
+            cells.push((side, edge));
+        }
+    }
+    cells
+}
+
+// Bill Gosper's gun, which fires a glider south east every 30 generations.
+pub fn gosper_glider_gun() -> Vec<(u32, u32)> {
+    vec![
+        (0, 24),
+        (1, 22),
+        (1, 24),
+        (2, 12),
+        (2, 13),
+        (2, 20),
+        (2, 21),
+        (2, 34),
+        (2, 35),
+        (3, 11),
+        (3, 15),
+        (3, 20),
+        (3, 21),
+        (3, 34),
+        (3, 35),
+        (4, 0),
+        (4, 1),
+        (4, 10),
+        (4, 16),
+        (4, 20),
+        (4, 21),
+        (5, 0),
+        (5, 1),
+        (5, 10),
+        (5, 14),
+        (5, 16),
+        (5, 17),
+        (5, 22),
+        (5, 24),
+        (6, 10),
+        (6, 16),
+        (6, 24),
+        (7, 11),
+        (7, 15),
+        (8, 12),
+        (8, 13),
+    ]
+}
+
+// The (height, width) of the smallest box around the cells.
+pub fn size(cells: &[(u32, u32)]) -> (u32, u32) {
+    let height = cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(0);
+    let width = cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(0);
+    (height, width)
+}
+
+// Turns the cells clockwise by `turns` quarter turns, keeping them in the
+// top-left corner.
+pub fn rotate(cells: Vec<(u32, u32)>, turns: u32) -> Vec<(u32, u32)> {
+    // Each quarter turn swaps the height and width of the pattern.
+    let (mut height, mut width) = size(&cells);
+    let mut rotated = cells;
+    for _ in 0..turns % 4 {
+        rotated = rotated
+            .into_iter()
+            .map(|(row, col)| (col, height - 1 - row))
+            .collect();
+        core::mem::swap(&mut height, &mut width);
+    }
+    rotated
+}
@@ -0,0 +1,267 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::State;
+
+// Which neighbor counts bring a dead cell to life and which keep a live one
+// alive, as bit sets indexed by the count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Default for Rule {
+    // Conway's rules: born with 3 neighbors, survives with 2 or 3.
+    fn default() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survival: 1 << 2 | 1 << 3,
+        }
+    }
+}
+
+impl Rule {
+    pub fn is_born(&self, neighbors: u8) -> bool {
+        self.birth & 1 << neighbors != 0
+    }
+
+    pub fn survives(&self, neighbors: u8) -> bool {
+        self.survival & 1 << neighbors != 0
+    }
+
+    // The state a cell in state `cell` moves to with `neighbors` live
+    // neighbors.
+    pub fn next<C: State>(&self, cell: C, neighbors: u8) -> C {
+        let alive = if cell.is_alive() {
+            // A live cell with a neighbor count in the survival set lives on.
+            // With Conway's rules that is two or three live neighbors; any
+            // other live cell dies, as if by under- or overpopulation.
+            self.survives(neighbors)
+        } else {
+            // A dead cell with a neighbor count in the birth set comes to
+            // life, as if by reproduction. With Conway's rules that is
+            // exactly three live neighbors.
+            self.is_born(neighbors)
+        };
+
+        if alive {
+            C::ALIVE
+        } else {
+            C::DEAD
+        }
+    }
+
+    // Parses a rule in B/S notation, like "B3/S23" for Conway's rules or
+    // "B36/S23" for HighLife.
+    pub fn parse(text: &str) -> Result<Rule, String> {
+        let mut rule = Rule {
+            birth: 0,
+            survival: 0,
+        };
+        let (mut seen_birth, mut seen_survival) = (false, false);
+
+        for part in text.trim().split('/') {
+            let mut chars = part.chars();
+            let set = match chars.next() {
+                Some('B') | Some('b') if !seen_birth => {
+                    seen_birth = true;
+                    &mut rule.birth
+                }
+                Some('S') | Some('s') if !seen_survival => {
+                    seen_survival = true;
+                    &mut rule.survival
+                }
+                _ => return Err(format!("expected a rule like B3/S23, not `{}`", text)),
+            };
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(count) if count <= 8 => *set |= 1 << count,
+                    _ => return Err(format!("`{}` isn't a neighbor count", c)),
+                }
+            }
+        }
+
+        if seen_birth && seen_survival {
+            Ok(rule)
+        } else {
+            Err(format!("expected a rule like B3/S23, not `{}`", text))
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        for count in 0..=8 {
+            if self.is_born(count) {
+                write!(f, "{}", count)?;
+            }
+        }
+        write!(f, "/S")?;
+        for count in 0..=8 {
+            if self.survives(count) {
+                write!(f, "{}", count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// A life-like rule for a 3D grid, where each cell has 26 neighbors. Works
+// like `Rule`, with room for the larger neighbor counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule3D {
+    birth: u32,
+    survival: u32,
+}
+
+impl Default for Rule3D {
+    // Bays' 5766: born with 6 neighbors, survives with 5 to 7.
+    fn default() -> Self {
+        Rule3D {
+            birth: 1 << 6,
+            survival: 1 << 5 | 1 << 6 | 1 << 7,
+        }
+    }
+}
+
+// Most neighbors a cell in a 3D grid can have.
+const MAX_NEIGHBORS_3D: u32 = 26;
+
+// The bits for counts `low` to `high` in a neighbor count set.
+fn count_range(low: u32, high: u32) -> u32 {
+    (low..=high).fold(0, |set, count| set | 1 << count)
+}
+
+fn neighbor_count(text: &str) -> Result<u32, String> {
+    match text.trim().parse() {
+        Ok(count) if count <= MAX_NEIGHBORS_3D => Ok(count),
+        _ => Err(format!("`{}` isn't a neighbor count", text)),
+    }
+}
+
+// A set of neighbor counts written as digits, like "45", or, for counts past
+// 9, as a list of counts and ranges, like "4,5" or "10-12".
+fn parse_counts(text: &str) -> Result<u32, String> {
+    if !text.contains([',', '-']) {
+        return text.chars().try_fold(0, |set, c| {
+            Ok(set | 1 << neighbor_count(c.encode_utf8(&mut [0; 4]))?)
+        });
+    }
+
+    let mut set = 0;
+    for item in text.split(',') {
+        set |= match item.split_once('-') {
+            Some((low, high)) => count_range(neighbor_count(low)?, neighbor_count(high)?),
+            None => 1 << neighbor_count(item)?,
+        };
+    }
+    Ok(set)
+}
+
+impl Rule3D {
+    pub fn is_born(&self, neighbors: u8) -> bool {
+        self.birth & 1 << neighbors != 0
+    }
+
+    pub fn survives(&self, neighbors: u8) -> bool {
+        self.survival & 1 << neighbors != 0
+    }
+
+    // The state a cell in state `cell` moves to with `neighbors` live
+    // neighbors.
+    pub fn next<C: State>(&self, cell: C, neighbors: u8) -> C {
+        let alive = if cell.is_alive() {
+            self.survives(neighbors)
+        } else {
+            self.is_born(neighbors)
+        };
+
+        if alive {
+            C::ALIVE
+        } else {
+            C::DEAD
+        }
+    }
+
+    // Parses a rule in Bays' notation, giving the range of neighbor counts a
+    // live cell survives with and then the range a dead one is born with:
+    // "5766" or "5/7/6/6" survives with 5 to 7 and is born with 6. B/S
+    // notation works too, like "B6/S567", with lists like "S4,5" or "S10-12"
+    // for counts past 9.
+    pub fn parse(text: &str) -> Result<Rule3D, String> {
+        let text = text.trim();
+        if text.starts_with(['B', 'b', 'S', 's']) {
+            return Rule3D::parse_bs(text);
+        }
+
+        let bounds: Result<Vec<u32>, String> = if text.contains('/') {
+            text.split('/').map(neighbor_count).collect()
+        } else {
+            text.chars()
+                .map(|c| neighbor_count(c.encode_utf8(&mut [0; 4])))
+                .collect()
+        };
+        match bounds?.as_slice() {
+            &[survive_low, survive_high, birth_low, birth_high] => Ok(Rule3D {
+                birth: count_range(birth_low, birth_high),
+                survival: count_range(survive_low, survive_high),
+            }),
+            _ => Err(format!(
+                "expected a rule like 5766 or B6/S567, not `{}`",
+                text
+            )),
+        }
+    }
+
+    fn parse_bs(text: &str) -> Result<Rule3D, String> {
+        let mut rule = Rule3D {
+            birth: 0,
+            survival: 0,
+        };
+        let (mut seen_birth, mut seen_survival) = (false, false);
+
+        for part in text.split('/') {
+            let mut chars = part.chars();
+            let set = match chars.next() {
+                Some('B') | Some('b') if !seen_birth => {
+                    seen_birth = true;
+                    &mut rule.birth
+                }
+                Some('S') | Some('s') if !seen_survival => {
+                    seen_survival = true;
+                    &mut rule.survival
+                }
+                _ => return Err(format!("expected a rule like B6/S567, not `{}`", text)),
+            };
+            *set = parse_counts(chars.as_str())?;
+        }
+
+        if seen_birth && seen_survival {
+            Ok(rule)
+        } else {
+            Err(format!("expected a rule like B6/S567, not `{}`", text))
+        }
+    }
+}
+
+impl fmt::Display for Rule3D {
+    // Writes the rule in B/S notation with the counts separated by commas,
+    // like "B6/S5,6,7", since counts can run past 9.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_counts = |f: &mut fmt::Formatter, set: u32| {
+            let mut counts = (0..=MAX_NEIGHBORS_3D).filter(|count| set & 1 << count != 0);
+            if let Some(first) = counts.next() {
+                write!(f, "{}", first)?;
+            }
+            counts.try_for_each(|count| write!(f, ",{}", count))
+        };
+        write!(f, "B")?;
+        write_counts(f, self.birth)?;
+        write!(f, "/S")?;
+        write_counts(f, self.survival)
+    }
+}
@@ -0,0 +1,69 @@
+// What a neighbor past one pair of opposite edges of the grid turns out to
+// be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    // The cell on the other side, as if the edges were glued together.
+    Wrap,
+    // Nothing: cells past the edge are always dead.
+    Bounded,
+    // The cell on the other side, mirrored along the edge, as if the grid
+    // were given a half twist before gluing.
+    Flip,
+}
+
+// How the grid's edges join up, one `Edge` for the left and right edges and
+// one for the top and bottom. Wrapping both gives the usual torus; the others
+// worth knowing by name are:
+//
+// - cylinder: wrapping left and right, bounded top and bottom.
+// - Möbius strip: flipping left and right, bounded top and bottom.
+// - Klein bottle: flipping left and right, wrapping top and bottom.
+// - projective plane: flipping both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Topology {
+    pub horizontal: Edge,
+    pub vertical: Edge,
+}
+
+impl Topology {
+    pub const TORUS: Topology = Topology {
+        horizontal: Edge::Wrap,
+        vertical: Edge::Wrap,
+    };
+
+    // Where the cell at (row, column) ends up on a width x height grid, for
+    // a neighbor that may be up to one cell past any edge. None if it's past
+    // a bounded edge.
+    pub fn resolve(&self, row: i32, column: i32, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (width, height) = (width as i32, height as i32);
+        let (mut row, mut column) = (row, column);
+
+        if column < 0 || column >= width {
+            match self.horizontal {
+                Edge::Bounded => return None,
+                Edge::Wrap => column = column.rem_euclid(width),
+                Edge::Flip => {
+                    column = column.rem_euclid(width);
+                    row = height - 1 - row;
+                }
+            }
+        }
+        if row < 0 || row >= height {
+            match self.vertical {
+                Edge::Bounded => return None,
+                Edge::Wrap => row = row.rem_euclid(height),
+                Edge::Flip => {
+                    row = row.rem_euclid(height);
+                    column = width - 1 - column;
+                }
+            }
+        }
+        Some((row as u32, column as u32))
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Topology::TORUS
+    }
+}
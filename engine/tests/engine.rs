@@ -0,0 +1,108 @@
+extern crate life_engine;
+
+use life_engine::{patterns, Edge, Grid, Neighborhood, Nutrients, Rule, Rule3D, Topology};
+
+#[test]
+fn test_blinker_oscillates() {
+    let vertical = [
+        0, 0, 0, 0, 0,
+        0, 0, 1, 0, 0,
+        0, 0, 1, 0, 0,
+        0, 0, 1, 0, 0,
+        0, 0, 0, 0, 0u8,
+    ];
+    let horizontal = [
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 1, 1, 1, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0u8,
+    ];
+
+    let mut next = [0; 25];
+    Grid::new(&vertical, 5, 5).step_into(&Rule::default(), Neighborhood::Moore, &mut next);
+    assert_eq!(next, horizontal);
+
+    let mut back = [0; 25];
+    Grid::new(&next, 5, 5).step_into(&Rule::default(), Neighborhood::Moore, &mut back);
+    assert_eq!(back, vertical);
+}
+
+#[test]
+fn test_rule_parse() {
+    let highlife = Rule::parse("b36/s23").unwrap();
+    assert_eq!(highlife.to_string(), "B36/S23");
+    assert!(highlife.is_born(6));
+    assert!(!highlife.survives(6));
+    assert_eq!(Rule::default().to_string(), "B3/S23");
+    assert!(Rule::parse("B9/S23").is_err());
+    assert!(Rule::parse("23/3").is_err());
+}
+
+#[test]
+fn test_rule3d_parse() {
+    let bays = Rule3D::parse("5766").unwrap();
+    assert_eq!(bays, Rule3D::default());
+    assert_eq!(bays.to_string(), "B6/S5,6,7");
+    assert_eq!(Rule3D::parse("5/7/6/6").unwrap(), bays);
+    assert_eq!(Rule3D::parse("B6/S567").unwrap(), bays);
+    assert_eq!(Rule3D::parse(&bays.to_string()).unwrap(), bays);
+
+    let wide = Rule3D::parse("B10-12/S4,20").unwrap();
+    assert!(wide.is_born(11));
+    assert!(wide.survives(20));
+    assert!(!wide.survives(5));
+    assert!(Rule3D::parse("B6/S4,27").is_err());
+    assert!(Rule3D::parse("576").is_err());
+}
+
+#[test]
+fn test_rotate_glider() {
+    let glider = patterns::glider();
+    let mut turned = patterns::rotate(glider.clone(), 1);
+    turned.sort_unstable();
+    assert_eq!(turned, vec![(0, 1), (1, 0), (2, 0), (2, 1), (2, 2)]);
+    assert_eq!(patterns::rotate(glider.clone(), 4), glider);
+    assert_eq!(patterns::size(&patterns::gosper_glider_gun()), (9, 36));
+}
+
+#[test]
+fn test_topology_resolve() {
+    let klein = Topology {
+        horizontal: Edge::Flip,
+        vertical: Edge::Wrap,
+    };
+    assert_eq!(klein.resolve(1, -1, 5, 4), Some((2, 4)));
+    assert_eq!(klein.resolve(-1, 1, 5, 4), Some((3, 1)));
+    assert_eq!(klein.resolve(2, 2, 5, 4), Some((2, 2)));
+
+    let cylinder = Topology {
+        horizontal: Edge::Wrap,
+        vertical: Edge::Bounded,
+    };
+    assert_eq!(cylinder.resolve(0, 5, 5, 4), Some((0, 0)));
+    assert_eq!(cylinder.resolve(4, 0, 5, 4), None);
+    assert_eq!(Topology::default(), Topology::TORUS);
+}
+
+#[test]
+fn test_nutrients() {
+    let nutrients = Nutrients {
+        diffusion: 0.5,
+        regrowth: 0.25,
+        ..Nutrients::default()
+    };
+
+    // Births the field can pay for go ahead and cost it; survivals need
+    // enough left.
+    let cells = [0, 0, 1, 1u8];
+    let mut next = [1, 1, 1, 1u8];
+    let mut field = [0.75, 0.25, 0.5, 0.0];
+    nutrients.feed(&cells, &mut next, &mut field);
+    assert_eq!(next, [1, 0, 1, 0]);
+    assert_eq!(field, [0.25, 0.25, 0.5, 0.0]);
+
+    let mut spread = [0.0; 4];
+    nutrients.diffuse(&[1.0, 0.0, 0.0, 0.0], 2, 2, &mut spread);
+    assert_eq!(spread, [0.75, 0.5, 0.5, 0.25]);
+}
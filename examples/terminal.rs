@@ -0,0 +1,30 @@
+// Runs a small universe in the terminal.
+//
+//     cargo run --example terminal --features terminal
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use wasm_game_of_life::{Terminal, Universe};
+
+fn main() -> io::Result<()> {
+    let mut universe = Universe::new();
+    universe.set_width(48).unwrap();
+    universe.set_height(32).unwrap();
+    universe.create_glider(2, 4).unwrap();
+    universe.create_glider(10, 30).unwrap();
+    universe.create_glider(20, 14).unwrap();
+    universe.set_cells(&[(26, 40), (26, 41), (26, 42)]).unwrap();
+
+    let stdout = io::stdout();
+    let mut terminal = Terminal::new(stdout.lock());
+
+    for _ in 0..500 {
+        terminal.draw(&universe)?;
+        universe.tick();
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    Ok(())
+}
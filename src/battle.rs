@@ -0,0 +1,347 @@
+use wasm_bindgen::prelude::*;
+
+use crate::rule::Rule;
+use crate::{check_size, utils};
+
+// One side of a `Battle`. Its value is what its cells are stored as.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Player {
+    One = 1,
+    Two = 2,
+}
+
+// Where a `Battle` is up to.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BattlePhase {
+    // The players are placing their cells.
+    Seeding = 0,
+    // Both players are ready and the generations are being played out.
+    Running = 1,
+    // The last generation has been played and the scores are final.
+    Finished = 2,
+}
+
+// What a cell of a `Battle` holds when neither player has it.
+const EMPTY: u8 = 0;
+
+// A two player game of Life. Each player seeds their half of the board, the
+// left for `One` and the right for `Two`, with up to a set number of cells,
+// and says they're ready. Then the board runs for a set number of
+// generations, with cells in the team colors of the Immigration variant: a
+// cell is born to whichever player has more of the live cells around it, and
+// with a tie, isn't born at all. The edges wrap, so the halves meet on both
+// sides. At the end the player with more live cells wins, or failing that
+// the one who has held more of the board at some point.
+//
+// Every move is checked and every score worked out here, so two clients
+// applying the same moves always agree.
+#[wasm_bindgen]
+pub struct Battle {
+    width: u32,
+    height: u32,
+    // Each cell as EMPTY or the player whose cell it is.
+    cells: Vec<u8>,
+    next: Vec<u8>,
+    // The last player to have had each cell, EMPTY if neither has.
+    territory: Vec<u8>,
+    rule: Rule,
+    budget: u32,
+    generations: u32,
+    generation: u32,
+    // Cells placed and whether they're ready, for each player.
+    placed: [u32; 2],
+    ready: [bool; 2],
+    phase: BattlePhase,
+}
+
+// Methods not being exported to Javascript
+impl Battle {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+
+    fn slot(player: Player) -> usize {
+        player as usize - 1
+    }
+
+    // Checks that `player` may change the cell at (row, column) now, and
+    // returns its index.
+    fn seeding_cell(&self, player: Player, row: u32, column: u32) -> Result<usize, String> {
+        if self.phase != BattlePhase::Seeding {
+            return Err(String::from(
+                "the cells can only be changed before the battle starts",
+            ));
+        }
+        if self.ready[Battle::slot(player)] {
+            return Err(format!("player {:?} is already ready", player));
+        }
+        if row >= self.height || column >= self.width {
+            return Err(format!(
+                "({}, {}) is outside the {} by {} board",
+                row, column, self.width, self.height
+            ));
+        }
+        let half = self.width / 2;
+        let own_half = match player {
+            Player::One => column < half,
+            Player::Two => column >= half,
+        };
+        if !own_half {
+            return Err(format!(
+                "({}, {}) isn't in player {:?}'s half",
+                row, column, player
+            ));
+        }
+        Ok(self.get_index(row, column))
+    }
+
+    fn count(&self, cells: &[u8], player: Player) -> u32 {
+        cells.iter().filter(|&&cell| cell == player as u8).count() as u32
+    }
+
+    fn step(&mut self) {
+        let (width, height) = (self.width, self.height);
+        for row in 0..height {
+            for col in 0..width {
+                let mut counts = [0u8; 3];
+                for delta_row in [height - 1, 0, 1].iter().copied() {
+                    for delta_col in [width - 1, 0, 1].iter().copied() {
+                        if delta_row == 0 && delta_col == 0 {
+                            continue;
+                        }
+                        let neighbor =
+                            self.get_index((row + delta_row) % height, (col + delta_col) % width);
+                        counts[self.cells[neighbor] as usize] += 1;
+                    }
+                }
+
+                let idx = self.get_index(row, col);
+                let neighbors = counts[1] + counts[2];
+                let cell = self.cells[idx];
+                self.next[idx] = if cell != EMPTY {
+                    if self.rule.survives(neighbors) {
+                        cell
+                    } else {
+                        EMPTY
+                    }
+                } else if self.rule.is_born(neighbors) && counts[1] != counts[2] {
+                    if counts[1] > counts[2] {
+                        Player::One as u8
+                    } else {
+                        Player::Two as u8
+                    }
+                } else {
+                    EMPTY
+                };
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+        for (owner, &cell) in self.territory.iter_mut().zip(self.cells.iter()) {
+            if cell != EMPTY {
+                *owner = cell;
+            }
+        }
+        self.generation += 1;
+        if self.generation >= self.generations {
+            self.phase = BattlePhase::Finished;
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Battle {
+    // A board of `width` by `height` cells where each player may place up to
+    // `budget` cells and the battle lasts `generations` generations. Throws
+    // if the board is too narrow to split in two, or too big.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32, budget: u32, generations: u32) -> Result<Battle, JsValue> {
+        utils::set_panic_hook();
+
+        if width < 2 || height == 0 {
+            return Err(JsValue::from_str(
+                "the board needs two columns to split in half",
+            ));
+        }
+        check_size(width, height).map_err(|err| JsValue::from_str(&err))?;
+        let len = width as usize * height as usize;
+        Ok(Battle {
+            width,
+            height,
+            cells: vec![EMPTY; len],
+            next: vec![EMPTY; len],
+            territory: vec![EMPTY; len],
+            rule: Rule::default(),
+            budget,
+            generations,
+            generation: 0,
+            placed: [0; 2],
+            ready: [false; 2],
+            phase: BattlePhase::Seeding,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn phase(&self) -> BattlePhase {
+        self.phase
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn generations(&self) -> u32 {
+        self.generations
+    }
+
+    pub fn budget(&self) -> u32 {
+        self.budget
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    // Plays the battle by another rule. Throws once it has started, or if
+    // the rule can't be read.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        if self.phase != BattlePhase::Seeding {
+            return Err(JsValue::from_str(
+                "the rule can't change once the battle starts",
+            ));
+        }
+        self.rule = Rule::parse(rule).map_err(|err| JsValue::from_str(&err))?;
+        Ok(())
+    }
+
+    // Pointer to the board, one byte per cell: 0 for empty, otherwise the
+    // player whose cell it is.
+    pub fn cells(&self) -> *const u8 {
+        self.cells.as_ptr()
+    }
+
+    // Pointer to who last had each cell, laid out like `cells`.
+    pub fn territory_cells(&self) -> *const u8 {
+        self.territory.as_ptr()
+    }
+
+    // Places one of `player`'s cells. Throws if the battle has started, the
+    // player is ready, the cell is outside their half or already theirs, or
+    // they've used up their budget.
+    pub fn place(&mut self, player: Player, row: u32, column: u32) -> Result<(), JsValue> {
+        let idx = self
+            .seeding_cell(player, row, column)
+            .map_err(|err| JsValue::from_str(&err))?;
+        if self.cells[idx] != EMPTY {
+            return Err(JsValue::from_str(&format!(
+                "({}, {}) is already placed",
+                row, column
+            )));
+        }
+        if self.placed[Battle::slot(player)] >= self.budget {
+            return Err(JsValue::from_str(&format!(
+                "player {:?} has placed all {} cells",
+                player, self.budget
+            )));
+        }
+        self.cells[idx] = player as u8;
+        self.territory[idx] = player as u8;
+        self.placed[Battle::slot(player)] += 1;
+        Ok(())
+    }
+
+    // Takes back one of `player`'s cells, with the same checks as `place`.
+    pub fn remove(&mut self, player: Player, row: u32, column: u32) -> Result<(), JsValue> {
+        let idx = self
+            .seeding_cell(player, row, column)
+            .map_err(|err| JsValue::from_str(&err))?;
+        if self.cells[idx] == EMPTY {
+            return Err(JsValue::from_str(&format!(
+                "({}, {}) isn't placed",
+                row, column
+            )));
+        }
+        self.cells[idx] = EMPTY;
+        self.territory[idx] = EMPTY;
+        self.placed[Battle::slot(player)] -= 1;
+        Ok(())
+    }
+
+    // Cells `player` can still place.
+    pub fn remaining(&self, player: Player) -> u32 {
+        self.budget - self.placed[Battle::slot(player)]
+    }
+
+    pub fn is_ready(&self, player: Player) -> bool {
+        self.ready[Battle::slot(player)]
+    }
+
+    // Marks `player` as done seeding. Once both are, the battle starts.
+    pub fn set_ready(&mut self, player: Player) {
+        self.ready[Battle::slot(player)] = true;
+        if self.phase == BattlePhase::Seeding && self.ready == [true; 2] {
+            self.phase = if self.generations == 0 {
+                BattlePhase::Finished
+            } else {
+                BattlePhase::Running
+            };
+        }
+    }
+
+    // Plays up to `generations` more generations, stopping at the end of the
+    // battle, and returns how many were played. Throws if the battle hasn't
+    // started.
+    pub fn advance(&mut self, generations: u32) -> Result<u32, JsValue> {
+        if self.phase == BattlePhase::Seeding {
+            return Err(JsValue::from_str("both players have to be ready first"));
+        }
+        let mut played = 0;
+        while played < generations && self.phase == BattlePhase::Running {
+            self.step();
+            played += 1;
+        }
+        Ok(played)
+    }
+
+    // Plays out the rest of the battle.
+    pub fn finish(&mut self) -> Result<(), JsValue> {
+        self.advance(u32::MAX).map(|_| ())
+    }
+
+    // Live cells `player` has now.
+    pub fn population(&self, player: Player) -> u32 {
+        self.count(&self.cells, player)
+    }
+
+    // Cells `player` was the last to have, alive now or not.
+    pub fn territory(&self, player: Player) -> u32 {
+        self.count(&self.territory, player)
+    }
+
+    // Who won: the player with more live cells at the end, or with a tie,
+    // more territory. Nothing before the battle is over or if it's a draw.
+    pub fn winner(&self) -> Option<Player> {
+        if self.phase != BattlePhase::Finished {
+            return None;
+        }
+        let score = |player| (self.population(player), self.territory(player));
+        let (one, two) = (score(Player::One), score(Player::Two));
+        if one > two {
+            Some(Player::One)
+        } else if two > one {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// Runs a universe natively, without a browser, for trying out the engine from
+// the command line.
+//
+//     cargo run --features terminal --bin life -- pattern.rle -n 500
+//     cargo run --features terminal --bin life -- pattern.rle --animate
+
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use wasm_game_of_life::{Cell, Terminal, Universe};
+
+const USAGE: &str = "usage: life [options] [pattern.rle]
+
+Runs a pattern, or the default universe without one, and prints how it went.
+
+options:
+  -n, --generations <n>  generations to run, 100 by default
+  -s, --size <w>x<h>     size of the universe, with the pattern in the middle
+                         or else empty
+  -a, --animate          draw every generation in the terminal instead
+  -d, --delay <ms>       time between generations when animating, 100 by default
+  -h, --help             show this message";
+
+// Space left around a pattern on every side when no size is given.
+const MARGIN: u32 = 16;
+
+struct Options {
+    pattern: Option<String>,
+    generations: u32,
+    size: Option<(u32, u32)>,
+    animate: bool,
+    delay: u64,
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("life: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(err) = run(&options) {
+        eprintln!("life: {}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        pattern: None,
+        generations: 100,
+        size: None,
+        animate: false,
+        delay: 100,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("`{}` needs a value", arg))
+        };
+        match arg.as_str() {
+            "-n" | "--generations" => options.generations = number(&value()?)?,
+            "-s" | "--size" => {
+                let size = value()?;
+                let (width, height) = size
+                    .split_once('x')
+                    .ok_or_else(|| format!("expected a size like 64x48, not `{}`", size))?;
+                let (width, height) = (number(width)?, number(height)?);
+                if width == 0 || height == 0 {
+                    return Err(format!("expected a size like 64x48, not `{}`", size));
+                }
+                options.size = Some((width, height));
+            }
+            "-a" | "--animate" => options.animate = true,
+            "-d" | "--delay" => options.delay = number(&value()?)?.into(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            flag if flag.starts_with('-') => return Err(format!("unknown option `{}`", flag)),
+            _ if options.pattern.is_some() => {
+                return Err(String::from("only one pattern at a time"))
+            }
+            _ => options.pattern = Some(arg),
+        }
+    }
+
+    Ok(options)
+}
+
+fn number(text: &str) -> Result<u32, String> {
+    text.parse()
+        .map_err(|_| format!("expected a number, not `{}`", text))
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut universe = match &options.pattern {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+            let pattern = Universe::from_rle(&text).map_err(|err| format!("{}: {}", path, err))?;
+            let (width, height) = options
+                .size
+                .unwrap_or((pattern.width() + 2 * MARGIN, pattern.height() + 2 * MARGIN));
+            centered(&pattern, width, height)?
+        }
+        None => {
+            let mut universe = Universe::new();
+            // Without a pattern, a size means an empty universe to fill.
+            if let Some((width, height)) = options.size {
+                universe.set_width(width)?;
+                universe.set_height(height)?;
+            }
+            universe
+        }
+    };
+
+    if options.animate {
+        animate(&mut universe, options).map_err(|err| err.to_string())
+    } else {
+        print_stats(&mut universe, options.generations);
+        Ok(())
+    }
+}
+
+// A width x height universe, with the live cells of `pattern` in the middle
+// and ticking by its rule.
+fn centered(pattern: &Universe, width: u32, height: u32) -> Result<Universe, String> {
+    if width < pattern.width() || height < pattern.height() {
+        return Err(format!(
+            "a {}x{} pattern doesn't fit in {}x{}",
+            pattern.width(),
+            pattern.height(),
+            width,
+            height
+        ));
+    }
+
+    let top = (height - pattern.height()) / 2;
+    let left = (width - pattern.width()) / 2;
+    let alive: Vec<(u32, u32)> = pattern
+        .get_cells()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &cell)| cell == Cell::Alive)
+        .map(|(idx, _)| {
+            let idx = idx as u32;
+            (top + idx / pattern.width(), left + idx % pattern.width())
+        })
+        .collect();
+
+    let mut universe = Universe::new();
+    universe.set_width(width)?;
+    universe.set_height(height)?;
+    universe
+        .set_rule(&pattern.rule())
+        .expect("a universe's own rule always reads back");
+    universe.set_cells(&alive)?;
+    Ok(universe)
+}
+
+fn animate(universe: &mut Universe, options: &Options) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut terminal = Terminal::new(stdout.lock());
+
+    for _ in 0..options.generations {
+        terminal.draw(universe)?;
+        universe.tick();
+        thread::sleep(Duration::from_millis(options.delay));
+    }
+    terminal.draw(universe)
+}
+
+fn print_stats(universe: &mut Universe, generations: u32) {
+    let start_population = universe.population();
+    let (mut min, mut max) = (start_population, start_population);
+
+    let start = Instant::now();
+    for _ in 0..generations {
+        universe.tick();
+        let population = universe.population();
+        min = min.min(population);
+        max = max.max(population);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "universe    {}x{}, {}",
+        universe.width(),
+        universe.height(),
+        universe.rule()
+    );
+    println!("generation  {}", universe.generation());
+    println!(
+        "population  {} at the start, {} at the end, {} to {} in between",
+        start_population,
+        universe.population(),
+        min,
+        max
+    );
+    if generations > 0 {
+        println!(
+            "time        {:.1} ms, {:.3} ms per generation",
+            elapsed.as_secs_f64() * 1000.0,
+            elapsed.as_secs_f64() * 1000.0 / generations as f64
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::Universe;
+
+// The size of wasm memory when `buffer_info` last looked, and how many times
+// it had grown by then.
+static LAST_MEMORY_BYTES: AtomicUsize = AtomicUsize::new(0);
+static MEMORY_EPOCH: AtomicU32 = AtomicU32::new(0);
+
+// Where one buffer lives in wasm memory and how to view it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Buffer {
+    ptr: usize,
+    len: usize,
+    element_size: usize,
+    // The typed array to view it with.
+    #[serde(rename = "type")]
+    array_type: &'static str,
+    layout: &'static str,
+}
+
+impl Buffer {
+    fn new<T>(buffer: &[T], array_type: &'static str, layout: &'static str) -> Buffer {
+        Buffer {
+            ptr: buffer.as_ptr() as usize,
+            len: buffer.len(),
+            element_size: std::mem::size_of::<T>(),
+            array_type,
+            layout,
+        }
+    }
+}
+
+// Everything `buffer_info` hands to JavaScript.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferInfo {
+    width: u32,
+    height: u32,
+    cells: Buffer,
+    ages: Buffer,
+    heat: Buffer,
+    pixels: Buffer,
+    memory_bytes: usize,
+    memory_epoch: u32,
+}
+
+#[cfg(target_arch = "wasm32")]
+fn memory_bytes() -> usize {
+    core::arch::wasm32::memory_size(0) * 65536
+}
+
+// Native builds have no wasm memory to grow.
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_bytes() -> usize {
+    0
+}
+
+// The number of times wasm memory has been seen to grow.
+fn memory_epoch() -> u32 {
+    let bytes = memory_bytes();
+    if LAST_MEMORY_BYTES.swap(bytes, Ordering::Relaxed) != bytes {
+        MEMORY_EPOCH.fetch_add(1, Ordering::Relaxed);
+    }
+    MEMORY_EPOCH.load(Ordering::Relaxed)
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Where every buffer JavaScript can view lives in wasm memory, as
+    // `{ width, height, cells, ages, heat, pixels, memoryBytes, memoryEpoch }`.
+    // Each buffer is a `{ ptr, len, elementSize, type, layout }` object, with
+    // `len` in elements and `type` the typed array to view it with, e.g.
+    // `new Uint32Array(memory.buffer, ages.ptr, ages.len)`. `pixels` is the
+    // last RGBA rendering, empty until something is rendered.
+    //
+    // Views stop working when wasm memory grows, and the buffers move when
+    // the universe is resized. `memoryEpoch` goes up whenever memory has grown
+    // since the last call, so keep it alongside the views and rebuild them
+    // when it changes.
+    pub fn buffer_info(&self) -> Result<JsValue, JsValue> {
+        let info = BufferInfo {
+            width: self.width,
+            height: self.height,
+            cells: Buffer::new(
+                &self.cells,
+                "Uint8Array",
+                "row-major, 0 dead, 1 alive, 2 wall",
+            ),
+            ages: Buffer::new(&self.ages, "Uint32Array", "row-major"),
+            heat: Buffer::new(&self.heat, "Uint8Array", "row-major"),
+            pixels: Buffer::new(&self.pixels, "Uint8ClampedArray", "row-major RGBA"),
+            memory_bytes: memory_bytes(),
+            memory_epoch: memory_epoch(),
+        };
+
+        Ok(serde_wasm_bindgen::to_value(&info)?)
+    }
+}
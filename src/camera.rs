@@ -0,0 +1,118 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+// How the viewport tracks the live cells after each tick.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FollowMode {
+    // The viewport only moves when told to.
+    None = 0,
+    // Centers the viewport on the average position of the live cells.
+    CenterOfMass = 1,
+    // Centers the viewport on the smallest box holding every live cell.
+    BoundingBox = 2,
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Recenters the viewport on the live cells according to the follow mode.
+    // Leaves it where it is when nothing is alive.
+    pub(crate) fn follow_camera(&mut self) {
+        if self.camera_follow == FollowMode::None {
+            return;
+        }
+
+        let mut rows = vec![0; self.height as usize];
+        let mut cols = vec![0; self.width as usize];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells[self.get_index(row, col)] == Cell::Alive {
+                    rows[row as usize] += 1;
+                    cols[col as usize] += 1;
+                }
+            }
+        }
+
+        let center = match self.camera_follow {
+            FollowMode::None => None,
+            FollowMode::CenterOfMass => circular_mean(&rows).zip(circular_mean(&cols)),
+            FollowMode::BoundingBox => span_center(&rows).zip(span_center(&cols)),
+        };
+
+        if let Some((row, col)) = center {
+            let origin_row = row - self.visible_rows() as f64 / 2.0;
+            let origin_col = col - self.visible_columns() as f64 / 2.0;
+            self.viewport.origin_row =
+                (origin_row.round() as i64).rem_euclid(self.height as i64) as u32;
+            self.viewport.origin_col =
+                (origin_col.round() as i64).rem_euclid(self.width as i64) as u32;
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn camera_follow(&self) -> FollowMode {
+        self.camera_follow
+    }
+
+    // Makes the viewport follow the live cells around every tick, e.g. to keep
+    // a lone spaceship on screen. Takes effect straight away.
+    pub fn set_camera_follow(&mut self, mode: FollowMode) {
+        self.camera_follow = mode;
+        self.follow_camera();
+    }
+}
+
+// The average of the positions 0..counts.len(), each weighted by its count,
+// treating the positions as a circle so clusters straddling the edge of the
+// universe average to the edge rather than its middle.
+fn circular_mean(counts: &[u32]) -> Option<f64> {
+    if counts.iter().all(|&count| count == 0) {
+        return None;
+    }
+
+    let n = counts.len() as f64;
+    let (mut x, mut y) = (0.0, 0.0);
+    for (i, &count) in counts.iter().enumerate() {
+        let angle = 2.0 * PI * i as f64 / n;
+        x += count as f64 * angle.cos();
+        y += count as f64 * angle.sin();
+    }
+
+    let angle = y.atan2(x).rem_euclid(2.0 * PI);
+    Some(angle / (2.0 * PI) * n)
+}
+
+// The middle of the shortest stretch of positions, wrapping around the end,
+// that covers every non-zero count. That is everything but the longest run of
+// zeros.
+fn span_center(counts: &[u32]) -> Option<f64> {
+    let n = counts.len();
+    let first = counts.iter().position(|&count| count != 0)?;
+
+    // Walk once round the circle starting from an occupied position, so every
+    // run of zeros is seen in one piece.
+    let (mut gap_start, mut gap_len) = (first, 0);
+    let mut run = 0;
+    for step in 1..=n {
+        let i = (first + step) % n;
+        if counts[i] == 0 {
+            run += 1;
+        } else {
+            if run > gap_len {
+                gap_start = (i + n - run) % n;
+                gap_len = run;
+            }
+            run = 0;
+        }
+    }
+
+    let start = (gap_start + gap_len) % n;
+    let span = n - gap_len;
+    Some(start as f64 + (span - 1) as f64 / 2.0)
+}
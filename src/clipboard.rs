@@ -0,0 +1,46 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::Clipboard;
+
+use crate::Universe;
+
+fn clipboard() -> Result<Clipboard, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window to copy from"))?;
+    Ok(window.navigator().clipboard())
+}
+
+async fn read_pattern() -> Result<JsValue, JsValue> {
+    let text = JsFuture::from(clipboard()?.read_text()).await?;
+    let text = text.as_string().unwrap_or_default();
+    Universe::from_rle(&text)
+        .map(JsValue::from)
+        .map_err(|err| JsValue::from_str(&format!("the clipboard doesn't hold a pattern: {}", err)))
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Copies the selected cells to the system clipboard as RLE, which Golly
+    // and most other Life programs can paste. Returns the clipboard's promise,
+    // which rejects if the page isn't allowed to write to it. Throws if
+    // nothing is selected.
+    pub fn copy_selection_to_clipboard(&self) -> Result<js_sys::Promise, JsValue> {
+        let selection = self
+            .selection
+            .ok_or_else(|| JsValue::from_str("nothing is selected"))?;
+        Ok(clipboard()?.write_text(&self.region_rle(selection)))
+    }
+
+    // Reads a pattern in RLE from the system clipboard, e.g. one copied in
+    // Golly. Returns a promise of the pattern as a universe of its own, for
+    // passing to `paste`, since the universe being pasted into can't be held
+    // on to while waiting for the clipboard:
+    //
+    //     universe.paste(await Universe.paste_pattern_from_clipboard(), row, col);
+    //
+    // Rejects if the page isn't allowed to read the clipboard or what's there
+    // isn't a pattern.
+    pub fn paste_pattern_from_clipboard() -> js_sys::Promise {
+        future_to_promise(read_pattern())
+    }
+}
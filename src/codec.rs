@@ -0,0 +1,40 @@
+// URL-safe base64 without padding, for putting binary data in text like save
+// slots and links.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+pub(crate) fn decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut group, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        let value = ALPHABET
+            .iter()
+            .position(|&letter| letter == c)
+            .ok_or_else(|| format!("`{}` isn't base64", c as char))?;
+        // Only the bits not yet written out are kept.
+        group = (group << 6 | value as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((group >> bits) as u8);
+        }
+    }
+    // A lone character left over holds too few bits for a byte.
+    if bits == 6 {
+        return Err(String::from("the base64 is cut short"));
+    }
+    Ok(out)
+}
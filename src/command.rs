@@ -0,0 +1,107 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Pattern, Universe};
+
+const HELP: &str = "commands:
+  tick [n]                          run n generations, 1 by default
+  place <pattern> <row> <col> [dir] place glider, pulsar or gun, facing se, sw, nw or ne
+  fill <row> <col> <row> <col>      bring a rectangle of cells to life
+  toggle <row> <col>                flip a single cell
+  wall <row> <col> <row> <col>      build walls over a rectangle of cells
+  unwall <row> <col> <row> <col>    take the walls in a rectangle down
+  clear                             kill every cell but the walls
+  rule [rule]                       show or change the rule, like B36/S23
+  population                        count the live cells
+  generation                        show the generation number";
+
+// Methods not being exported to Javascript
+impl Universe {
+    fn run_command(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["help"] => Ok(String::from(HELP)),
+            ["tick"] => self.run_command(&["tick", "1"]),
+            ["tick", n] => {
+                let n = number(n)?;
+                for _ in 0..n {
+                    self.tick();
+                }
+                Ok(format!("generation {}", self.generation))
+            }
+            ["place", pattern, row, col] => self.run_command(&["place", pattern, row, col, "se"]),
+            ["place", pattern, row, col, direction] => {
+                let pattern = match *pattern {
+                    "glider" => Pattern::Glider,
+                    "pulsar" => Pattern::Pulsar,
+                    "gun" => Pattern::GosperGliderGun,
+                    other => return Err(format!("unknown pattern `{}`", other)),
+                };
+                // Patterns are drawn heading south east, and each clockwise
+                // quarter turn moves that round by one.
+                let rotation = match *direction {
+                    "se" => 0,
+                    "sw" => 1,
+                    "nw" => 2,
+                    "ne" => 3,
+                    other => return Err(format!("unknown direction `{}`", other)),
+                };
+                self.stamp(pattern, number(row)?, number(col)?, rotation);
+                Ok(String::from("ok"))
+            }
+            ["fill", row1, col1, row2, col2] => {
+                self.fill(number(row1)?, number(col1)?, number(row2)?, number(col2)?);
+                Ok(String::from("ok"))
+            }
+            ["wall", row1, col1, row2, col2] => {
+                self.fill_walls(number(row1)?, number(col1)?, number(row2)?, number(col2)?);
+                Ok(String::from("ok"))
+            }
+            ["unwall", row1, col1, row2, col2] => {
+                self.clear_walls(number(row1)?, number(col1)?, number(row2)?, number(col2)?);
+                Ok(String::from("ok"))
+            }
+            ["toggle", row, col] => {
+                let (row, col) = (number(row)?, number(col)?);
+                if row >= self.height || col >= self.width {
+                    return Err(format!("({}, {}) is outside the universe", row, col));
+                }
+                self.toggle(row, col);
+                Ok(String::from("ok"))
+            }
+            ["clear"] => {
+                self.kill_universe();
+                Ok(String::from("ok"))
+            }
+            ["rule"] => Ok(self.rule.to_string()),
+            ["rule", rule] => {
+                self.set_rule_text(rule)?;
+                Ok(self.rule.to_string())
+            }
+            ["population"] => Ok(self.population().to_string()),
+            ["generation"] => Ok(self.generation.to_string()),
+            [] => Err(String::from("no command given, try `help`")),
+            [command, ..] => Err(format!("can't make sense of `{}`, try `help`", command)),
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Runs a command from a tiny command language, like "place glider 10 10
+    // se", "tick 50", "fill 0 0 10 10" or "rule B36/S23", and returns what it
+    // printed. Errors come back as text starting with "error:" rather than
+    // being thrown, ready to show in a console panel. "help" lists every
+    // command.
+    pub fn execute(&mut self, command: &str) -> String {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match self.run_command(&words) {
+            Ok(output) => output,
+            Err(err) => format!("error: {}", err),
+        }
+    }
+}
+
+fn number(word: &str) -> Result<u32, String> {
+    word.parse()
+        .map_err(|_| format!("expected a number, not `{}`", word))
+}
@@ -0,0 +1,68 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Condition {
+    PopulationAbove(u32),
+    PopulationBelow(u32),
+    CellAlive(u32, u32),
+    Generation(u32),
+}
+
+// Something to watch for while the simulation runs, like a breakpoint. Build
+// one with the constructors and add it with
+// `Simulation::add_pause_condition`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PauseCondition {
+    condition: Condition,
+}
+
+// Methods not being exported to Javascript
+impl PauseCondition {
+    pub(crate) fn is_met(&self, universe: &Universe) -> bool {
+        match self.condition {
+            Condition::PopulationAbove(n) => universe.population() > n,
+            Condition::PopulationBelow(n) => universe.population() < n,
+            Condition::CellAlive(row, col) => {
+                row < universe.height()
+                    && col < universe.width()
+                    && universe.get_cells()[(row * universe.width() + col) as usize] == Cell::Alive
+            }
+            Condition::Generation(n) => universe.generation() == n,
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl PauseCondition {
+    // Met when more than `n` cells are alive.
+    pub fn population_above(n: u32) -> PauseCondition {
+        PauseCondition {
+            condition: Condition::PopulationAbove(n),
+        }
+    }
+
+    // Met when fewer than `n` cells are alive.
+    pub fn population_below(n: u32) -> PauseCondition {
+        PauseCondition {
+            condition: Condition::PopulationBelow(n),
+        }
+    }
+
+    // Met when the cell at (row, column) is alive.
+    pub fn cell_alive(row: u32, column: u32) -> PauseCondition {
+        PauseCondition {
+            condition: Condition::CellAlive(row, column),
+        }
+    }
+
+    // Met at generation `n`.
+    pub fn generation(n: u32) -> PauseCondition {
+        PauseCondition {
+            condition: Condition::Generation(n),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::random::Random;
+use crate::rle::parse_rle;
+use crate::rule::Rule;
+use crate::{check_size, utils, Cell, Edge, NutrientOptions, Universe};
+
+#[wasm_bindgen(typescript_custom_section)]
+const UNIVERSE_CONFIG: &str = r#"
+export type Boundary = "wrap" | "bounded" | "flip";
+
+export interface NutrientConfig {
+    level?: number;
+    birthCost?: number;
+    survivalMin?: number;
+    diffusion?: number;
+    regrowth?: number;
+    capacity?: number;
+}
+
+export interface UniverseConfig {
+    width?: number;
+    height?: number;
+    rule?: string;
+    boundary?: Boundary | [Boundary, Boundary];
+    wallsAlive?: boolean;
+    nutrients?: NutrientConfig;
+    seed?: number;
+    density?: number;
+    initialPattern?: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    // The object `with_config` takes, typed as the interface above.
+    #[wasm_bindgen(typescript_type = "UniverseConfig")]
+    pub type UniverseConfig;
+}
+
+// What happens at the edges of the universe: one edge for all of them, or
+// one for the left and right edges and another for the top and bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum Boundary {
+    All(Edge),
+    Each(Edge, Edge),
+}
+
+impl From<Boundary> for Topology {
+    fn from(boundary: Boundary) -> Self {
+        let (horizontal, vertical) = match boundary {
+            Boundary::All(edge) => (edge, edge),
+            Boundary::Each(horizontal, vertical) => (horizontal, vertical),
+        };
+        Topology {
+            horizontal: horizontal.into(),
+            vertical: vertical.into(),
+        }
+    }
+}
+
+// A nutrient field to lay under the cells: the level it starts at
+// everywhere, full to capacity unless given, and the nutrient options.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct NutrientConfig {
+    level: Option<f32>,
+    birth_cost: f32,
+    survival_min: f32,
+    diffusion: f32,
+    regrowth: f32,
+    capacity: f32,
+}
+
+impl Default for NutrientConfig {
+    fn default() -> Self {
+        let [birth_cost, survival_min, diffusion, regrowth, capacity] =
+            NutrientOptions::default().to_array();
+        NutrientConfig {
+            level: None,
+            birth_cost,
+            survival_min,
+            diffusion,
+            regrowth,
+            capacity,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    #[serde(default = "default_size")]
+    width: u32,
+    #[serde(default = "default_size")]
+    height: u32,
+    rule: Option<String>,
+    #[serde(default = "default_boundary")]
+    boundary: Boundary,
+    #[serde(default)]
+    walls_alive: bool,
+    nutrients: Option<NutrientConfig>,
+    seed: Option<u64>,
+    #[serde(default)]
+    density: f64,
+    initial_pattern: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: default_size(),
+            height: default_size(),
+            rule: None,
+            boundary: default_boundary(),
+            walls_alive: false,
+            nutrients: None,
+            seed: None,
+            density: 0.0,
+            initial_pattern: None,
+        }
+    }
+}
+
+fn default_size() -> u32 {
+    100
+}
+
+fn default_boundary() -> Boundary {
+    Boundary::All(Edge::Wrap)
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    fn from_config(config: Config) -> Result<Universe, String> {
+        let Config {
+            width,
+            height,
+            rule,
+            boundary,
+            walls_alive,
+            nutrients,
+            seed,
+            density,
+            initial_pattern,
+        } = config;
+        check_size(width, height)?;
+        if !(0.0..=1.0).contains(&density) {
+            return Err(format!("the density must be from 0 to 1, not {}", density));
+        }
+
+        // The same seed always fills the universe the same way.
+        let mut random = seed.map_or_else(Random::new, Random::seeded);
+        let mut cells: Vec<Cell> = (0..width as usize * height as usize)
+            .map(|_| {
+                if random.next_f64() < density {
+                    Cell::Alive
+                } else {
+                    Cell::Dead
+                }
+            })
+            .collect();
+
+        // The pattern goes in the middle, over the top of any random cells,
+        // and brings its rule with it unless one was given.
+        let mut pattern_rule = None;
+        if let Some(text) = initial_pattern {
+            let pattern = parse_rle(&text)?;
+            if pattern.width > width || pattern.height > height {
+                return Err(format!(
+                    "the {} by {} pattern doesn't fit in a {} by {} universe",
+                    pattern.width, pattern.height, width, height
+                ));
+            }
+            let top = (height - pattern.height) / 2;
+            let left = (width - pattern.width) / 2;
+            for (row, col) in pattern.alive {
+                cells[((top + row) * width + left + col) as usize] = Cell::Alive;
+            }
+            pattern_rule = Some(pattern.rule);
+        }
+
+        let mut universe = Universe::with_cells(width, height, cells);
+        universe.rule = match rule {
+            Some(rule) => Rule::parse(&rule)?,
+            None => pattern_rule.unwrap_or_default(),
+        };
+        universe.topology = boundary.into();
+        universe.walls_alive = walls_alive;
+        if let Some(nutrients) = nutrients {
+            universe.nutrient_options = NutrientOptions::new(
+                nutrients.birth_cost,
+                nutrients.survival_min,
+                nutrients.diffusion,
+                nutrients.regrowth,
+                nutrients.capacity,
+            );
+            universe.enable_nutrients(nutrients.level.unwrap_or(nutrients.capacity));
+        }
+        Ok(universe)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Builds a universe from a `UniverseConfig`, with every field optional:
+    //
+    // - `width` and `height`, 100 each by default.
+    // - `rule`, like "B3/S23", which is the default.
+    // - `boundary`, what's past the edges: "wrap" for cells on one edge to
+    //   neighbor those on the other, the default, "bounded" for dead cells or
+    //   "flip" to wrap with a twist. A pair like ["flip", "wrap"] gives the
+    //   left and right edges, then the top and bottom, as in `set_topology`.
+    // - `wallsAlive`, whether walls count as live neighbors, false by
+    //   default.
+    // - `nutrients`, to lay a nutrient field under the cells: the `level` it
+    //   starts at, full to `capacity` by default, and any of the other
+    //   nutrient options, which default as in `NutrientOptions`.
+    // - `density`, the chance of each cell starting alive, 0 by default.
+    // - `seed`, so the same seed always gives the same random cells.
+    // - `initialPattern`, a pattern in RLE placed in the middle. Its rule is
+    //   used when there's no `rule`.
+    //
+    // Throws if a field has the wrong type or is out of range, or the pattern
+    // can't be read or doesn't fit.
+    pub fn with_config(config: UniverseConfig) -> Result<Universe, JsValue> {
+        utils::set_panic_hook();
+
+        let config: Config = serde_wasm_bindgen::from_value(config.into())?;
+        Universe::from_config(config).map_err(|err| JsValue::from_str(&err))
+    }
+}
+
+// Builds a universe one option at a time, for JavaScript like
+// `new UniverseBuilder().width(64).height(48).rule("B36/S23").build()`. Every
+// setter hands back the builder, so calls chain; options left unset have the
+// same defaults as in `with_config`.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct UniverseBuilder {
+    config: Config,
+}
+
+#[wasm_bindgen]
+impl UniverseBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> UniverseBuilder {
+        UniverseBuilder::default()
+    }
+
+    pub fn width(mut self, width: u32) -> UniverseBuilder {
+        self.config.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: u32) -> UniverseBuilder {
+        self.config.height = height;
+        self
+    }
+
+    // The rule, like "B3/S23". Checked by `build`.
+    pub fn rule(mut self, rule: String) -> UniverseBuilder {
+        self.config.rule = Some(rule);
+        self
+    }
+
+    // What's past the left and right edges, then the top and bottom, as in
+    // `Universe.set_topology`. Both wrap by default.
+    pub fn topology(mut self, horizontal: Edge, vertical: Edge) -> UniverseBuilder {
+        self.config.boundary = Boundary::Each(horizontal, vertical);
+        self
+    }
+
+    // Whether walls count as live neighbors. They don't by default.
+    pub fn walls_alive(mut self, alive: bool) -> UniverseBuilder {
+        self.config.walls_alive = alive;
+        self
+    }
+
+    // Lays a nutrient field under the cells, at `level` everywhere, which
+    // they have to live off as `options` say.
+    pub fn nutrients(mut self, level: f32, options: &NutrientOptions) -> UniverseBuilder {
+        let [birth_cost, survival_min, diffusion, regrowth, capacity] = options.to_array();
+        self.config.nutrients = Some(NutrientConfig {
+            level: Some(level),
+            birth_cost,
+            survival_min,
+            diffusion,
+            regrowth,
+            capacity,
+        });
+        self
+    }
+
+    // Makes the random cells the same every time for the same seed. Takes a
+    // plain number rather than a BigInt, with any fraction dropped, so it
+    // matches a `seed` in the config.
+    pub fn seed(mut self, seed: f64) -> UniverseBuilder {
+        self.config.seed = Some(seed as u64);
+        self
+    }
+
+    // The chance of each cell starting alive, from 0 to 1.
+    pub fn density(mut self, density: f64) -> UniverseBuilder {
+        self.config.density = density;
+        self
+    }
+
+    // A pattern in RLE to place in the middle. Checked by `build`.
+    pub fn pattern(mut self, rle: String) -> UniverseBuilder {
+        self.config.initial_pattern = Some(rle);
+        self
+    }
+
+    // Builds the universe. The builder is left as it was, so it can build
+    // more. Throws if an option is out of range, or the rule or pattern can't
+    // be read.
+    pub fn build(&self) -> Result<Universe, JsValue> {
+        utils::set_panic_hook();
+
+        Universe::from_config(self.config.clone()).map_err(|err| JsValue::from_str(&err))
+    }
+}
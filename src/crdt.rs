@@ -0,0 +1,180 @@
+use wasm_bindgen::prelude::*;
+
+use crate::sync::{put_varint, put_walls, Reader};
+use crate::{Cell, Universe};
+
+// When a cell was last written and by whom. Later Lamport times win, and
+// ties go to the higher replica id, so every replica picks the same winner
+// whatever order the writes arrive in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Stamp {
+    clock: u64,
+    replica: u32,
+}
+
+// Per-cell last-writer-wins state for peer-to-peer editing.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Replica {
+    pub(crate) id: u32,
+    // Lamport clock: above every stamp seen so far, local or remote.
+    clock: u64,
+    // The winning write for each cell, empty until the first exchange.
+    stamps: Vec<Stamp>,
+    // Cells as of the last ops sent or merged, to spot local changes.
+    base: Vec<Cell>,
+}
+
+impl Replica {
+    pub(crate) fn new(id: u32) -> Replica {
+        Replica {
+            id,
+            ..Replica::default()
+        }
+    }
+
+    // Forgets every stamp, e.g. after a resize changes what the cell indexes
+    // mean.
+    pub(crate) fn clear(&mut self) {
+        self.stamps.clear();
+        self.base.clear();
+    }
+
+    fn fit(&mut self, len: usize) {
+        if self.stamps.len() != len {
+            self.stamps = vec![Stamp::default(); len];
+            self.base = vec![Cell::Dead; len];
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    fn merge_ops(&mut self, ops: &[u8]) -> Result<u32, String> {
+        let mut reader = Reader { bytes: ops };
+        let replica = reader.varint()?;
+        if replica == self.replica.id {
+            return Err(format!(
+                "the ops are from replica {}, which is this one's id, so pick a different one",
+                replica
+            ));
+        }
+        let clock = reader.varint64()?;
+        let len = reader.varint()? as usize;
+        if len != self.cells.len() {
+            return Err(String::from(
+                "the ops are for a universe of a different size",
+            ));
+        }
+
+        // Read everything before touching any cells, so a broken message
+        // changes nothing.
+        let count = reader.varint()?;
+        let mut writes = Vec::with_capacity(count as usize);
+        let mut next = 0usize;
+        for _ in 0..count {
+            let entry = reader.varint()?;
+            let idx = next + (entry >> 1) as usize;
+            if idx >= len {
+                return Err(format!("cell {} is outside the universe", idx));
+            }
+            let cell = if entry & 1 == 1 {
+                Cell::Alive
+            } else {
+                Cell::Dead
+            };
+            writes.push((idx, cell));
+            next = idx + 1;
+        }
+        for idx in reader.walls(len)? {
+            writes.push((idx, Cell::Wall));
+        }
+
+        let stamp = Stamp { clock, replica };
+        self.replica.fit(len);
+        self.replica.clock = self.replica.clock.max(clock);
+        self.previous.clone_from(&self.cells);
+        self.previous_valid = false;
+
+        let mut applied = 0;
+        for (idx, cell) in writes {
+            if stamp > self.replica.stamps[idx] {
+                self.replica.stamps[idx] = stamp;
+                self.replica.base[idx] = cell;
+                self.set_cell(idx, cell);
+                applied += 1;
+            }
+        }
+        if applied > 0 {
+            self.record_snapshot();
+        }
+        Ok(applied)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The id that breaks ties between writes made at the same Lamport time.
+    // Random to begin with; peers must each have a different one.
+    pub fn replica_id(&self) -> u32 {
+        self.replica.id
+    }
+
+    pub fn set_replica_id(&mut self, id: u32) {
+        self.replica.id = id;
+    }
+
+    // Every cell changed since ops were last sent or merged, whether by
+    // editing or ticking, as one message stamped with the next Lamport time
+    // and this replica's id. Empty when nothing changed. Send it to every
+    // peer, who pass it to `merge_remote_ops`; the transport is up to the
+    // page, e.g. a WebRTC data channel.
+    pub fn local_ops(&mut self) -> Vec<u8> {
+        let len = self.cells.len();
+        self.replica.fit(len);
+
+        let (walls, changed): (Vec<usize>, Vec<usize>) = (0..len)
+            .filter(|&idx| self.cells[idx] != self.replica.base[idx])
+            .partition(|&idx| self.cells[idx] == Cell::Wall);
+        if changed.is_empty() && walls.is_empty() {
+            return Vec::new();
+        }
+
+        self.replica.clock += 1;
+        let stamp = Stamp {
+            clock: self.replica.clock,
+            replica: self.replica.id,
+        };
+
+        let mut out = Vec::new();
+        put_varint(&mut out, stamp.replica);
+        put_varint(&mut out, stamp.clock);
+        put_varint(&mut out, len as u32);
+        put_varint(&mut out, changed.len() as u32);
+        let mut next = 0;
+        for idx in changed {
+            let cell = self.cells[idx];
+            put_varint(&mut out, ((idx - next) as u32) << 1 | cell as u32);
+            self.replica.stamps[idx] = stamp;
+            self.replica.base[idx] = cell;
+            next = idx + 1;
+        }
+        put_walls(&mut out, &walls);
+        for idx in walls {
+            self.replica.stamps[idx] = stamp;
+            self.replica.base[idx] = Cell::Wall;
+        }
+        out
+    }
+
+    // Merges ops from another replica's `local_ops`. Each cell keeps whichever
+    // write has the latest Lamport time, with the replica id breaking ties,
+    // so peers that have seen the same ops agree on every cell whatever order
+    // they arrived in. Local changes not yet sent lose to any op for the same
+    // cell, so call `local_ops` first. Returns how many cells the ops won.
+    // Throws if the message can't be read, is for a universe of a different
+    // size, or carries this replica's own id.
+    pub fn merge_remote_ops(&mut self, ops: &[u8]) -> Result<u32, JsValue> {
+        self.merge_ops(ops).map_err(|err| JsValue::from_str(&err))
+    }
+}
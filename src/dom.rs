@@ -0,0 +1,145 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{CustomEvent, CustomEventInit, EventTarget};
+
+use crate::events::Notification;
+use crate::simulation::CycleDetector;
+use crate::{Pattern, Universe};
+
+// The DOM target the universe's events are dispatched on.
+pub(crate) struct EventBinding {
+    target: EventTarget,
+    cycles: CycleDetector,
+    // Whether the last generation was part of a cycle, so "stable" is only
+    // dispatched once for each time the universe settles.
+    stable: bool,
+}
+
+// Builds a plain object from number fields, for an event's detail.
+fn detail(fields: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let detail = js_sys::Object::new();
+    for (key, value) in fields {
+        js_sys::Reflect::set(&detail, &JsValue::from_str(key), value)?;
+    }
+    Ok(detail.into())
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Dispatches a CustomEvent called `name` on the bound target, if there is
+    // one. The detail is only built when there's somewhere to send it.
+    // Listeners can't stop what dispatched the event, so anything that goes
+    // wrong is logged and otherwise ignored.
+    fn dispatch(&mut self, name: &str, fields: impl FnOnce() -> Vec<(&'static str, JsValue)>) {
+        let target = match &self.events {
+            Some(binding) => binding.target.clone(),
+            None => return,
+        };
+
+        let event = detail(&fields()).and_then(|detail| {
+            let init = CustomEventInit::new();
+            init.set_detail(&detail);
+            CustomEvent::new_with_event_init_dict(name, &init)
+        });
+        match event {
+            Ok(event) => self.notify(Notification::Event(target, event)),
+            Err(err) => crate::utils::log_error(&err),
+        }
+    }
+
+    // Dispatches "tick" after every generation, and "extinct" or "stable"
+    // when the generation just reached is the first with no live cells or
+    // the first to repeat one of the last few, with the length of the cycle.
+    pub(crate) fn dispatch_tick_events(&mut self) {
+        if self.events.is_none() {
+            return;
+        }
+        let (population, births, deaths) = self.tick_counts();
+        let hash = self.cells_hash();
+        let stable = match &mut self.events {
+            Some(binding) => {
+                let period = binding.cycles.push(hash).filter(|_| population > 0);
+                let newly = period.filter(|_| !binding.stable);
+                binding.stable = period.is_some();
+                newly
+            }
+            None => return,
+        };
+
+        let generation = JsValue::from(self.generation);
+        self.dispatch("tick", || {
+            vec![
+                ("generation", generation.clone()),
+                ("population", population.into()),
+                ("births", births.into()),
+                ("deaths", deaths.into()),
+            ]
+        });
+        if population == 0 && deaths > 0 {
+            self.dispatch("extinct", || vec![("generation", generation.clone())]);
+        }
+        if let Some(period) = stable {
+            self.dispatch("stable", || {
+                vec![
+                    ("generation", generation.clone()),
+                    ("period", period.into()),
+                ]
+            });
+        }
+    }
+
+    // Dispatches "patternPlaced" for a pattern just brought to life at
+    // (row, column).
+    pub(crate) fn dispatch_pattern_placed(
+        &mut self,
+        pattern: Pattern,
+        row: u32,
+        column: u32,
+        rotation: u32,
+    ) {
+        let name = match pattern {
+            Pattern::Glider => "glider",
+            Pattern::Pulsar => "pulsar",
+            Pattern::GosperGliderGun => "gosperGliderGun",
+        };
+        self.dispatch("patternPlaced", || {
+            vec![
+                ("pattern", name.into()),
+                ("row", row.into()),
+                ("column", column.into()),
+                ("rotation", rotation.into()),
+            ]
+        });
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Dispatches the universe's events on `target` as CustomEvents, so pages
+    // can use `addEventListener` rather than callback setters:
+    //
+    // - "tick" after every generation, with `{ generation, population,
+    //   births, deaths }` as its detail.
+    // - "extinct" when the last live cell dies, with `{ generation }`.
+    // - "stable" when a generation repeats one of the last 64, once until the
+    //   cycle is broken, with `{ generation, period }` where period is the
+    //   length of the cycle, 1 for a still life.
+    // - "patternPlaced" when a pattern is stamped, with `{ pattern, row,
+    //   column, rotation }`.
+    //
+    // Listeners run while the universe is still busy, so calling back into it
+    // from one throws. Inside a Simulation the events are held back until the
+    // frame's generations are done, so listeners are free to call back into
+    // the simulation. Replaces any target bound before.
+    pub fn bind_event_target(&mut self, target: EventTarget) {
+        self.events = Some(EventBinding {
+            target,
+            cycles: CycleDetector::default(),
+            stable: false,
+        });
+    }
+
+    pub fn unbind_event_target(&mut self) {
+        self.events = None;
+    }
+}
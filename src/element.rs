@@ -0,0 +1,290 @@
+use std::cell::{Cell as StdCell, RefCell};
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{Element, HtmlCanvasElement, ImageData};
+
+use crate::formats::parse_pattern;
+use crate::random::Random;
+use crate::rule::Rule;
+use crate::{check_size, Cell, Pattern, Simulation, Universe};
+
+// The tag the element is registered under.
+const TAG: &str = "game-of-life";
+
+// Attributes the element reacts to, and what it falls back on without them.
+const ATTRIBUTES: [&str; 5] = ["width", "height", "rule", "speed", "pattern"];
+const DEFAULT_SIZE: u32 = 64;
+const DEFAULT_SPEED: f64 = 10.0;
+
+// Where each element keeps the id of its state in `ELEMENTS`.
+const ID_PROPERTY: &str = "__gameOfLifeId";
+
+// Defines the element's class. Custom elements have to be classes extending
+// HTMLElement, which can't be made from Rust, so this is the one bit of
+// JavaScript; everything the class does is handed straight back to Rust.
+#[wasm_bindgen(inline_js = r#"
+export function define_element(tag, observed, connected, disconnected, changed) {
+    if (customElements.get(tag)) {
+        return false;
+    }
+    customElements.define(tag, class extends HTMLElement {
+        static get observedAttributes() { return observed; }
+        connectedCallback() { connected(this); }
+        disconnectedCallback() { disconnected(this); }
+        attributeChangedCallback(name, oldValue, value) {
+            if (oldValue !== value) {
+                changed(this, name);
+            }
+        }
+    });
+    return true;
+}
+"#)]
+extern "C" {
+    fn define_element(
+        tag: &str,
+        observed: &js_sys::Array,
+        connected: &JsValue,
+        disconnected: &JsValue,
+        changed: &JsValue,
+    ) -> bool;
+}
+
+// The canvas's 2d context, with `putImageData` bound here rather than
+// through web-sys, whose binding takes f64 or i32 coordinates depending on
+// whether its unstable APIs are turned on.
+#[wasm_bindgen]
+extern "C" {
+    type Context2d;
+
+    #[wasm_bindgen(method, catch, js_name = putImageData)]
+    fn put_image_data(
+        this: &Context2d,
+        image: &ImageData,
+        x: f64,
+        y: f64,
+    ) -> Result<(), JsValue>;
+}
+
+// What a connected element runs: its simulation and the canvas it draws on.
+struct LifeElement {
+    simulation: Simulation,
+    canvas: HtmlCanvasElement,
+    // Draws every frame. Kept so the simulation's reference to it stays
+    // valid.
+    _on_frame: Closure<dyn FnMut()>,
+}
+
+thread_local! {
+    static ELEMENTS: RefCell<HashMap<u32, LifeElement>> = RefCell::new(HashMap::new());
+    static NEXT_ID: StdCell<u32> = const { StdCell::new(0) };
+}
+
+fn element_id(element: &Element) -> Option<u32> {
+    js_sys::Reflect::get(element, &ID_PROPERTY.into())
+        .ok()?
+        .as_f64()
+        .map(|id| id as u32)
+}
+
+fn number_attribute<T: std::str::FromStr>(element: &Element, name: &str, default: T) -> T {
+    element
+        .get_attribute(name)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+// A universe set up from the element's attributes. A `pattern` can be one of
+// the built-in patterns by name, or a pattern in any format `from_pattern`
+// reads. Without one the cells are random. Fails if the size is too big, or
+// the pattern or rule can't be read.
+fn attribute_universe(element: &Element) -> Result<Universe, String> {
+    let width = number_attribute(element, "width", DEFAULT_SIZE).max(1);
+    let height = number_attribute(element, "height", DEFAULT_SIZE).max(1);
+    check_size(width, height)?;
+    let len = width as usize * height as usize;
+
+    let mut universe = match element.get_attribute("pattern") {
+        Some(pattern) => {
+            let mut universe = Universe::with_cells(width, height, vec![Cell::Dead; len]);
+            match Pattern::from_name(pattern.trim()) {
+                Some(built_in) => universe.stamp(built_in, height / 2, width / 2, 0),
+                None => {
+                    let pattern = Universe::from_parsed(parse_pattern(&pattern)?);
+                    universe.paste_centered(&pattern, height / 2, width / 2);
+                    universe.rule = pattern.rule;
+                }
+            }
+            universe
+        }
+        None => {
+            let mut random = Random::new();
+            let cells = (0..len)
+                .map(|_| {
+                    if random.next_u32().is_multiple_of(2) {
+                        Cell::Alive
+                    } else {
+                        Cell::Dead
+                    }
+                })
+                .collect();
+            Universe::with_cells(width, height, cells)
+        }
+    };
+
+    if let Some(rule) = element.get_attribute("rule") {
+        universe.rule = Rule::parse(rule.trim())?;
+    }
+    Ok(universe)
+}
+
+// Draws the element's universe onto its canvas.
+fn draw(id: u32) -> Result<(), JsValue> {
+    ELEMENTS.with(|elements| {
+        let mut elements = elements.borrow_mut();
+        let element = match elements.get_mut(&id) {
+            Some(element) => element,
+            None => return Ok(()),
+        };
+
+        let context = element
+            .canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("the canvas has no 2d context"))?
+            .unchecked_into::<Context2d>();
+        element.simulation.with_universe(|universe| {
+            universe.render_rgba();
+            let image = ImageData::new_with_u8_clamped_array_and_sh(
+                Clamped(&universe.pixels),
+                universe.render_width(),
+                universe.render_height(),
+            )?;
+            context.put_image_data(&image, 0.0, 0.0)
+        })
+    })
+}
+
+// Sets the element up from its attributes and starts it running. Problems
+// with the attributes are logged, since there's no caller to throw to.
+fn connect(element: Element) {
+    if let Err(err) = try_connect(&element) {
+        crate::utils::log_error(&err);
+    }
+}
+
+fn try_connect(element: &Element) -> Result<(), JsValue> {
+    disconnect(element.clone());
+    let universe = attribute_universe(element).map_err(|err| JsValue::from_str(&err))?;
+
+    let document = element
+        .owner_document()
+        .ok_or_else(|| JsValue::from_str("the element isn't in a document"))?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.unchecked_into();
+    canvas.set_width(universe.render_width());
+    canvas.set_height(universe.render_height());
+    canvas.set_attribute(
+        "style",
+        "display: block; width: 100%; image-rendering: pixelated;",
+    )?;
+
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    js_sys::Reflect::set(element, &ID_PROPERTY.into(), &id.into())?;
+
+    let mut simulation = Simulation::new(universe);
+    simulation.set_speed(number_attribute(element, "speed", DEFAULT_SPEED));
+    let on_frame = Closure::wrap(Box::new(move || {
+        if let Err(err) = draw(id) {
+            crate::utils::log_error(&err);
+        }
+    }) as Box<dyn FnMut()>);
+    simulation.set_on_frame(Some(
+        on_frame
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ));
+    simulation.start()?;
+
+    element.append_child(&canvas)?;
+    ELEMENTS.with(|elements| {
+        elements.borrow_mut().insert(
+            id,
+            LifeElement {
+                simulation,
+                canvas,
+                _on_frame: on_frame,
+            },
+        )
+    });
+    draw(id)
+}
+
+// Stops the element's simulation and takes its canvas out.
+fn disconnect(element: Element) {
+    let removed = element_id(&element)
+        .and_then(|id| ELEMENTS.with(|elements| elements.borrow_mut().remove(&id)));
+    if let Some(mut removed) = removed {
+        let _ = removed.simulation.stop();
+        removed.canvas.remove();
+    }
+    let _ = js_sys::Reflect::delete_property(&element, &ID_PROPERTY.into());
+}
+
+// Follows a change to one of the attributes. A new speed is picked up as it
+// is; anything else starts the element over.
+fn attribute_changed(element: Element, name: String) {
+    let id = match element_id(&element) {
+        Some(id) => id,
+        // Not connected yet, so the attribute is read when it is.
+        None => return,
+    };
+
+    if name == "speed" {
+        let speed = number_attribute(&element, "speed", DEFAULT_SPEED);
+        ELEMENTS.with(|elements| {
+            if let Some(running) = elements.borrow_mut().get_mut(&id) {
+                running.simulation.set_speed(speed);
+            }
+        });
+    } else {
+        connect(element);
+    }
+}
+
+// Defines the `<game-of-life>` element, which runs a simulation and draws it
+// on a canvas inside itself, with no other glue needed:
+//
+//     <game-of-life width="80" height="60" rule="B36/S23" speed="20"
+//                   pattern="glider"></game-of-life>
+//
+// `width` and `height` are in cells, 64 by default, and `speed` is in
+// generations per second, 10 by default. `pattern` is "glider", "pulsar",
+// "gosper-glider-gun", or a pattern in RLE, plaintext or Life 1.05 or 1.06,
+// placed in the middle; without one the cells start out random. `rule`, if
+// given, replaces the pattern's rule. Changing an attribute starts the
+// element over, except for `speed`.
+//
+// Returns false if the element was already defined. Throws outside a page.
+#[wasm_bindgen]
+pub fn register_life_element() -> Result<bool, JsValue> {
+    web_sys::window().ok_or_else(|| JsValue::from_str("custom elements need a page"))?;
+
+    let observed: js_sys::Array = ATTRIBUTES
+        .iter()
+        .map(|&name| JsValue::from_str(name))
+        .collect();
+    let connected = Closure::wrap(Box::new(connect) as Box<dyn FnMut(Element)>);
+    let disconnected = Closure::wrap(Box::new(disconnect) as Box<dyn FnMut(Element)>);
+    let changed = Closure::wrap(Box::new(attribute_changed) as Box<dyn FnMut(Element, String)>);
+
+    // The class keeps these for as long as the page lives.
+    Ok(define_element(
+        TAG,
+        &observed,
+        &connected.into_js_value(),
+        &disconnected.into_js_value(),
+        &changed.into_js_value(),
+    ))
+}
@@ -0,0 +1,117 @@
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "browser")]
+use web_sys::{CustomEvent, EventTarget};
+
+use crate::{Cell, Universe};
+
+// A callback or event held back while the universe is in a simulation, to
+// be delivered once the simulation is done with it.
+pub(crate) enum Notification {
+    Call(js_sys::Function, js_sys::Array),
+    #[cfg(feature = "browser")]
+    Event(EventTarget, CustomEvent),
+}
+
+impl Notification {
+    // Delivers the notification. Anything that goes wrong is logged and
+    // otherwise ignored, as there's nothing left to stop.
+    pub(crate) fn deliver(self) {
+        let result = match self {
+            Notification::Call(callback, args) => callback.apply(&JsValue::NULL, &args).map(|_| ()),
+            #[cfg(feature = "browser")]
+            Notification::Event(target, event) => target.dispatch_event(&event).map(|_| ()),
+        };
+        if let Err(err) = result {
+            crate::utils::log_error(&err);
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Calls the on_tick callback, if there is one, with the generation just
+    // reached and its stats, and dispatches the DOM events for it. Neither
+    // can stop the tick, so anything they throw is logged and otherwise
+    // ignored.
+    pub(crate) fn notify_tick(&mut self) {
+        #[cfg(feature = "browser")]
+        self.dispatch_tick_events();
+
+        let on_tick = match &self.on_tick {
+            Some(on_tick) => on_tick.clone(),
+            None => return,
+        };
+
+        let (population, births, deaths) = self.tick_counts();
+        match tick_stats(population, births, deaths) {
+            Ok(stats) => {
+                let args = js_sys::Array::of2(&JsValue::from(self.generation), &stats);
+                self.notify(Notification::Call(on_tick, args));
+            }
+            Err(err) => crate::utils::log_error(&err),
+        }
+    }
+
+    // Delivers `notification` straight away, or queues it up if they're
+    // being held back.
+    pub(crate) fn notify(&mut self, notification: Notification) {
+        match &mut self.deferred {
+            Some(deferred) => deferred.push(notification),
+            None => notification.deliver(),
+        }
+    }
+
+    // Holds back notifications from now on until they're taken, for when
+    // whatever ticks the universe can't be called back into until it's done.
+    #[cfg(feature = "browser")]
+    pub(crate) fn defer_notifications(&mut self) {
+        self.deferred.get_or_insert_with(Vec::new);
+    }
+
+    // The notifications held back since they were last taken.
+    #[cfg(feature = "browser")]
+    pub(crate) fn take_notifications(&mut self) -> Vec<Notification> {
+        self.deferred
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    // The population of the current generation and how many cells were born
+    // and died getting to it.
+    pub(crate) fn tick_counts(&self) -> (u32, u32, u32) {
+        let (mut population, mut births, mut deaths) = (0u32, 0u32, 0u32);
+        for (&cell, &previous) in self.cells.iter().zip(self.previous.iter()) {
+            match (previous, cell) {
+                (Cell::Dead, Cell::Alive) => births += 1,
+                (Cell::Alive, Cell::Dead) => deaths += 1,
+                _ => {}
+            }
+            population += (cell == Cell::Alive) as u32;
+        }
+        (population, births, deaths)
+    }
+}
+
+// The counts as a `{ population, births, deaths }` object.
+fn tick_stats(population: u32, births: u32, deaths: u32) -> Result<JsValue, JsValue> {
+    let stats = js_sys::Object::new();
+    js_sys::Reflect::set(&stats, &"population".into(), &population.into())?;
+    js_sys::Reflect::set(&stats, &"births".into(), &births.into())?;
+    js_sys::Reflect::set(&stats, &"deaths".into(), &deaths.into())?;
+    Ok(stats.into())
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Sets a function to call after every generation, however it was reached,
+    // with the generation number and a `{ population, births, deaths }` stats
+    // object. The universe is still busy ticking while the callback runs, so
+    // calling back into it from there throws. Inside a Simulation the calls
+    // are held back until the frame's generations are done, so the callback
+    // is free to call back into the simulation. Pass nothing to remove it.
+    pub fn set_on_tick(&mut self, callback: Option<js_sys::Function>) {
+        self.on_tick = callback;
+    }
+}
@@ -0,0 +1,164 @@
+use std::convert::TryFrom;
+
+use wasm_bindgen::prelude::*;
+
+use crate::rle::{parse_rle, parse_rule, ParsedPattern};
+use crate::rule::Rule;
+use crate::{check_size, Universe};
+
+// The pattern covering a set of live cells given as (x, y) coordinates that
+// can be anywhere, even negative, moved so its top-left corner is at 0, 0.
+fn from_coordinates(cells: Vec<(i64, i64)>, rule: Rule) -> Result<ParsedPattern, String> {
+    let left = cells.iter().map(|&(x, _)| x).min();
+    let top = cells.iter().map(|&(_, y)| y).min();
+    let right = cells.iter().map(|&(x, _)| x).max();
+    let bottom = cells.iter().map(|&(_, y)| y).max();
+    let (left, top, right, bottom) = match (left, top, right, bottom) {
+        (Some(left), Some(top), Some(right), Some(bottom)) => (left, top, right, bottom),
+        _ => return Err(String::from("the pattern has no live cells")),
+    };
+
+    let size =
+        |extent: i64| u32::try_from(extent + 1).map_err(|_| String::from("the pattern is too big"));
+    let mut alive: Vec<(u32, u32)> = cells
+        .into_iter()
+        .map(|(x, y)| ((y - top) as u32, (x - left) as u32))
+        .collect();
+    alive.sort_unstable();
+    alive.dedup();
+    let (width, height) = (size(right - left)?, size(bottom - top)?);
+    check_size(width, height)?;
+    Ok(ParsedPattern {
+        width,
+        height,
+        rule,
+        alive,
+    })
+}
+
+// The live cells in rows of `.` for dead and `O` or `*` for alive, starting
+// at row `top`.
+fn picture_cells<'a>(
+    lines: impl Iterator<Item = &'a str>,
+    top: i64,
+    left: i64,
+    cells: &mut Vec<(i64, i64)>,
+) -> Result<(), String> {
+    for (row, line) in lines.enumerate() {
+        for (col, c) in line.trim_end().chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => cells.push((left + col as i64, top + row as i64)),
+                c => return Err(format!("unexpected `{}` in the pattern", c)),
+            }
+        }
+    }
+    Ok(())
+}
+
+// Reads a pattern in plaintext, the `.cells` format: `!` comment lines, then
+// rows of `.` for dead cells and `O` for live ones. Blank lines are empty rows.
+fn parse_plaintext(text: &str) -> Result<ParsedPattern, String> {
+    let rows: Vec<&str> = text.lines().filter(|line| !line.starts_with('!')).collect();
+    let width = rows
+        .iter()
+        .map(|row| row.trim_end().len())
+        .max()
+        .unwrap_or(0);
+    let height = rows.len();
+
+    if width == 0 {
+        return Err(String::from("the pattern is empty"));
+    }
+    check_size(
+        u32::try_from(width).unwrap_or(u32::MAX),
+        u32::try_from(height).unwrap_or(u32::MAX),
+    )?;
+
+    // Plaintext says how big the pattern is, dead edges and all, so it isn't
+    // trimmed down to the live cells.
+    let mut cells = Vec::new();
+    picture_cells(rows.into_iter(), 0, 0, &mut cells)?;
+    Ok(ParsedPattern {
+        width: width as u32,
+        height: height as u32,
+        rule: Rule::default(),
+        alive: cells
+            .into_iter()
+            .map(|(x, y)| (y as u32, x as u32))
+            .collect(),
+    })
+}
+
+// Reads a pattern in Life 1.05 or 1.06, the `.lif` formats. 1.06 is one
+// `x y` pair per live cell. 1.05 has blocks of rows like plaintext but with
+// `*` for live cells, each after a `#P x y` line giving its top-left corner,
+// with `#N` for the usual rule or `#R 23/3` for another.
+fn parse_life(text: &str) -> Result<ParsedPattern, String> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let version = lines.next().unwrap_or_default();
+    let mut cells = Vec::new();
+
+    if version.starts_with("#Life 1.06") {
+        for line in lines.filter(|line| !line.starts_with('#')) {
+            let coordinate = |word: Option<&str>| {
+                word.and_then(|word| word.parse::<i64>().ok())
+                    .ok_or_else(|| format!("expected a pair of numbers, not `{}`", line))
+            };
+            let mut words = line.split_whitespace();
+            cells.push((coordinate(words.next())?, coordinate(words.next())?));
+        }
+        return from_coordinates(cells, Rule::default());
+    }
+
+    let mut rule = Rule::default();
+    let (mut top, mut left, mut row) = (0, 0, 0);
+    for line in lines {
+        if let Some(position) = line.strip_prefix("#P") {
+            let mut words = position.split_whitespace().map(str::parse::<i64>);
+            match (words.next(), words.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => {
+                    left = x;
+                    top = y;
+                    row = 0;
+                }
+                _ => return Err(format!("expected `#P x y`, not `{}`", line)),
+            }
+        } else if let Some(text) = line.strip_prefix("#R") {
+            rule = parse_rule(text.trim())?;
+        } else if line.starts_with('#') {
+            // Descriptions, names, and `#N` for the usual rule.
+        } else {
+            picture_cells(std::iter::once(line), top + row, left, &mut cells)?;
+            row += 1;
+        }
+    }
+    from_coordinates(cells, rule)
+}
+
+// Reads a pattern in any of the formats above, told apart by how they start.
+pub(crate) fn parse_pattern(text: &str) -> Result<ParsedPattern, String> {
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if first.starts_with("#Life") {
+        parse_life(text)
+    } else if first.starts_with(['!', '.', 'O']) {
+        parse_plaintext(text)
+    } else {
+        parse_rle(text)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Builds a universe just big enough for a pattern file's contents, in RLE
+    // (`.rle`), plaintext (`.cells`) or Life 1.05 or 1.06 (`.lif`), whichever
+    // it looks like. Throws if the pattern can't be read.
+    pub fn from_pattern(text: &str) -> Result<Universe, String> {
+        parse_pattern(text).map(Universe::from_parsed)
+    }
+}
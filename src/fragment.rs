@@ -0,0 +1,152 @@
+use std::convert::TryFrom;
+
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::rule::Rule;
+use crate::sync::Reader;
+use crate::{check_size, codec, Cell, Edge, NutrientOptions, Universe};
+
+// The `key=value` fields of a fragment like "#size=64x48&rule=B3/S23".
+pub(crate) fn fragment_fields(hash: &str) -> impl Iterator<Item = (&str, &str)> {
+    hash.trim_start_matches('#')
+        .split('&')
+        .filter(|field| !field.is_empty())
+        .map(|field| field.split_once('=').unwrap_or((field, "")))
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    fn apply_fragment(&mut self, hash: &str) -> Result<(), String> {
+        let (mut size, mut rule, mut alive) = (None, None, None);
+        let mut topology = Topology::TORUS;
+        let mut walls_alive = false;
+        let mut nutrients = None;
+        for (key, value) in fragment_fields(hash) {
+            match key {
+                "size" => {
+                    let (width, height) = value
+                        .split_once('x')
+                        .and_then(|(width, height)| {
+                            Some((width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|&(width, height): &(u32, u32)| width > 0 && height > 0)
+                        .ok_or_else(|| format!("expected a size like 64x48, not `{}`", value))?;
+                    size = Some((width, height));
+                }
+                "rule" => rule = Some(Rule::parse(value)?),
+                "edges" => {
+                    let (horizontal, vertical) = value.split_once(',').ok_or_else(|| {
+                        format!("expected edges like flip,wrap, not `{}`", value)
+                    })?;
+                    topology = Topology {
+                        horizontal: Edge::from_name(horizontal)?.into(),
+                        vertical: Edge::from_name(vertical)?.into(),
+                    };
+                }
+                "walls" => match value {
+                    "alive" => walls_alive = true,
+                    "dead" => walls_alive = false,
+                    _ => return Err(format!("expected walls=alive, not `{}`", value)),
+                },
+                "nutrients" => {
+                    let options = value
+                        .split(',')
+                        .map(|option| option.parse::<f32>().ok())
+                        .collect::<Option<Vec<f32>>>()
+                        .and_then(|options| <[f32; 5]>::try_from(options).ok())
+                        .ok_or_else(|| {
+                            format!("expected five nutrient options, not `{}`", value)
+                        })?;
+                    nutrients = Some(NutrientOptions::from_array(options));
+                }
+                "cells" => alive = Some(codec::decode(value)?),
+                // Anything else is for someone else, like the simulation's
+                // speed.
+                _ => {}
+            }
+        }
+
+        let (width, height) = size.ok_or_else(|| String::from("the link has no size"))?;
+        // Links come from anyone, so the size is checked before making room.
+        check_size(width, height)?;
+        let mut cells = vec![Cell::Dead; width as usize * height as usize];
+        if let Some(alive) = alive {
+            Reader { bytes: &alive }.cells(&mut cells)?;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.reset_cells();
+        for (idx, cell) in cells.into_iter().enumerate() {
+            self.set_cell(idx, cell);
+        }
+        if let Some(rule) = rule {
+            self.rule = rule;
+        }
+        self.topology = topology;
+        self.walls_alive = walls_alive;
+        if let Some(options) = nutrients {
+            self.nutrient_options = options;
+        }
+        let len = self.cells.len();
+        self.set_nutrient_levels(nutrients.map(|options| vec![options.capacity(); len]));
+        self.record_snapshot();
+        Ok(())
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The size, rule and live cells as a fragment for a shareable link, like
+    // "#size=64x48&rule=B3/S23&cells=...", with the cells packed into a few
+    // characters of base64 per live cell. A topology other than a torus adds
+    // the edges, like "&edges=flip,wrap", walls that count as alive add
+    // "&walls=alive", and a nutrient field adds the nutrient options in the
+    // order `NutrientOptions.new` takes them, like
+    // "&nutrients=0.5,0.1,0.2,0.02,1". The field itself is left out and comes
+    // back full. Set it as `location.hash` and pass it back to
+    // `apply_url_fragment` on load.
+    pub fn to_url_fragment(&self) -> String {
+        let mut cells = Vec::new();
+        self.put_cells(&mut cells, |_| Cell::Dead);
+        let mut fragment = format!(
+            "#size={}x{}&rule={}&cells={}",
+            self.width,
+            self.height,
+            self.rule,
+            codec::encode(&cells)
+        );
+        if self.topology != Topology::TORUS {
+            fragment.push_str(&format!(
+                "&edges={},{}",
+                Edge::from(self.topology.horizontal).name(),
+                Edge::from(self.topology.vertical).name()
+            ));
+        }
+        if self.walls_alive {
+            fragment.push_str("&walls=alive");
+        }
+        if self.nutrients.is_some() {
+            let options: Vec<String> = self
+                .nutrient_options
+                .to_array()
+                .iter()
+                .map(f32::to_string)
+                .collect();
+            fragment.push_str(&format!("&nutrients={}", options.join(",")));
+        }
+        fragment
+    }
+
+    // Sets up the universe from a fragment made by `to_url_fragment`, with or
+    // without the leading '#', starting over at generation 0. Fields it
+    // doesn't know are skipped. Throws, leaving the universe alone, if the
+    // fragment can't be read.
+    pub fn apply_url_fragment(&mut self, hash: &str) -> Result<(), JsValue> {
+        self.apply_fragment(hash)
+            .map_err(|err| JsValue::from_str(&err))
+    }
+}
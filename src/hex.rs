@@ -0,0 +1,143 @@
+use wasm_bindgen::prelude::*;
+
+use crate::recording::Edit;
+use crate::Universe;
+
+const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+// Which cells count as neighbors when ticking.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    // The eight surrounding cells of a square grid.
+    Moore = 0,
+    // The six surrounding cells of a hexagonal grid, with odd rows shifted
+    // half a cell to the right.
+    Hex = 1,
+}
+
+impl From<Neighborhood> for life_engine::Neighborhood {
+    fn from(neighborhood: Neighborhood) -> Self {
+        match neighborhood {
+            Neighborhood::Moore => life_engine::Neighborhood::Moore,
+            Neighborhood::Hex => life_engine::Neighborhood::Hex,
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Center of the hexagon for (row, column) when hexagons have `size`
+    // pixels from center to corner and the grid starts at (0, 0).
+    fn hex_center(&self, row: u32, column: u32, size: f64) -> (f64, f64) {
+        let hex_width = SQRT_3 * size;
+        let shift = if row % 2 == 1 { hex_width / 2.0 } else { 0.0 };
+        (
+            column as f64 * hex_width + hex_width / 2.0 + shift,
+            row as f64 * 1.5 * size + size,
+        )
+    }
+
+    // The (row, column) of the hexagon under a pixel, if there is one.
+    fn hex_at(&self, x: f64, y: f64, size: f64) -> Option<(u32, u32)> {
+        // Pixel to axial coordinates, relative to the center of cell (0, 0).
+        let x = x - SQRT_3 * size / 2.0;
+        let y = y - size;
+        let q = (SQRT_3 / 3.0 * x - y / 3.0) / size;
+        let r = (2.0 / 3.0 * y) / size;
+
+        // Round to the nearest hexagon in cube coordinates.
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        // Axial to offset coordinates with odd rows shifted right.
+        let (rq, rr) = (rq as i64, rr as i64);
+        let row = rr;
+        let col = rq + (rr - (rr & 1)) / 2;
+
+        if row < 0 || col < 0 || row >= self.height as i64 || col >= self.width as i64 {
+            None
+        } else {
+            Some((row as u32, col as u32))
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.neighborhood = neighborhood;
+        self.record(Edit::Neighborhood(neighborhood));
+    }
+
+    // Width in pixels of the universe drawn as pointy-topped hexagons with
+    // `size` pixels from center to corner.
+    pub fn hex_render_width(&self, size: f64) -> u32 {
+        (SQRT_3 * size * (self.width as f64 + 0.5)).ceil() as u32
+    }
+
+    // Height in pixels of the universe drawn as pointy-topped hexagons with
+    // `size` pixels from center to corner.
+    pub fn hex_render_height(&self, size: f64) -> u32 {
+        (1.5 * size * (self.height as f64 - 1.0) + 2.0 * size).ceil() as u32
+    }
+
+    // Converts a pixel position into the [row, column] of the hexagon under
+    // it, or an empty array when it's outside the grid.
+    pub fn pixel_to_hex(&self, x: f64, y: f64, size: f64) -> Vec<u32> {
+        match self.hex_at(x, y, size) {
+            Some((row, col)) => vec![row, col],
+            None => Vec::new(),
+        }
+    }
+
+    // The six corners of the hexagon for (row, column) as [x0, y0, x1, y1,
+    // ...], clockwise from the top, ready to trace as a canvas path.
+    pub fn hex_polygon(&self, row: u32, column: u32, size: f64) -> Vec<f64> {
+        let (cx, cy) = self.hex_center(row, column, size);
+        let mut corners = Vec::with_capacity(12);
+        for i in 0..6 {
+            let angle = (60.0 * i as f64 - 90.0).to_radians();
+            corners.push(cx + size * angle.cos());
+            corners.push(cy + size * angle.sin());
+        }
+        corners
+    }
+
+    // Renders the universe as hexagons into the universe's pixel buffer and
+    // returns a pointer to it. The buffer is hex_render_width(size) *
+    // hex_render_height(size) RGBA pixels, with the palette background
+    // filling the gaps around the grid.
+    pub fn render_hex_rgba(&mut self, size: f64) -> *const u8 {
+        let width = self.hex_render_width(size);
+        let height = self.hex_render_height(size);
+        let mut pixels = std::mem::take(&mut self.pixels);
+        pixels.resize((width * height * 4) as usize, 0);
+
+        let background = self.palette.background_color();
+        for y in 0..height {
+            for x in 0..width {
+                let color = match self.hex_at(x as f64 + 0.5, y as f64 + 0.5, size) {
+                    Some((row, col)) => self.pixel_color(self.get_index(row, col)),
+                    None => background,
+                };
+                let start = ((y * width + x) * 4) as usize;
+                pixels[start..start + 4].copy_from_slice(&color);
+            }
+        }
+
+        self.pixels = pixels;
+        self.pixels.as_ptr()
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+// What one cell looked like before a tick changed it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CellChange {
+    idx: u32,
+    cell: Cell,
+    age: u32,
+    heat: u8,
+}
+
+// The changes made by the most recent ticks, newest last, so they can be
+// undone one generation at a time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct History {
+    limit: usize,
+    generations: VecDeque<Vec<CellChange>>,
+}
+
+impl History {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.limit > 0
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.generations.clear();
+    }
+
+    // Remembers the changes made by a tick, forgetting the oldest generation
+    // once over the limit.
+    pub(crate) fn push(&mut self, changes: Vec<CellChange>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.generations.push_back(changes);
+        while self.generations.len() > self.limit {
+            self.generations.pop_front();
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Records the state of the cell at `idx` before a tick changes it.
+    pub(crate) fn cell_change(&self, idx: usize) -> CellChange {
+        CellChange {
+            idx: idx as u32,
+            cell: self.cells[idx],
+            age: self.ages[idx],
+            heat: self.heat[idx],
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn history_limit(&self) -> u32 {
+        self.history.limit as u32
+    }
+
+    // Starts recording what every tick changes, keeping the last
+    // `generations` of them so they can be undone with `step_back`. 0 turns
+    // recording off and forgets what was recorded.
+    pub fn set_history_limit(&mut self, generations: u32) {
+        self.history.limit = generations as usize;
+        while self.history.generations.len() > self.history.limit {
+            self.history.generations.pop_front();
+        }
+    }
+
+    // Number of generations that can currently be stepped back through.
+    pub fn history_len(&self) -> u32 {
+        self.history.generations.len() as u32
+    }
+
+    // Undoes the most recent recorded tick, putting every cell it changed
+    // back the way it was. Cells the tick left alone keep their state, along
+    // with any edits made since, and get a generation younger. Heat isn't
+    // recorded for them, so it stays as it is. Returns false when there's
+    // nothing left to undo.
+    pub fn step_back(&mut self) -> bool {
+        let changes = match self.history.generations.pop_back() {
+            Some(changes) => changes,
+            None => return false,
+        };
+
+        for age in self.ages.iter_mut() {
+            if *age != u32::MAX {
+                *age = age.saturating_sub(1);
+            }
+        }
+
+        for change in changes {
+            let idx = change.idx as usize;
+            self.cells[idx] = change.cell;
+            self.ages[idx] = change.age;
+            self.heat[idx] = change.heat;
+        }
+
+        self.previous.clone_from(&self.cells);
+        self.previous_valid = false;
+        self.generation = self.generation.wrapping_sub(1);
+        self.record_snapshot();
+        true
+    }
+
+    // Undoes the last tick, even with no history recorded, by going back to
+    // the previous generation's cells that are kept around anyway. Only works
+    // once in a row, and throws away any edits made since the tick. Uses the
+    // recorded history instead when there is some. Returns false when there's
+    // nothing to undo.
+    pub fn step_back_once(&mut self) -> bool {
+        if !self.history.generations.is_empty() {
+            return self.step_back();
+        }
+        if !self.previous_valid {
+            return false;
+        }
+
+        for idx in 0..self.cells.len() {
+            if self.cells[idx] != self.previous[idx] {
+                // How long the cell had been in its old state is lost, so it
+                // starts over as if it had just changed.
+                self.ages[idx] = 0;
+                self.heat[idx] = u8::MAX;
+            } else if self.ages[idx] != u32::MAX {
+                self.ages[idx] = self.ages[idx].saturating_sub(1);
+            }
+        }
+
+        self.cells.clone_from(&self.previous);
+        self.previous_valid = false;
+        self.generation = self.generation.wrapping_sub(1);
+        self.record_snapshot();
+        true
+    }
+}
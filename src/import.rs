@@ -0,0 +1,80 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{File, Response};
+
+use crate::formats::parse_pattern;
+use crate::Universe;
+
+// Reads a pattern file, e.g. one dropped on the page, into a universe just
+// big enough for it.
+pub(crate) async fn read_pattern_file(file: File) -> Result<Universe, JsValue> {
+    let text = JsFuture::from(file.text()).await?;
+    let text = text.as_string().unwrap_or_default();
+    parse_pattern(&text)
+        .map(Universe::from_parsed)
+        .map_err(|err| JsValue::from_str(&format!("{}: {}", file.name(), err)))
+}
+
+// Downloads a pattern file into a universe just big enough for it, with the
+// page's or worker's `fetch`.
+pub(crate) async fn fetch_pattern(url: String) -> Result<Universe, JsValue> {
+    let global = js_sys::global();
+    let fetch: js_sys::Function = js_sys::Reflect::get(&global, &"fetch".into())?
+        .dyn_into()
+        .map_err(|_| JsValue::from_str("fetch isn't available here"))?;
+
+    // A request the server doesn't allow cross-origin fails without saying
+    // why, so the likely reason is spelled out.
+    let request = fetch.call1(&global, &JsValue::from_str(&url))?;
+    let response: Response = JsFuture::from(js_sys::Promise::from(request))
+        .await
+        .map_err(|err| {
+            let reason = match err.dyn_ref::<js_sys::Error>() {
+                Some(err) => String::from(err.message()),
+                None => format!("{:?}", err),
+            };
+            JsValue::from_str(&format!(
+                "couldn't fetch {}, maybe it doesn't allow cross-origin requests: {}",
+                url, reason
+            ))
+        })?
+        .unchecked_into();
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "couldn't fetch {}: {} {}",
+            url,
+            response.status(),
+            response.status_text()
+        )));
+    }
+
+    let text = JsFuture::from(response.text()?).await?;
+    parse_pattern(&text.as_string().unwrap_or_default())
+        .map(Universe::from_parsed)
+        .map_err(|err| JsValue::from_str(&format!("{}: {}", url, err)))
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Reads a pattern file in RLE (`.rle`), plaintext (`.cells`) or Life 1.05
+    // or 1.06 (`.lif`), e.g. from a drop event's `dataTransfer.files`.
+    // Returns a promise of the pattern as a universe of its own, for passing
+    // to `paste`, since the universe being pasted into can't be held on to
+    // while the file is read. `Simulation.import_file` does both in one go.
+    // Rejects if the file can't be read or isn't a pattern.
+    pub fn import_file(file: File) -> js_sys::Promise {
+        future_to_promise(async move { read_pattern_file(file).await.map(JsValue::from) })
+    }
+
+    // Downloads a pattern file in any of the formats `import_file` reads,
+    // e.g. straight from a LifeWiki pattern link. Returns a promise of the
+    // pattern as a universe of its own, for passing to `paste`;
+    // `Simulation.import_from_url` does both in one go. Rejects if the
+    // download fails, including when the server doesn't allow cross-origin
+    // requests, or it isn't a pattern.
+    pub fn import_from_url(url: String) -> js_sys::Promise {
+        future_to_promise(async move { fetch_pattern(url).await.map(JsValue::from) })
+    }
+}
@@ -0,0 +1,54 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+#[wasm_bindgen]
+extern "C" {
+    // What `live_cells_iter` returns.
+    #[wasm_bindgen(typescript_type = "IterableIterator<{ row: number, col: number }>")]
+    pub type LiveCellsIter;
+}
+
+// A `{ value, done }` result for an iterator's `next`.
+fn iter_result(value: &JsValue, done: bool) -> JsValue {
+    let result = Object::new();
+    let _ = Reflect::set(&result, &"value".into(), value);
+    let _ = Reflect::set(&result, &"done".into(), &done.into());
+    result.into()
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The live cells as `{ row, col }` objects, row by row, for `for...of`,
+    // spreading into an array and the like. Which cells are live is taken
+    // when this is called, so ticking while iterating doesn't change what
+    // comes out. The objects are only made as they are asked for.
+    pub fn live_cells_iter(&self) -> LiveCellsIter {
+        let width = self.width;
+        let alive: Vec<u32> = (0..self.cells.len() as u32)
+            .filter(|&idx| self.cells[idx as usize] == Cell::Alive)
+            .collect();
+
+        let mut next = 0;
+        let next_cell = Closure::wrap(Box::new(move || match alive.get(next) {
+            Some(&idx) => {
+                next += 1;
+                let cell = Object::new();
+                let _ = Reflect::set(&cell, &"row".into(), &(idx / width).into());
+                let _ = Reflect::set(&cell, &"col".into(), &(idx % width).into());
+                iter_result(&cell, false)
+            }
+            None => iter_result(&JsValue::UNDEFINED, true),
+        }) as Box<dyn FnMut() -> JsValue>);
+
+        // Built on the prototype shared by the built-in iterators, whose
+        // `[Symbol.iterator]` hands back the iterator itself, so this works
+        // with `for...of` as well as calling `next` by hand.
+        let array_iterator = Object::get_prototype_of(&Array::new().values());
+        let iter = Object::create(&Object::get_prototype_of(&array_iterator));
+        let _ = Reflect::set(&iter, &"next".into(), &next_cell.into_js_value());
+        iter.unchecked_into()
+    }
+}
@@ -0,0 +1,298 @@
+use wasm_bindgen::prelude::*;
+
+use crate::rule::Rule;
+use crate::{check_size, utils, Cell, Palette, Universe};
+
+// Colors given to new layers in turn, so they can be told apart when drawn
+// over each other.
+const LAYER_COLORS: [u32; 6] = [
+    0x000000ff, 0xd62728ff, 0x1f77b4ff, 0x2ca02cff, 0x9467bdff, 0xff7f0eff,
+];
+
+struct Layer {
+    name: String,
+    universe: Universe,
+    color: u32,
+    visible: bool,
+    locked: bool,
+    ticking: bool,
+}
+
+// A stack of same-sized universes shown in one view, e.g. a background soup
+// under a construction being planned, like Golly's layers. Each layer has its
+// own cells and rule and can be hidden, locked against edits or left out of
+// ticks on its own. Layers are referred to by their place in the stack, 0
+// being the bottom, and drawn bottom first with each layer's live cells in
+// its own color.
+#[wasm_bindgen]
+pub struct LayerStack {
+    width: u32,
+    height: u32,
+    layers: Vec<Layer>,
+    palette: Palette,
+    pixels: Vec<u8>,
+}
+
+// Methods not being exported to Javascript
+impl LayerStack {
+    fn get(&self, layer: u32) -> Result<&Layer, JsValue> {
+        self.layers
+            .get(layer as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no layer {}", layer)))
+    }
+
+    fn get_mut(&mut self, layer: u32) -> Result<&mut Layer, JsValue> {
+        self.layers
+            .get_mut(layer as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no layer {}", layer)))
+    }
+
+    // The universe on `layer`, as long as it isn't locked.
+    fn editable(&mut self, layer: u32) -> Result<&mut Universe, JsValue> {
+        let layer = self.get_mut(layer)?;
+        if layer.locked {
+            return Err(JsValue::from_str(&format!(
+                "layer `{}` is locked",
+                layer.name
+            )));
+        }
+        Ok(&mut layer.universe)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl LayerStack {
+    // An empty stack of `width` by `height` layers. Throws if the size is
+    // zero or too big, so every layer added later fits too.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Result<LayerStack, JsValue> {
+        utils::set_panic_hook();
+
+        check_size(width, height).map_err(|err| JsValue::from_str(&err))?;
+        Ok(LayerStack {
+            width,
+            height,
+            layers: Vec::new(),
+            palette: Palette::default(),
+            pixels: vec![0; width as usize * height as usize * 4],
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn len(&self) -> u32 {
+        self.layers.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    // Adds an empty layer ticking by `rule` on top of the others and returns
+    // its place in the stack. Throws if the rule can't be read.
+    pub fn add_layer(&mut self, name: String, rule: &str) -> Result<u32, JsValue> {
+        let mut universe = Universe::with_cells(
+            self.width,
+            self.height,
+            vec![Cell::Dead; self.width as usize * self.height as usize],
+        );
+        universe.rule = Rule::parse(rule).map_err(|err| JsValue::from_str(&err))?;
+        self.layers.push(Layer {
+            name,
+            universe,
+            color: LAYER_COLORS[self.layers.len() % LAYER_COLORS.len()],
+            visible: true,
+            locked: false,
+            ticking: true,
+        });
+        Ok(self.layers.len() as u32 - 1)
+    }
+
+    // Takes a layer out of the stack and hands back its universe. The layers
+    // above it move down one.
+    pub fn remove_layer(&mut self, layer: u32) -> Result<Universe, JsValue> {
+        self.get(layer)?;
+        Ok(self.layers.remove(layer as usize).universe)
+    }
+
+    // Moves a layer to place `to` in the stack, shifting the ones between.
+    pub fn move_layer(&mut self, from: u32, to: u32) -> Result<(), JsValue> {
+        self.get(from)?;
+        self.get(to)?;
+        let layer = self.layers.remove(from as usize);
+        self.layers.insert(to as usize, layer);
+        Ok(())
+    }
+
+    pub fn name(&self, layer: u32) -> Result<String, JsValue> {
+        Ok(self.get(layer)?.name.clone())
+    }
+
+    pub fn set_name(&mut self, layer: u32, name: String) -> Result<(), JsValue> {
+        self.get_mut(layer)?.name = name;
+        Ok(())
+    }
+
+    pub fn visible(&self, layer: u32) -> Result<bool, JsValue> {
+        Ok(self.get(layer)?.visible)
+    }
+
+    // Hidden layers aren't drawn or merged, but still tick.
+    pub fn set_visible(&mut self, layer: u32, visible: bool) -> Result<(), JsValue> {
+        self.get_mut(layer)?.visible = visible;
+        Ok(())
+    }
+
+    pub fn locked(&self, layer: u32) -> Result<bool, JsValue> {
+        Ok(self.get(layer)?.locked)
+    }
+
+    // Locked layers throw instead of changing when edited, e.g. to keep a
+    // finished part of a construction safe. They still tick.
+    pub fn set_locked(&mut self, layer: u32, locked: bool) -> Result<(), JsValue> {
+        self.get_mut(layer)?.locked = locked;
+        Ok(())
+    }
+
+    pub fn ticking(&self, layer: u32) -> Result<bool, JsValue> {
+        Ok(self.get(layer)?.ticking)
+    }
+
+    // Layers that aren't ticking are skipped by `tick`, e.g. to hold a
+    // construction still while the soup under it runs.
+    pub fn set_ticking(&mut self, layer: u32, ticking: bool) -> Result<(), JsValue> {
+        self.get_mut(layer)?.ticking = ticking;
+        Ok(())
+    }
+
+    // The color the layer's live cells are drawn in, as 0xRRGGBBAA.
+    pub fn color(&self, layer: u32) -> Result<u32, JsValue> {
+        Ok(self.get(layer)?.color)
+    }
+
+    pub fn set_color(&mut self, layer: u32, color: u32) -> Result<(), JsValue> {
+        self.get_mut(layer)?.color = color;
+        Ok(())
+    }
+
+    pub fn rule(&self, layer: u32) -> Result<String, JsValue> {
+        Ok(self.get(layer)?.universe.rule())
+    }
+
+    pub fn set_rule(&mut self, layer: u32, rule: &str) -> Result<(), JsValue> {
+        self.get_mut(layer)?.universe.set_rule(rule)
+    }
+
+    pub fn generation(&self, layer: u32) -> Result<u32, JsValue> {
+        Ok(self.get(layer)?.universe.generation)
+    }
+
+    pub fn population(&self, layer: u32) -> Result<u32, JsValue> {
+        Ok(self.get(layer)?.universe.population())
+    }
+
+    // Pointer to a layer's cells, like `Universe.cells`.
+    pub fn cells(&self, layer: u32) -> Result<*const Cell, JsValue> {
+        Ok(self.get(layer)?.universe.cells())
+    }
+
+    // Ticks every ticking layer once, each by its own rule. Layers don't
+    // affect each other.
+    pub fn tick(&mut self) {
+        for layer in self.layers.iter_mut().filter(|layer| layer.ticking) {
+            layer.universe.tick();
+        }
+    }
+
+    // Ticks one layer, whether or not it's ticking.
+    pub fn tick_layer(&mut self, layer: u32) -> Result<(), JsValue> {
+        self.get_mut(layer)?.universe.tick();
+        Ok(())
+    }
+
+    pub fn toggle_cell(&mut self, layer: u32, row: u32, column: u32) -> Result<(), JsValue> {
+        self.editable(layer)?.toggle_cell(row, column)
+    }
+
+    // Brings the live cells of `pattern` to life on a layer, centered on
+    // (row, column) and wrapping around the edges.
+    pub fn place_pattern(
+        &mut self,
+        layer: u32,
+        pattern: &Universe,
+        row: u32,
+        column: u32,
+    ) -> Result<(), JsValue> {
+        self.editable(layer)?.paste_centered(pattern, row, column);
+        Ok(())
+    }
+
+    // Kills every cell on a layer.
+    pub fn clear_layer(&mut self, layer: u32) -> Result<(), JsValue> {
+        self.editable(layer)?.kill_universe();
+        Ok(())
+    }
+
+    // The palette whose dead cell color is drawn behind the layers.
+    pub fn palette(&self) -> Palette {
+        self.palette.clone()
+    }
+
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = palette.clone();
+    }
+
+    // Draws the visible layers over each other, bottom first, one pixel per
+    // cell, and returns a pointer to the width * height RGBA pixels.
+    pub fn render_rgba(&mut self) -> *const u8 {
+        let dead = self.palette.color(Cell::Dead);
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&dead);
+        }
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            let color = layer.color.to_be_bytes();
+            for (pixel, &cell) in self
+                .pixels
+                .chunks_exact_mut(4)
+                .zip(layer.universe.cells.iter())
+            {
+                if cell == Cell::Alive {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+        self.pixels.as_ptr()
+    }
+
+    // Flattens the visible layers into the lowest of them, which keeps its
+    // name, rule and generation and gains every cell alive on any of them.
+    // The others are removed; hidden layers are left as they are. Returns
+    // the merged layer's new place in the stack, or nothing if no layer is
+    // visible.
+    pub fn merge_layers(&mut self) -> Option<u32> {
+        let bottom = self.layers.iter().position(|layer| layer.visible)?;
+        let mut merged = self.layers.remove(bottom);
+        let mut idx = bottom;
+        while idx < self.layers.len() {
+            if !self.layers[idx].visible {
+                idx += 1;
+                continue;
+            }
+            let layer = self.layers.remove(idx);
+            for (cell_idx, &cell) in layer.universe.cells.iter().enumerate() {
+                if cell == Cell::Alive {
+                    merged.universe.set_cell(cell_idx, Cell::Alive);
+                }
+            }
+        }
+        self.layers.insert(bottom, merged);
+        Some(bottom as u32)
+    }
+}
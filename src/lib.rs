@@ -1,20 +1,170 @@
+mod battle;
+mod buffers;
+mod camera;
+#[cfg(feature = "browser")]
+mod clipboard;
+mod codec;
+mod command;
+mod config;
+mod crdt;
+#[cfg(feature = "browser")]
+mod dom;
+#[cfg(feature = "browser")]
+mod element;
+#[cfg(feature = "browser")]
+mod conditions;
+mod events;
+mod formats;
+mod fragment;
+mod hex;
+mod history;
+#[cfg(feature = "browser")]
+mod import;
+mod iter;
+mod layers;
+mod methuselah;
+mod nutrients;
+mod overlay;
+mod palette;
+mod pattern;
+mod predecessor;
+mod random;
+mod recording;
+mod render;
+mod rle;
+mod rule;
+mod script;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod selection;
+mod set;
+mod shared;
+#[cfg(feature = "browser")]
+mod simulation;
+mod sonify;
+mod state;
+#[cfg(feature = "browser")]
+mod storage;
+mod sync;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+mod terminal;
+mod text;
+mod timeline;
+#[cfg(feature = "browser")]
+mod timing;
+mod topology;
+mod transfer;
+mod universe3d;
 mod utils;
-
+mod viewport;
+mod walls;
+#[cfg(feature = "browser")]
+mod webgl;
+#[cfg(feature = "webgpu")]
+mod webgpu;
+
+#[cfg(feature = "browser")]
 extern crate web_sys;
+#[cfg(feature = "rand")]
 extern crate rand;
 
 use wasm_bindgen::prelude::*;
 use std::fmt;
-use rand::Rng;
+use life_engine::{Grid, State, Topology};
+
+use crdt::Replica;
+#[cfg(feature = "browser")]
+use dom::EventBinding;
+use events::Notification;
+use history::History;
+use nutrients::NutrientField;
+use pattern::PendingPattern;
+use random::Random;
+use recording::{Edit, Recording};
+use rule::Rule;
+use selection::Selection;
+use shared::SharedCells;
+use timeline::Timeline;
+use viewport::Viewport;
+
+pub use battle::{Battle, BattlePhase, Player};
+pub use camera::FollowMode;
+pub use config::{UniverseBuilder, UniverseConfig};
+#[cfg(feature = "browser")]
+pub use conditions::PauseCondition;
+#[cfg(feature = "browser")]
+pub use element::register_life_element;
+pub use hex::Neighborhood;
+pub use iter::LiveCellsIter;
+pub use layers::LayerStack;
+pub use methuselah::{MethuselahScore, MethuselahSearch};
+pub use nutrients::NutrientOptions;
+pub use overlay::OverlayOptions;
+pub use palette::Palette;
+pub use pattern::Pattern;
+pub use render::RenderMode;
+pub use script::Script;
+pub use set::UniverseSet;
+#[cfg(feature = "browser")]
+pub use simulation::Simulation;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+pub use terminal::Terminal;
+#[cfg(feature = "browser")]
+pub use timing::TimingStats;
+pub use topology::Edge;
+pub use universe3d::{Axis, Universe3D};
+#[cfg(feature = "browser")]
+pub use webgl::WebGlRenderer;
+#[cfg(feature = "webgpu")]
+pub use webgpu::WebGpuRenderer;
 
 // Macro to simplify logging.
 #[allow(unused_macros)]
 macro_rules! log {
     ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
+        $crate::utils::log(&format!( $( $t )* ).into());
     };
 }
 
+// How much heat a cell loses each generation it doesn't change state.
+const HEAT_DECAY: u8 = 8;
+
+// The most cells a universe can have, so sizes coming from outside can't ask
+// for more memory than there is.
+const MAX_CELLS: u64 = 1 << 24;
+
+// Checks a width x height universe has at least one cell and no more than
+// MAX_CELLS of them.
+pub(crate) fn check_size(width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err(format!("a {} by {} universe has no cells", width, height));
+    }
+    if width as u64 * height as u64 > MAX_CELLS {
+        return Err(format!(
+            "a {} by {} universe is too big, the most is {} cells",
+            width, height, MAX_CELLS
+        ));
+    }
+    Ok(())
+}
+
+// The same as `check_size` for a width x height x depth universe of voxels.
+pub(crate) fn check_size_3d(width: u32, height: u32, depth: u32) -> Result<(), String> {
+    if width == 0 || height == 0 || depth == 0 {
+        return Err(format!("a {} by {} by {} universe has no cells", width, height, depth));
+    }
+    let cells = (width as u64)
+        .checked_mul(height as u64)
+        .and_then(|cells| cells.checked_mul(depth as u64));
+    if cells.is_none_or(|cells| cells > MAX_CELLS) {
+        return Err(format!(
+            "a {} by {} by {} universe is too big, the most is {} cells",
+            width, height, depth, MAX_CELLS
+        ));
+    }
+    Ok(())
+}
+
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
 #[cfg(feature = "wee_alloc")]
@@ -28,7 +178,9 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Cell {
     Dead = 0,
-    Alive = 1
+    Alive = 1,
+    // Never changes when ticking, only when edited.
+    Wall = 2
 }
 
 impl Cell {
@@ -36,39 +188,351 @@ impl Cell {
         *self = match *self {
             Cell::Dead => Cell::Alive,
             Cell::Alive => Cell::Dead,
+            Cell::Wall => Cell::Wall,
         };
     }
 }
 
+// Lets the engine tick the universe's cells in place.
+impl State for Cell {
+    const DEAD: Cell = Cell::Dead;
+    const ALIVE: Cell = Cell::Alive;
+
+    fn is_wall(self) -> bool {
+        self == Cell::Wall
+    }
+}
+
 
 #[wasm_bindgen]
 pub struct Universe {
     width: u32,
     height: u32,
-    cells: Vec<Cell>
+    cells: Vec<Cell>,
+    rule: Rule,
+    // Number of ticks since the universe was created or resized.
+    generation: u32,
+    // Cells as of the generation before the current one.
+    previous: Vec<Cell>,
+    // Whether `previous` really is the last generation, so it can be stepped
+    // back to. Not the case before the first tick or after stepping back.
+    previous_valid: bool,
+    // What recent ticks changed, when recording is turned on.
+    history: History,
+    // Recent generations to scrub over, when the timeline is turned on.
+    timeline: Timeline,
+    neighborhood: Neighborhood,
+    // How the edges join up when ticking.
+    topology: Topology,
+    // Whether walls count as live neighbors when ticking.
+    walls_alive: bool,
+    // The nutrients under the cells, if they have to live off any, and how
+    // they feed them.
+    nutrients: Option<NutrientField>,
+    nutrient_options: NutrientOptions,
+    // Generations each cell has spent in its current state. Cells that have
+    // been dead since the universe was created count as dead forever.
+    ages: Vec<u32>,
+    // Recent activity per cell: maxed out when a cell changes state and
+    // cooling off by HEAT_DECAY every generation it doesn't.
+    heat: Vec<u8>,
+    palette: Palette,
+    render_mode: RenderMode,
+    pixels: Vec<u8>,
+    // Cells as of the last `render_changes` call.
+    rendered: Vec<Cell>,
+    viewport: Viewport,
+    camera_follow: FollowMode,
+    // Generations a dead cell keeps fading out for, 0 for no trails.
+    trail_length: u32,
+    // How far the RGBA renderer has faded from the previous generation to
+    // the current one, 1 to show only the current one.
+    crossfade: f32,
+    selection: Option<Selection>,
+    // The cell under the pointer and the size of the brush around it.
+    hover: Option<(u32, u32)>,
+    brush_size: u32,
+    // Pattern previewed over the cells until it is committed.
+    pending: Option<PendingPattern>,
+    // Edits made since recording started, if it has.
+    recording: Option<Recording>,
+    on_tick: Option<js_sys::Function>,
+    // Where the universe's DOM events are dispatched, if anywhere.
+    #[cfg(feature = "browser")]
+    events: Option<EventBinding>,
+    // Notifications held back until whatever is ticking the universe lets go
+    // of it, while it's in a simulation.
+    deferred: Option<Vec<Notification>>,
+    overlay: OverlayOptions,
+    // Buffer the cells are mirrored to for other workers, if attached.
+    shared: Option<SharedCells>,
+    // Cells as of the last sync message sent or applied.
+    synced: Vec<Cell>,
+    // Who wrote each cell last, for merging edits from peers.
+    replica: Replica,
+    // Whether coordinates outside the universe are an error rather than
+    // wrapped around.
+    strict: bool,
 }
 
 // Methods not being exported to Javascript
 impl Universe {
+    // Builds a universe around an existing set of cells, with everything else
+    // at its default.
+    fn with_cells(width: u32, height: u32, cells: Vec<Cell>) -> Universe {
+        Universe {
+            width,
+            height,
+            ages: cells
+                .iter()
+                .map(|&cell| if cell == Cell::Alive { 0 } else { u32::MAX })
+                .collect(),
+            heat: vec![0; cells.len()],
+            rule: Rule::default(),
+            generation: 0,
+            previous: cells.clone(),
+            previous_valid: false,
+            history: History::default(),
+            timeline: Timeline::default(),
+            cells,
+            neighborhood: Neighborhood::Moore,
+            topology: Topology::TORUS,
+            walls_alive: false,
+            nutrients: None,
+            nutrient_options: NutrientOptions::default(),
+            palette: Palette::default(),
+            render_mode: RenderMode::Binary,
+            pixels: Vec::new(),
+            rendered: Vec::new(),
+            viewport: Viewport::default(),
+            camera_follow: FollowMode::None,
+            trail_length: 0,
+            crossfade: 1.0,
+            selection: None,
+            hover: None,
+            brush_size: 1,
+            pending: None,
+            recording: None,
+            on_tick: None,
+            #[cfg(feature = "browser")]
+            events: None,
+            deferred: None,
+            overlay: OverlayOptions::default(),
+            shared: None,
+            synced: Vec::new(),
+            replica: Replica::new(Random::new().next_u32()),
+            strict: false,
+        }
+    }
+
+    // Changes the state of a single cell, restarting its age and heating it
+    // up if the state actually changed.
+    fn set_cell(&mut self, idx: usize, cell: Cell) {
+        if self.cells[idx] != cell {
+            self.cells[idx] = cell;
+            self.ages[idx] = 0;
+            self.heat[idx] = u8::MAX;
+        }
+    }
+
+    // Brings the cells at each (row, column) to life. They must already be
+    // inside the universe.
+    pub(crate) fn set_alive(&mut self, positions: &[(u32, u32)]) {
+        for &(row, col) in positions {
+            let idx = self.get_index(row, col);
+            self.set_cell(idx, Cell::Alive);
+        }
+    }
+
+    // Changes the size of the universe, which must have passed check_size,
+    // and resets all cells to the dead state.
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.reset_cells();
+    }
+
+    // Replaces every cell with a dead one, forgetting all ages and heat.
+    fn reset_cells(&mut self) {
+        self.selection = None;
+        self.hover = None;
+        self.pending = None;
+        let len = (self.width * self.height) as usize;
+        self.cells = vec![Cell::Dead; len];
+        self.ages = vec![u32::MAX; len];
+        self.heat = vec![0; len];
+        self.previous = self.cells.clone();
+        self.previous_valid = false;
+        self.generation = 0;
+        self.history.clear();
+        self.timeline.clear();
+        self.rendered.clear();
+        self.replica.clear();
+        self.reset_nutrients(self.nutrient_options.capacity());
+        if self.shared.as_ref().is_some_and(|shared| !shared.fits(len)) {
+            self.shared = None;
+        }
+    }
+
+    // The cell at (row, column) wrapped around the edges, or an error in
+    // strict mode if it's outside the universe.
+    pub(crate) fn position(&self, row: u32, column: u32) -> Result<(u32, u32), String> {
+        if self.strict && (row >= self.height || column >= self.width) {
+            return Err(format!(
+                "({}, {}) is outside the {} by {} universe",
+                row, column, self.width, self.height
+            ));
+        }
+        Ok((row % self.height, column % self.width))
+    }
+
+    // Creates a pulsar centered at the row / col location, which must be
+    // inside the universe.
+    pub(crate) fn pulsar(&mut self, row: u32, column: u32) {
+        self.record(Edit::Pulsar(row, column));
+        let y_axis = column;
+        let x_axis = row;
+
+        // 0 centered: Seed initial pulsar segment - upper right segment.
+        let pulsar_seed = [(6,4),(6,3),(6,2),(4,6),(4,1),(3,6),(3,1),(2,6),(2,1),(1,4),(1,3),(1,2)];
+
+        // Map the shape of the upper right pulsar segment to the offset from click location.
+        let mut pulsar: Vec<(u32, u32)> =
+                        pulsar_seed.iter()
+                            .map(|pair| {
+                                ((row + pair.0) % self.height , (column + pair.1) % self.width)
+                            })
+                            .collect();
+
+        // Mirror initial pulsar segment on Y axis.
+        let pulsar_segment: Vec<(u32, u32)> = 
+                                pulsar.iter()
+                                    .map(|pair| {
+                                        (pair.0, (2 * y_axis + self.width - pair.1) % self.width)
+                                    })
+                                    .collect();
+        
+        // Combine mirrored segment with initial segment, resulting in top half of pulsar.
+        pulsar.extend(pulsar_segment);
+        
+        // Mirror top half pulsar segment on X axis.
+        let pulsar_segment: Vec<(u32, u32)> = 
+                                pulsar.iter()
+                                    .map(|pair| {
+                                        ((2 * x_axis + self.height - pair.0) % self.height, pair.1)
+                                    })
+                                    .collect();
+        
+        // Combine top half segment with bottom half segment.
+        pulsar.extend(pulsar_segment);
+
+        self.set_alive(&pulsar);
+        #[cfg(feature = "browser")]
+        self.dispatch_pattern_placed(Pattern::Pulsar, row, column, 0);
+    }
+
+    // Creates a glider with its top-left corner at the row / col location,
+    // which must be inside the universe.
+    pub(crate) fn glider(&mut self, row: u32, column: u32) {
+        self.record(Edit::Glider(row, column));
+        // 0 centered: Seed for glider.
+        let glider_seed = [(0,2),(1,0),(1,2),(2,1),(2,2)];
+
+        // Map the shape of glider to the offset from click location.
+        let glider: Vec<(u32, u32)> =
+                        glider_seed.iter()
+                            .map(|pair| {
+                                ((row + pair.0) % self.height , (column + pair.1) % self.width)
+                            })
+                            .collect();
+        
+        self.set_alive(&glider);
+        #[cfg(feature = "browser")]
+        self.dispatch_pattern_placed(Pattern::Glider, row, column, 0);
+    }
+
+    pub(crate) fn toggle(&mut self, row: u32, column: u32) {
+        self.record(Edit::Toggle(row, column));
+        let idx = self.get_index(row, column);
+        let mut cell = self.cells[idx];
+        cell.toggle();
+        self.set_cell(idx, cell);
+    }
+
     fn get_index(&self, row: u32, column: u32) ->  usize {
         (row * self.width + column) as usize
     }
 
-    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
-        let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
-                if delta_row == 0 && delta_col == 0 {
-                    continue;
-                }
+    // The cells as a grid for the engine to work on.
+    pub(crate) fn grid(&self) -> Grid<'_, Cell> {
+        Grid::new(&self.cells, self.width, self.height)
+            .with_topology(self.topology)
+            .with_walls_alive(self.walls_alive)
+    }
 
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
-                count += self.cells[idx] as u8;
+    // Advances the universe by one generation, keeping ages and heat up to
+    // date. The buffer of the generation being dropped is reused for the new
+    // one, so this doesn't allocate.
+    fn step(&mut self) {
+        let mut next = std::mem::take(&mut self.previous);
+        next.clone_from(&self.cells);
+        self.grid().step_into(&self.rule, self.neighborhood.into(), &mut next);
+        // A timeline starting over begins at this generation, so it needs
+        // the nutrients from before they're fed on.
+        let nutrients_before = if self.timeline.is_restarting(self.cells.len()) {
+            self.nutrient_levels().map(<[f32]>::to_vec)
+        } else {
+            None
+        };
+        self.feed_nutrients(&mut next);
+
+        let mut changes = Vec::new();
+        for (idx, &next_cell) in next.iter().enumerate() {
+            let cell = self.cells[idx];
+            if next_cell == cell {
+                self.ages[idx] = self.ages[idx].saturating_add(1);
+                self.heat[idx] = self.heat[idx].saturating_sub(HEAT_DECAY);
+                continue;
+            }
+
+            if self.history.is_enabled() {
+                changes.push(self.cell_change(idx));
             }
+            self.ages[idx] = 0;
+            self.heat[idx] = u8::MAX;
         }
-        count
+
+        self.previous = std::mem::replace(&mut self.cells, next);
+        self.previous_valid = true;
+        self.generation = self.generation.wrapping_add(1);
+        self.history.push(changes);
+        self.timeline.record(
+            &self.previous,
+            &self.cells,
+            self.generation,
+            nutrients_before,
+            self.nutrients.as_ref().map(NutrientField::levels),
+        );
+        self.publish_shared();
+        self.notify_tick();
+    }
+
+    // A hash of the cell states, for cheaply telling generations apart.
+    #[cfg(feature = "browser")]
+    pub(crate) fn cells_hash(&self) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = DefaultHasher::new();
+        self.cell_bytes().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // View the cells as raw bytes, one byte per cell, for uploading to the GPU
+    // or copying out to JavaScript.
+    pub(crate) fn cell_bytes(&self) -> &[u8] {
+        // Cell is #[repr(u8)], so the buffer has the same layout as [u8].
+        unsafe { std::slice::from_raw_parts(self.cells.as_ptr() as *const u8, self.cells.len()) }
     }
 
     // Get the dead and alive values of the entire universe
@@ -77,12 +541,21 @@ impl Universe {
     }
 
     // Set cells to be alive in a universe by passing the row and column 
-    // of each cell as an array.
-    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
-        for (row, col) in cells.iter().cloned() {
-            let idx = self.get_index(row, col);
-            self.cells[idx] = Cell::Alive;
-        }
+    // of each cell as an array. Cells past the edges wrap around, or in
+    // strict mode are an error and leave every cell as it was.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) -> Result<(), String> {
+        let positions = cells
+            .iter()
+            .map(|&(row, col)| self.position(row, col))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.set_alive(&positions);
+        Ok(())
+    }
+}
+
+impl Default for Universe {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -91,10 +564,14 @@ impl fmt::Display for Universe {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for line in self.cells.as_slice().chunks(self.width as usize) {
             for &cell in line {
-                let symbol = if cell == Cell::Dead { '◻' } else { '◼' };
+                let symbol = match cell {
+                    Cell::Dead => '◻',
+                    Cell::Alive => '◼',
+                    Cell::Wall => '▦',
+                };
                 write!(f, " {}", symbol)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
@@ -105,50 +582,24 @@ impl fmt::Display for Universe {
 #[wasm_bindgen]
 impl Universe {
     pub fn tick(&mut self) {
-        let mut next = self.cells.clone();
-
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let idx = self.get_index(row, col);
-                let cell = self.cells[idx];
-                let live_neighbors = self.live_neighbor_count(row, col);
-
-                /*                 
-                log!(
-                    "cell[{}, {}] is initially {:?} and has {} live neighbors",
-                    row,
-                    col,
-                    cell,
-                    live_neighbors
-                ); 
-                */
-
-                let next_cell = match (cell, live_neighbors) {
-                    // Rule 1: Any live cell with fewer than two live neighbors
-                    // dies, as if caused by underpopulation.
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
-                    // Rule 2: Any live cell with two or three live neighbors
-                    // lives on to the next generation.
-                    (Cell::Alive, 2) | (Cell::Alive, 3) => Cell::Alive,
-                    // Rule 3: Any live cells more than three live neighbors
-                    // dies, as if by overpopulation
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
-                    // Rule 4: Any dead cell with exactly three live neighbors
-                    // becomes a live cell, as if by reproduction.
-                    (Cell::Dead, 3) => Cell::Alive,
-                    // All other cells remain in the same state.
-                    (otherwise, _) =>otherwise
-                };
+        self.step();
+        self.follow_camera();
+    }
 
-                /*
-                log!("    it becomes {:?}", next_cell);
-                */
+    // Jumps forward to the given generation without returning to JavaScript
+    // in between, and returns how long it took in milliseconds. Does nothing
+    // if the universe is already at or past it.
+    pub fn advance_to(&mut self, generation: u32) -> f64 {
+        let start = utils::now_ms();
 
-                next[idx] = next_cell;
+        if generation > self.generation {
+            for _ in self.generation..generation {
+                self.step();
             }
+            self.follow_camera();
         }
 
-        self.cells = next;
+        utils::now_ms() - start
     }
 
     // Constructor for a new Universe
@@ -170,22 +621,18 @@ impl Universe {
             })
             .collect();
         
-        Universe {
-            width,
-            height,
-            cells
-        }
+        Universe::with_cells(width, height, cells)
     }
 
     pub fn random_universe() -> Universe {
         
-        let mut rng = rand::thread_rng();
+        let mut random = Random::new();
 
         let width = 100;
         let height = 100;
 
         let cells = (0..width * height)
-            .map(|_| rng.gen_range(0, 2))
+            .map(|_| random.next_u32() % 2)
             .map(|i| {
                 if i == 0 {
                     Cell::Alive
@@ -196,88 +643,75 @@ impl Universe {
             })
             .collect();
         
-        Universe {
-            width,
-            height,
-            cells
-        }
+        Universe::with_cells(width, height, cells)
     }
 
     // Refactor to use Map
     pub fn kill_universe(&mut self) {
+        self.record(Edit::Kill);
         for row in 0..self.height {
             for col in 0..self.width {
                 let idx = self.get_index(row, col);
-                self.cells[idx] = Cell::Dead;
+                if self.cells[idx] != Cell::Wall {
+                    self.set_cell(idx, Cell::Dead);
+                }
             }
         }
     }
 
-    // Creates a pulsar centered at the row / col location.
-    pub fn create_pulsar(&mut self, row: u32, column: u32) {
-        let y_axis = column;
-        let x_axis = row;
-
-        // 0 centered: Seed initial pulsar segment - upper right segment.
-        let pulsar_seed = vec![(6,4),(6,3),(6,2),(4,6),(4,1),(3,6),(3,1),(2,6),(2,1),(1,4),(1,3),(1,2)];
-
-        // Map the shape of the upper right pulsar segment to the offset from click location.
-        let mut pulsar: Vec<(u32, u32)> =
-                        pulsar_seed.iter()
-                            .map(|pair| {
-                                ((row + pair.0) % self.height , (column + pair.1) % self.width)
-                            })
-                            .collect();
-
-        // Mirror initial pulsar segment on Y axis.
-        let pulsar_segment: Vec<(u32, u32)> = 
-                                pulsar.iter()
-                                    .map(|pair| {
-                                        (pair.0 % self.height, (y_axis + (y_axis - pair.1)) % self.width)
-                                    })
-                                    .collect();
-        
-        // Combine mirrored segment with initial segment, resulting in top half of pulsar.
-        pulsar.extend(pulsar_segment);
-        
-        // Mirror top half pulsar segment on X axis.
-        let pulsar_segment: Vec<(u32, u32)> = 
-                                pulsar.iter()
-                                    .map(|pair| {
-                                        ((x_axis + (x_axis - pair.0)) % self.height, pair.1 % self.width)
-                                    })
-                                    .collect();
-        
-        // Combine top half segment with bottom half segment.
-        pulsar.extend(pulsar_segment);
+    // Creates a pulsar centered at the row / col location. Throws in strict
+    // mode if that's outside the universe, and otherwise wraps it around.
+    pub fn create_pulsar(&mut self, row: u32, column: u32) -> Result<(), JsValue> {
+        let (row, column) = self.position(row, column).map_err(|err| JsValue::from_str(&err))?;
+        self.pulsar(row, column);
+        Ok(())
+    }
 
-        self.set_cells(&pulsar);
+    // Creates a glider with its top-left corner at the row / col location.
+    // Throws in strict mode if that's outside the universe, and otherwise
+    // wraps it around.
+    pub fn create_glider(&mut self, row: u32, column: u32) -> Result<(), JsValue> {
+        let (row, column) = self.position(row, column).map_err(|err| JsValue::from_str(&err))?;
+        self.glider(row, column);
+        Ok(())
     }
 
-    pub fn create_glider(&mut self, row: u32, column: u32) {
-        // 0 centered: Seed for glider.
-        let glider_seed = vec![(0,2),(1,0),(1,2),(2,1),(2,2)];
+    // Flips a cell between dead and alive. Throws in strict mode if it's
+    // outside the universe, and otherwise wraps it around.
+    pub fn toggle_cell(&mut self, row: u32, column: u32) -> Result<(), JsValue> {
+        let (row, column) = self.position(row, column).map_err(|err| JsValue::from_str(&err))?;
+        self.toggle(row, column);
+        Ok(())
+    }
 
-        // Map the shape of glider to the offset from click location.
-        let glider: Vec<(u32, u32)> =
-                        glider_seed.iter()
-                            .map(|pair| {
-                                ((row + pair.0) % self.height , (column + pair.1) % self.width)
-                            })
-                            .collect();
-        
-        self.set_cells(&glider);
+    // Whether coordinates outside the universe throw rather than wrap around
+    // the edges. Off to begin with.
+    pub fn strict(&self) -> bool {
+        self.strict
     }
 
-    pub fn toggle_cell(&mut self, row: u32, column: u32) {
-        let idx = self.get_index(row, column);
-        self.cells[idx].toggle();
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
     pub fn width(&self) -> u32 {
         self.width
     }
 
+    // Number of live cells.
+    pub fn population(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Alive)
+            .count() as u32
+    }
+
+    // Number of generations ticked since the universe was created or last
+    // resized.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
     pub fn height(&self) -> u32 {
         self.height
     }
@@ -286,16 +720,34 @@ impl Universe {
         self.cells.as_ptr()
     }
 
+    // Pointer to width * height u32 ages, the number of generations each
+    // cell has been in its current state.
+    pub fn ages(&self) -> *const u32 {
+        self.ages.as_ptr()
+    }
+
+    // Pointer to width * height u8 heat values, 255 for a cell that just
+    // changed state and fading towards 0 while it stays the same.
+    pub fn heat(&self) -> *const u8 {
+        self.heat.as_ptr()
+    }
+
     // Sets the width of the universe and resets all cells to the dead state.
-    pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        self.cells = (0..width * self.height).map(|_i| Cell::Dead).collect();
+    // Throws if the width is 0.
+    pub fn set_width(&mut self, width: u32) -> Result<(), String> {
+        check_size(width, self.height)?;
+        self.record(Edit::Width(width));
+        self.resize(width, self.height);
+        Ok(())
     }
 
     // Sets the height of the universe and resets all cells to the dead state.
-    pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        self.cells = (0..self.width * height).map(|_i| Cell::Dead).collect();
+    // Throws if the height is 0.
+    pub fn set_height(&mut self, height: u32) -> Result<(), String> {
+        check_size(self.width, height)?;
+        self.record(Edit::Height(height));
+        self.resize(self.width, height);
+        Ok(())
     }
 
     pub fn render(&self) -> String {
@@ -0,0 +1,341 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+use wasm_bindgen::prelude::*;
+
+use life_engine::{Edge, Grid, Neighborhood, Topology};
+
+use crate::random::Random;
+use crate::rule::Rule;
+use crate::{check_size, utils};
+
+// How many of the best seeds are kept, and how many of them carry over
+// unchanged into each new generation of the search.
+const BEST_KEPT: usize = 10;
+const ELITE: usize = 2;
+
+// How many generations back a repeat is looked for, so oscillators up to
+// this period count as settled.
+const SETTLE_WINDOW: usize = 30;
+
+// The widest arena made for a seed by default, the most `check_size` allows
+// for a square.
+const MAX_DEFAULT_ARENA: u32 = 1 << 12;
+
+// Seeds in the arena are cut off at its edges rather than wrapping, so
+// gliders leaving a methuselah don't come back and keep it going forever.
+const ARENA: Topology = Topology {
+    horizontal: Edge::Bounded,
+    vertical: Edge::Bounded,
+};
+
+// What makes a seed better than another in a `MethuselahSearch`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MethuselahScore {
+    // Generations until the pattern settles into still lifes and
+    // oscillators.
+    Lifespan = 0,
+    // Live cells once it settles, or when the search stops running it.
+    FinalPopulation = 1,
+}
+
+// A seed pattern as the (row, column) of its live cells within the seed
+// box, sorted.
+type Seed = Vec<(u32, u32)>;
+
+// Searches for methuselahs: small seeds that take a long time to settle, or
+// leave a lot behind when they do. A population of random seeds in a small
+// box is evolved with a genetic algorithm, each seed run in an arena until it
+// settles and scored, the best bred by crossover and mutation into the next
+// generation. The work is done in slices of a given number of milliseconds,
+// so `run` can be called from an animation loop, with the best seeds so far
+// available in between.
+#[wasm_bindgen]
+pub struct MethuselahSearch {
+    seed_size: u32,
+    arena_size: u32,
+    max_generations: u32,
+    rule: Rule,
+    score: MethuselahScore,
+    random: Random,
+    population: Vec<Seed>,
+    // Scores of the seeds in `population` evaluated so far, in order.
+    scores: Vec<u32>,
+    generation: u32,
+    evaluations: u32,
+    // The best seeds found so far, best first, with their scores.
+    best: Vec<(u32, Seed)>,
+}
+
+// Methods not being exported to Javascript
+impl MethuselahSearch {
+    fn random_seed(&mut self) -> Seed {
+        let (size, random) = (self.seed_size, &mut self.random);
+        let mut seed: Seed = (0..size * size)
+            .filter(|_| random.next_u32().is_multiple_of(2))
+            .map(|idx| (idx / size, idx % size))
+            .collect();
+        seed.sort_unstable();
+        seed
+    }
+
+    // Runs `seed` in the middle of an empty arena until it settles or runs
+    // out of generations, and scores it.
+    fn evaluate(&self, seed: &[(u32, u32)]) -> u32 {
+        let size = self.arena_size;
+        let offset = (size - self.seed_size) / 2;
+        let mut cells = vec![0u8; (size * size) as usize];
+        for &(row, col) in seed {
+            cells[((row + offset) * size + col + offset) as usize] = 1;
+        }
+        let mut next = cells.clone();
+
+        let mut recent: VecDeque<u64> = VecDeque::with_capacity(SETTLE_WINDOW);
+        let mut lifespan = self.max_generations;
+        for generation in 1..=self.max_generations {
+            Grid::new(&cells, size, size)
+                .with_topology(ARENA)
+                .step_into(&self.rule, Neighborhood::Moore, &mut next);
+            std::mem::swap(&mut cells, &mut next);
+
+            let mut hasher = DefaultHasher::new();
+            cells.hash(&mut hasher);
+            let hash = hasher.finish();
+            if let Some(back) = recent.iter().rev().position(|&seen| seen == hash) {
+                // It's been repeating since `back + 1` generations ago.
+                lifespan = generation - back as u32 - 1;
+                break;
+            }
+            if recent.len() == SETTLE_WINDOW {
+                recent.pop_front();
+            }
+            recent.push_back(hash);
+        }
+
+        match self.score {
+            MethuselahScore::Lifespan => lifespan,
+            MethuselahScore::FinalPopulation => cells.iter().map(|&cell| cell as u32).sum(),
+        }
+    }
+
+    // Keeps `seed` among the best if it scores well enough and isn't there
+    // already.
+    fn offer_best(&mut self, score: u32, seed: &[(u32, u32)]) {
+        if self.best.iter().any(|(_, best)| best.as_slice() == seed) {
+            return;
+        }
+        let at = self
+            .best
+            .iter()
+            .position(|&(best, _)| score > best)
+            .unwrap_or(self.best.len());
+        if at < BEST_KEPT {
+            self.best.insert(at, (score, seed.to_vec()));
+            self.best.truncate(BEST_KEPT);
+        }
+    }
+
+    // The better of two seeds picked at random from the scored population.
+    fn tournament(&mut self) -> usize {
+        let len = self.population.len() as u32;
+        let a = (self.random.next_u32() % len) as usize;
+        let b = (self.random.next_u32() % len) as usize;
+        if self.scores[a] >= self.scores[b] {
+            a
+        } else {
+            b
+        }
+    }
+
+    // Each cell of either parent ends up in the child with even chances, so
+    // cells both have are likelier to.
+    fn crossover(&mut self, a: usize, b: usize) -> Seed {
+        let random = &mut self.random;
+        let mut child: Seed = self.population[a]
+            .iter()
+            .chain(self.population[b].iter())
+            .copied()
+            .filter(|_| random.next_u32().is_multiple_of(2))
+            .collect();
+        child.sort_unstable();
+        child.dedup();
+        child
+    }
+
+    // Toggles a cell or two of the seed box.
+    fn mutate(&mut self, seed: &mut Seed) {
+        let flips = 1 + self.random.next_u32() % 2;
+        for _ in 0..flips {
+            let idx = self.random.next_u32() % (self.seed_size * self.seed_size);
+            let cell = (idx / self.seed_size, idx % self.seed_size);
+            match seed.binary_search(&cell) {
+                Ok(at) => {
+                    seed.remove(at);
+                }
+                Err(at) => seed.insert(at, cell),
+            }
+        }
+    }
+
+    // Replaces the scored population with the next generation.
+    fn breed(&mut self) {
+        let mut ranked: Vec<usize> = (0..self.population.len()).collect();
+        ranked.sort_by(|&a, &b| self.scores[b].cmp(&self.scores[a]));
+
+        let mut next: Vec<Seed> = ranked
+            .iter()
+            .take(ELITE)
+            .map(|&idx| self.population[idx].clone())
+            .collect();
+        while next.len() < self.population.len() {
+            let (a, b) = (self.tournament(), self.tournament());
+            let mut child = self.crossover(a, b);
+            self.mutate(&mut child);
+            next.push(child);
+        }
+
+        self.population = next;
+        self.scores.clear();
+        self.generation += 1;
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl MethuselahSearch {
+    // A search over seeds in a `seed_size` square box with
+    // `population_size` seeds to a generation, scoring by lifespan under
+    // Conway's rules by default. Give `seed` to get the same search every
+    // time. Throws if either size is zero, or the seed box is too big.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        seed_size: u32,
+        population_size: u32,
+        seed: Option<u64>,
+    ) -> Result<MethuselahSearch, JsValue> {
+        utils::set_panic_hook();
+
+        if seed_size == 0 || population_size == 0 {
+            return Err(JsValue::from_str("the seeds and population can't be empty"));
+        }
+        check_size(seed_size, seed_size).map_err(|err| JsValue::from_str(&err))?;
+        let mut search = MethuselahSearch {
+            seed_size,
+            arena_size: (seed_size * 8).clamp(64, MAX_DEFAULT_ARENA),
+            max_generations: 1000,
+            rule: Rule::default(),
+            score: MethuselahScore::Lifespan,
+            random: seed.map_or_else(Random::new, Random::seeded),
+            population: Vec::new(),
+            scores: Vec::new(),
+            generation: 0,
+            evaluations: 0,
+            best: Vec::new(),
+        };
+        search.population = (0..population_size).map(|_| search.random_seed()).collect();
+        Ok(search)
+    }
+
+    pub fn score(&self) -> MethuselahScore {
+        self.score
+    }
+
+    // Changing how seeds are scored or run starts the best seeds over, as
+    // they were scored differently.
+    pub fn set_score(&mut self, score: MethuselahScore) {
+        self.score = score;
+        self.best.clear();
+    }
+
+    pub fn max_generations(&self) -> u32 {
+        self.max_generations
+    }
+
+    // The longest each seed is run for. Seeds still going by then score this
+    // as their lifespan.
+    pub fn set_max_generations(&mut self, generations: u32) {
+        self.max_generations = generations.max(1);
+        self.best.clear();
+    }
+
+    pub fn arena_size(&self) -> u32 {
+        self.arena_size
+    }
+
+    // The width and height of the square each seed is run in, with the seed
+    // in the middle, no smaller than the seed box. Anything reaching the
+    // edge is cut off. Throws if the arena would be too big.
+    pub fn set_arena_size(&mut self, size: u32) -> Result<(), JsValue> {
+        let size = size.max(self.seed_size);
+        check_size(size, size).map_err(|err| JsValue::from_str(&err))?;
+        self.arena_size = size;
+        self.best.clear();
+        Ok(())
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        self.rule = Rule::parse(rule).map_err(|err| JsValue::from_str(&err))?;
+        self.best.clear();
+        Ok(())
+    }
+
+    // Generations of seeds bred so far.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    // Seeds run and scored so far.
+    pub fn evaluations(&self) -> u32 {
+        self.evaluations
+    }
+
+    // Scores seeds and breeds new generations for about `budget_ms`
+    // milliseconds, always scoring at least one seed, and returns how many
+    // were scored.
+    pub fn run(&mut self, budget_ms: f64) -> u32 {
+        let start = utils::now_ms();
+        let mut scored = 0;
+        loop {
+            let seed = std::mem::take(&mut self.population[self.scores.len()]);
+            let score = self.evaluate(&seed);
+            self.offer_best(score, &seed);
+            self.population[self.scores.len()] = seed;
+            self.scores.push(score);
+            self.evaluations += 1;
+            scored += 1;
+
+            if self.scores.len() == self.population.len() {
+                self.breed();
+            }
+            if utils::now_ms() - start >= budget_ms {
+                return scored;
+            }
+        }
+    }
+
+    // How many of the best seeds there are so far, up to 10.
+    pub fn best_count(&self) -> u32 {
+        self.best.len() as u32
+    }
+
+    // The score of the `rank`th best seed so far, 0 being the best.
+    pub fn best_score(&self, rank: u32) -> Option<u32> {
+        self.best.get(rank as usize).map(|&(score, _)| score)
+    }
+
+    // The live cells of the `rank`th best seed so far as a flat list of
+    // [row, column, row, column, ...] pairs within the seed box, or an empty
+    // array if there aren't that many.
+    pub fn best_cells(&self, rank: u32) -> Vec<u32> {
+        self.best
+            .get(rank as usize)
+            .map(|(_, seed)| seed.iter().flat_map(|&(row, col)| vec![row, col]).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,251 @@
+use wasm_bindgen::prelude::*;
+
+use life_engine::Nutrients;
+
+use crate::recording::Edit;
+use crate::{Cell, Universe};
+
+// How a universe's nutrient field feeds its cells, the engine's `Nutrients`
+// for JavaScript. A cell is only born where the field has at least
+// `birth_cost`, which it uses up, and only survives where there's at least
+// `survival_min`. Each tick every level moves `diffusion` of the way, from 0
+// to 1, towards the average of its four neighbors, then grows back by
+// `regrowth`, up to `capacity`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NutrientOptions {
+    birth_cost: f32,
+    survival_min: f32,
+    diffusion: f32,
+    regrowth: f32,
+    capacity: f32,
+}
+
+impl Default for NutrientOptions {
+    fn default() -> Self {
+        Nutrients::default().into()
+    }
+}
+
+impl From<Nutrients> for NutrientOptions {
+    fn from(nutrients: Nutrients) -> Self {
+        NutrientOptions {
+            birth_cost: nutrients.birth_cost,
+            survival_min: nutrients.survival_min,
+            diffusion: nutrients.diffusion,
+            regrowth: nutrients.regrowth,
+            capacity: nutrients.capacity,
+        }
+    }
+}
+
+impl From<NutrientOptions> for Nutrients {
+    fn from(options: NutrientOptions) -> Self {
+        Nutrients {
+            birth_cost: options.birth_cost,
+            survival_min: options.survival_min,
+            diffusion: options.diffusion,
+            regrowth: options.regrowth,
+            capacity: options.capacity,
+        }
+    }
+}
+
+impl NutrientOptions {
+    // The options in the order `new` takes them, for saving.
+    pub(crate) fn to_array(self) -> [f32; 5] {
+        [
+            self.birth_cost,
+            self.survival_min,
+            self.diffusion,
+            self.regrowth,
+            self.capacity,
+        ]
+    }
+
+    pub(crate) fn from_array(
+        [birth_cost, survival_min, diffusion, regrowth, capacity]: [f32; 5],
+    ) -> Self {
+        NutrientOptions::new(birth_cost, survival_min, diffusion, regrowth, capacity)
+    }
+}
+
+// The nutrient level under each cell, and a buffer the next tick's levels
+// are written into.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NutrientField {
+    levels: Vec<f32>,
+    next: Vec<f32>,
+}
+
+impl NutrientField {
+    pub(crate) fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Feeds the generation the rules stepped into `next` from the nutrient
+    // field, if there is one, and moves the field on a tick.
+    pub(crate) fn feed_nutrients(&mut self, next: &mut [Cell]) {
+        let field = match &mut self.nutrients {
+            Some(field) => field,
+            None => return,
+        };
+        let nutrients = Nutrients::from(self.nutrient_options);
+        nutrients.feed(&self.cells, next, &mut field.levels);
+        nutrients.diffuse(&field.levels, self.width, self.height, &mut field.next);
+        std::mem::swap(&mut field.levels, &mut field.next);
+    }
+
+    // The nutrient level under each cell, if there's a field.
+    pub(crate) fn nutrient_levels(&self) -> Option<&[f32]> {
+        self.nutrients.as_ref().map(NutrientField::levels)
+    }
+
+    // Lays a nutrient field under the cells with the given levels, one for
+    // each cell, or takes it away.
+    pub(crate) fn set_nutrient_levels(&mut self, levels: Option<Vec<f32>>) {
+        self.nutrients = levels.map(|levels| NutrientField {
+            next: vec![0.0; levels.len()],
+            levels: levels.into_iter().map(|level| level.max(0.0)).collect(),
+        });
+    }
+
+    // Writes the nutrient options as five little-endian f32s, followed by a 1
+    // and the level under each cell, or a 0 without a field.
+    pub(crate) fn put_nutrients(&self, out: &mut Vec<u8>) {
+        for option in self.nutrient_options.to_array().iter() {
+            out.extend_from_slice(&option.to_le_bytes());
+        }
+        match self.nutrient_levels() {
+            Some(levels) => {
+                out.push(1);
+                for level in levels {
+                    out.extend_from_slice(&level.to_le_bytes());
+                }
+            }
+            None => out.push(0),
+        }
+    }
+
+    // Starts the nutrient field over at `level` everywhere, e.g. after a
+    // resize.
+    pub(crate) fn reset_nutrients(&mut self, level: f32) {
+        let len = self.cells.len();
+        if let Some(field) = &mut self.nutrients {
+            field.levels = vec![level; len];
+            field.next = vec![0.0; len];
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl NutrientOptions {
+    pub fn new(
+        birth_cost: f32,
+        survival_min: f32,
+        diffusion: f32,
+        regrowth: f32,
+        capacity: f32,
+    ) -> NutrientOptions {
+        NutrientOptions {
+            birth_cost: birth_cost.max(0.0),
+            survival_min: survival_min.max(0.0),
+            diffusion: diffusion.clamp(0.0, 1.0),
+            regrowth: regrowth.max(0.0),
+            capacity: capacity.max(0.0),
+        }
+    }
+
+    pub fn birth_cost(&self) -> f32 {
+        self.birth_cost
+    }
+
+    pub fn survival_min(&self) -> f32 {
+        self.survival_min
+    }
+
+    pub fn diffusion(&self) -> f32 {
+        self.diffusion
+    }
+
+    pub fn regrowth(&self) -> f32 {
+        self.regrowth
+    }
+
+    pub fn capacity(&self) -> f32 {
+        self.capacity
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Lays a nutrient field under the cells, at `level` everywhere, which
+    // they then have to live off as the nutrient options say. Starts it over
+    // if there already is one.
+    pub fn enable_nutrients(&mut self, level: f32) {
+        self.record(Edit::Nutrients(Some(level)));
+        self.nutrients = Some(NutrientField::default());
+        self.reset_nutrients(level);
+    }
+
+    // Takes the nutrient field away, so the cells tick by the rules alone.
+    pub fn disable_nutrients(&mut self) {
+        self.record(Edit::Nutrients(None));
+        self.nutrients = None;
+    }
+
+    pub fn has_nutrients(&self) -> bool {
+        self.nutrients.is_some()
+    }
+
+    pub fn nutrient_options(&self) -> NutrientOptions {
+        self.nutrient_options
+    }
+
+    pub fn set_nutrient_options(&mut self, options: NutrientOptions) {
+        self.record(Edit::NutrientOptions(options));
+        self.nutrient_options = options;
+    }
+
+    // Pointer to width * height f32 nutrient levels, row by row, for drawing
+    // the field under the cells. Null without a field. Like `cells()`, only
+    // valid until the universe next changes size or the field is enabled
+    // again.
+    pub fn nutrients(&self) -> *const f32 {
+        self.nutrients
+            .as_ref()
+            .map_or(std::ptr::null(), |field| field.levels.as_ptr())
+    }
+
+    // The nutrient level under a cell, or nothing without a field.
+    pub fn nutrient_level(&self, row: u32, column: u32) -> Option<f32> {
+        let idx = self.get_index(row % self.height, column % self.width);
+        self.nutrients.as_ref().map(|field| field.levels[idx])
+    }
+
+    // Sets the nutrient level over the rectangle between two opposite
+    // corners, in either order, like `fill` does the cells. Does nothing
+    // without a field.
+    pub fn fill_nutrients(&mut self, row1: u32, column1: u32, row2: u32, column2: u32, level: f32) {
+        if self.nutrients.is_none() {
+            return;
+        }
+        self.record(Edit::FillNutrients(row1, column1, row2, column2, level));
+        let (max_row, max_col) = (self.height - 1, self.width - 1);
+        let width = self.width;
+        let field = match &mut self.nutrients {
+            Some(field) => field,
+            None => return,
+        };
+        for row in row1.min(row2).min(max_row)..=row1.max(row2).min(max_row) {
+            for col in column1.min(column2).min(max_col)..=column1.max(column2).min(max_col) {
+                field.levels[(row * width + col) as usize] = level.max(0.0);
+            }
+        }
+    }
+}
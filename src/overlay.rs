@@ -0,0 +1,188 @@
+use wasm_bindgen::prelude::*;
+
+use crate::Universe;
+
+// Extras the RGBA renderer can draw on top of the cells. With the defaults the
+// renderer produces one plain pixel per cell; anything else switches it to
+// drawing each cell as a `cell_px` square, optionally separated by 1px grid
+// lines, with the selection tinted and outlined and the cells under the brush
+// tinted.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverlayOptions {
+    cell_px: u32,
+    grid: bool,
+    selection: bool,
+    cursor: bool,
+}
+
+impl Default for OverlayOptions {
+    fn default() -> Self {
+        OverlayOptions {
+            cell_px: 1,
+            grid: false,
+            selection: false,
+            cursor: false,
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl OverlayOptions {
+    pub fn new(cell_px: u32, grid: bool, selection: bool) -> OverlayOptions {
+        OverlayOptions {
+            cell_px: cell_px.max(1),
+            grid,
+            selection,
+            cursor: false,
+        }
+    }
+
+    // The same options with the hovered cell and brush footprint drawn or
+    // not.
+    pub fn with_cursor(&self, cursor: bool) -> OverlayOptions {
+        OverlayOptions { cursor, ..*self }
+    }
+
+    pub fn cell_px(&self) -> u32 {
+        self.cell_px
+    }
+
+    pub fn grid(&self) -> bool {
+        self.grid
+    }
+
+    pub fn selection(&self) -> bool {
+        self.selection
+    }
+
+    pub fn cursor(&self) -> bool {
+        self.cursor
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Width of a grid line in pixels, 0 when the grid is off.
+    fn grid_px(&self) -> u32 {
+        self.overlay.grid as u32
+    }
+
+    // Distance in pixels from the start of one cell to the start of the next.
+    fn cell_pitch(&self) -> u32 {
+        self.overlay.cell_px + self.grid_px()
+    }
+
+    // Fills `out`, which must hold render_width() * render_height() pixels,
+    // with the cells, the pending pattern and every enabled overlay.
+    pub(crate) fn draw_overlaid(&self, out: &mut [u8]) {
+        let stride = self.render_width() as usize;
+        let cell_px = self.overlay.cell_px as usize;
+        let pitch = self.cell_pitch() as usize;
+        let grid = self.grid_px() as usize;
+
+        if self.overlay.grid {
+            let grid_color = self.palette.grid_color();
+            for pixel in out.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&grid_color);
+            }
+        }
+
+        let selection = self.selection.filter(|_| self.overlay.selection);
+        let selection_color = self.palette.selection_color();
+        let cursor_color = self.palette.cursor_color();
+        let hover = self.hover.filter(|_| self.overlay.cursor);
+        let pending = self.pending_mask();
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let mut color = self.frame_color(idx);
+                if pending[idx] {
+                    color = self.ghost_color(color);
+                }
+                if selection.is_some_and(|s| s.contains(row, col)) {
+                    color = blend(color, selection_color);
+                }
+                // The hovered cell is tinted twice so it stands out from the
+                // rest of the brush.
+                if self.overlay.cursor && self.brush_contains(row, col) {
+                    color = blend(color, cursor_color);
+                    if hover == Some((row, col)) {
+                        color = blend(color, cursor_color);
+                    }
+                }
+
+                let x = col as usize * pitch + grid;
+                let y = row as usize * pitch + grid;
+                for py in y..y + cell_px {
+                    let start = (py * stride + x) * 4;
+                    for pixel in out[start..start + cell_px * 4].chunks_exact_mut(4) {
+                        pixel.copy_from_slice(&color);
+                    }
+                }
+            }
+        }
+
+        // Outline the selection, over the grid lines around it if there are
+        // any or else along the edges of its outermost cells.
+        if let Some(s) = selection {
+            let outline = [
+                selection_color[0],
+                selection_color[1],
+                selection_color[2],
+                0xff,
+            ];
+            let left = s.left as usize * pitch;
+            let top = s.top as usize * pitch;
+            let right = (s.right as usize + 1) * pitch + grid - 1;
+            let bottom = (s.bottom as usize + 1) * pitch + grid - 1;
+
+            for x in left..=right {
+                for &y in &[top, bottom] {
+                    let start = (y * stride + x) * 4;
+                    out[start..start + 4].copy_from_slice(&outline);
+                }
+            }
+            for y in top..=bottom {
+                for &x in &[left, right] {
+                    let start = (y * stride + x) * 4;
+                    out[start..start + 4].copy_from_slice(&outline);
+                }
+            }
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn overlay_options(&self) -> OverlayOptions {
+        self.overlay
+    }
+
+    pub fn set_overlay_options(&mut self, options: OverlayOptions) {
+        self.overlay = options;
+    }
+
+    // Width in pixels of the image produced by the RGBA renderer.
+    pub fn render_width(&self) -> u32 {
+        self.width * self.cell_pitch() + self.grid_px()
+    }
+
+    // Height in pixels of the image produced by the RGBA renderer.
+    pub fn render_height(&self) -> u32 {
+        self.height * self.cell_pitch() + self.grid_px()
+    }
+}
+
+// Draws a translucent `over` color on top of `under`.
+fn blend(under: [u8; 4], over: [u8; 4]) -> [u8; 4] {
+    let alpha = over[3] as u32;
+    let mut out = under;
+    for i in 0..3 {
+        out[i] = ((under[i] as u32 * (255 - alpha) + over[i] as u32 * alpha) / 255) as u8;
+    }
+    out
+}
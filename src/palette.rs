@@ -0,0 +1,234 @@
+use wasm_bindgen::prelude::*;
+
+use crate::Cell;
+
+// The colors every renderer draws with. Each color is packed as 0xRRGGBBAA so
+// it can be written as a hex literal on the JS side.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    alive: u32,
+    dead: u32,
+    wall: u32,
+    grid: u32,
+    // Behind the cells, wherever a renderer draws outside of them.
+    background: u32,
+    // Drawn over selected cells, blended by its alpha.
+    selection: u32,
+    // Drawn over the cells under the brush, blended by its alpha.
+    cursor: u32,
+    // Evenly spaced stops used by the age and heat render modes.
+    gradient: Vec<u32>,
+    // Age at which a live cell reaches the end of the gradient.
+    age_span: u32,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::classic()
+    }
+}
+
+// Methods not being exported to Javascript
+impl Palette {
+    pub(crate) fn color(&self, cell: Cell) -> [u8; 4] {
+        match cell {
+            Cell::Alive => self.alive.to_be_bytes(),
+            Cell::Dead => self.dead.to_be_bytes(),
+            Cell::Wall => self.wall.to_be_bytes(),
+        }
+    }
+
+    pub(crate) fn grid_color(&self) -> [u8; 4] {
+        self.grid.to_be_bytes()
+    }
+
+    pub(crate) fn background_color(&self) -> [u8; 4] {
+        self.background.to_be_bytes()
+    }
+
+    pub(crate) fn selection_color(&self) -> [u8; 4] {
+        self.selection.to_be_bytes()
+    }
+
+    pub(crate) fn cursor_color(&self) -> [u8; 4] {
+        self.cursor.to_be_bytes()
+    }
+
+    // Color `t` of the way along the gradient, where `t` is from 0 to 1.
+    pub(crate) fn gradient(&self, t: f32) -> [u8; 4] {
+        match self.gradient.len() {
+            0 => self.alive.to_be_bytes(),
+            1 => self.gradient[0].to_be_bytes(),
+            stops => {
+                let position = t.clamp(0.0, 1.0) * (stops - 1) as f32;
+                let i = (position.floor() as usize).min(stops - 2);
+                lerp(
+                    self.gradient[i].to_be_bytes(),
+                    self.gradient[i + 1].to_be_bytes(),
+                    position - i as f32,
+                )
+            }
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Palette {
+    // A palette with the given live and dead cell colors and the classic
+    // palette's other colors.
+    pub fn new(alive: u32, dead: u32) -> Palette {
+        Palette {
+            alive,
+            dead,
+            ..Palette::classic()
+        }
+    }
+
+    // Black cells on white with a light grey grid, like the original canvas.
+    pub fn classic() -> Palette {
+        Palette {
+            alive: 0x000000ff,
+            dead: 0xffffffff,
+            wall: 0x808080ff,
+            grid: 0xccccccff,
+            background: 0xffffffff,
+            selection: 0x3080ff60,
+            cursor: 0xff800060,
+            gradient: vec![0x2040ffff, 0xff3020ff],
+            age_span: 100,
+        }
+    }
+
+    // Light cells on a near-black background.
+    pub fn dark() -> Palette {
+        Palette {
+            alive: 0xe0e0e0ff,
+            dead: 0x111111ff,
+            wall: 0x8a6d3bff,
+            grid: 0x333333ff,
+            background: 0x000000ff,
+            selection: 0x3fa7d660,
+            cursor: 0xf9f87160,
+            gradient: vec![0x1b3a6bff, 0x3fa7d6ff, 0xf9f871ff],
+            age_span: 100,
+        }
+    }
+
+    // Ethan Schoonover's Solarized (dark) colors.
+    pub fn solarized() -> Palette {
+        Palette {
+            alive: 0x93a1a1ff,
+            dead: 0x002b36ff,
+            wall: 0x586e75ff,
+            grid: 0x073642ff,
+            background: 0x002b36ff,
+            selection: 0x268bd260,
+            cursor: 0xb5890060,
+            gradient: vec![
+                0x268bd2ff, 0x2aa198ff, 0x859900ff, 0xb58900ff, 0xcb4b16ff, 0xdc322fff,
+            ],
+            age_span: 100,
+        }
+    }
+
+    pub fn alive(&self) -> u32 {
+        self.alive
+    }
+
+    pub fn set_alive(&mut self, alive: u32) {
+        self.alive = alive;
+    }
+
+    pub fn dead(&self) -> u32 {
+        self.dead
+    }
+
+    pub fn set_dead(&mut self, dead: u32) {
+        self.dead = dead;
+    }
+
+    pub fn wall(&self) -> u32 {
+        self.wall
+    }
+
+    pub fn set_wall(&mut self, wall: u32) {
+        self.wall = wall;
+    }
+
+    pub fn grid(&self) -> u32 {
+        self.grid
+    }
+
+    pub fn set_grid(&mut self, grid: u32) {
+        self.grid = grid;
+    }
+
+    pub fn background(&self) -> u32 {
+        self.background
+    }
+
+    pub fn set_background(&mut self, background: u32) {
+        self.background = background;
+    }
+
+    pub fn selection(&self) -> u32 {
+        self.selection
+    }
+
+    pub fn set_selection(&mut self, selection: u32) {
+        self.selection = selection;
+    }
+
+    pub fn cursor(&self) -> u32 {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: u32) {
+        self.cursor = cursor;
+    }
+
+    pub fn gradient_stops(&self) -> Vec<u32> {
+        self.gradient.clone()
+    }
+
+    // Sets the colors the age and heat render modes blend between, spaced
+    // evenly from youngest/coolest to oldest/hottest.
+    pub fn set_gradient_stops(&mut self, stops: Vec<u32>) {
+        self.gradient = stops;
+    }
+
+    pub fn age_span(&self) -> u32 {
+        self.age_span
+    }
+
+    // Sets how many generations a cell has to live to reach the end of the
+    // gradient in age mode.
+    pub fn set_age_span(&mut self, age_span: u32) {
+        self.age_span = age_span.max(1);
+    }
+}
+
+// Splits a packed 0xRRGGBBAA color into floats from 0 to 1, as shaders
+// expect them.
+#[cfg(feature = "browser")]
+pub(crate) fn to_floats(color: u32) -> [f32; 4] {
+    let [r, g, b, a] = color.to_be_bytes();
+    [
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        a as f32 / 255.0,
+    ]
+}
+
+// Blends two RGBA colors, `t` of the way from `from` to `to`.
+pub(crate) fn lerp(from: [u8; 4], to: [u8; 4], t: f32) -> [u8; 4] {
+    let t = t.clamp(0.0, 1.0);
+    let mut out = [0; 4];
+    for i in 0..4 {
+        out[i] = (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t).round() as u8;
+    }
+    out
+}
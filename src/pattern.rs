@@ -0,0 +1,164 @@
+use life_engine::patterns;
+use wasm_bindgen::prelude::*;
+
+use crate::palette::lerp;
+use crate::recording::Edit;
+use crate::{Cell, Universe};
+
+// How far the preview of a pending pattern shades cells towards the live
+// color, from 0 to 1.
+const GHOST_ALPHA: f32 = 0.5;
+
+// Well known patterns that can be stamped into the universe.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    Glider = 0,
+    Pulsar = 1,
+    GosperGliderGun = 2,
+}
+
+impl Pattern {
+    // The pattern called `name`, in the kebab case the element attributes
+    // and scripts use, like "gosper-glider-gun".
+    pub(crate) fn from_name(name: &str) -> Option<Pattern> {
+        match name {
+            "glider" => Some(Pattern::Glider),
+            "pulsar" => Some(Pattern::Pulsar),
+            "gosper-glider-gun" => Some(Pattern::GosperGliderGun),
+            _ => None,
+        }
+    }
+
+    // The live cells of the pattern as (row, column) offsets from its top-left
+    // corner.
+    fn cells(self) -> Vec<(u32, u32)> {
+        match self {
+            Pattern::Glider => patterns::glider(),
+            Pattern::Pulsar => patterns::pulsar(),
+            Pattern::GosperGliderGun => patterns::gosper_glider_gun(),
+        }
+    }
+}
+
+// A pattern waiting to be stamped, centered on (row, column) and turned
+// clockwise by `rotation` quarter turns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PendingPattern {
+    pattern: Pattern,
+    row: u32,
+    column: u32,
+    rotation: u32,
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // The cells the pending pattern would bring to life, wrapped around the
+    // edges of the universe.
+    fn pending_positions(&self) -> Vec<(u32, u32)> {
+        match self.pending {
+            Some(pending) => self.pattern_positions(
+                pending.pattern,
+                pending.row,
+                pending.column,
+                pending.rotation,
+            ),
+            None => Vec::new(),
+        }
+    }
+
+    // The cells `pattern` covers when centered on (row, column) and turned
+    // clockwise by `rotation` quarter turns, wrapped around the edges of the
+    // universe.
+    fn pattern_positions(
+        &self,
+        pattern: Pattern,
+        row: u32,
+        column: u32,
+        rotation: u32,
+    ) -> Vec<(u32, u32)> {
+        let rotated = patterns::rotate(pattern.cells(), rotation);
+        let size = patterns::size(&rotated);
+
+        let top = row % self.height + self.height * 2 - (size.0 / 2) % self.height;
+        let left = column % self.width + self.width * 2 - (size.1 / 2) % self.width;
+        rotated
+            .into_iter()
+            .map(|(row, col)| ((top + row) % self.height, (left + col) % self.width))
+            .collect()
+    }
+
+    // One flag per cell, set for the cells covered by the pending pattern.
+    pub(crate) fn pending_mask(&self) -> Vec<bool> {
+        let mut mask = vec![false; self.cells.len()];
+        for (row, col) in self.pending_positions() {
+            mask[self.get_index(row, col)] = true;
+        }
+        mask
+    }
+
+    // Brings `pattern` to life centered on (row, column) and turned clockwise
+    // by `rotation` quarter turns.
+    pub(crate) fn stamp(&mut self, pattern: Pattern, row: u32, column: u32, rotation: u32) {
+        self.record(Edit::Stamp {
+            pattern,
+            row,
+            column,
+            rotation,
+        });
+        let cells = self.pattern_positions(pattern, row, column, rotation);
+        self.set_alive(&cells);
+        #[cfg(feature = "browser")]
+        self.dispatch_pattern_placed(pattern, row, column, rotation);
+    }
+
+    // Shades a cell's color to show the pending pattern over it.
+    pub(crate) fn ghost_color(&self, color: [u8; 4]) -> [u8; 4] {
+        lerp(color, self.palette.color(Cell::Alive), GHOST_ALPHA)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Shows a translucent preview of `pattern` centered on (row, column) and
+    // turned `rotation` quarter turns clockwise, without changing any cells.
+    // Meant to follow the pointer until the user clicks to commit it.
+    pub fn set_pending_pattern(&mut self, pattern: Pattern, row: u32, column: u32, rotation: u32) {
+        self.pending = Some(PendingPattern {
+            pattern,
+            row: row % self.height,
+            column: column % self.width,
+            rotation: rotation % 4,
+        });
+    }
+
+    pub fn clear_pending(&mut self) {
+        self.pending = None;
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // The cells the pending pattern covers as a flat list of [row, column,
+    // row, column, ...] pairs, for canvas renderers to draw the preview.
+    pub fn pending_cells(&self) -> Vec<u32> {
+        self.pending_positions()
+            .into_iter()
+            .flat_map(|(row, col)| vec![row, col])
+            .collect()
+    }
+
+    // Stamps the pending pattern into the universe and removes the preview.
+    pub fn commit_pending(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.stamp(
+                pending.pattern,
+                pending.row,
+                pending.column,
+                pending.rotation,
+            );
+        }
+    }
+}
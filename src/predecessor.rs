@@ -0,0 +1,117 @@
+use wasm_bindgen::prelude::*;
+
+use crate::selection::Selection;
+use crate::{Cell, Universe};
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Looks for cells one generation back that tick into the cells in
+    // `region`, by trying cells of the region and a one cell border around it
+    // alive or dead in turn, row by row, and backing up as soon as a cell of
+    // the region comes out wrong. The region is taken on its own, without
+    // wrapping or the cells around it. Ok(None) means the whole search was
+    // made and nothing works; an error means it was given up on after
+    // `max_candidates` cells were tried.
+    fn predecessor(
+        &self,
+        region: Selection,
+        max_candidates: u32,
+    ) -> Result<Option<(u32, u32, Vec<Cell>)>, String> {
+        let height = region.bottom - region.top + 1;
+        let width = region.right - region.left + 1;
+        let (rows, cols) = (height as usize + 2, width as usize + 2);
+
+        // Whether the region's cell under (row, col) of the predecessor comes
+        // out right, given every cell around it has been tried.
+        let fits = |cells: &[Cell], row: usize, col: usize| {
+            let mut neighbors = 0;
+            for r in row - 1..=row + 1 {
+                for c in col - 1..=col + 1 {
+                    if (r, c) != (row, col) && cells[r * cols + c] == Cell::Alive {
+                        neighbors += 1;
+                    }
+                }
+            }
+            let target = self.cells[self.get_index(
+                region.top + row as u32 - 1,
+                region.left + col as u32 - 1,
+            )];
+            self.rule.next(cells[row * cols + col], neighbors) == target
+        };
+
+        let mut cells = vec![Cell::Dead; rows * cols];
+        let mut idx = 0;
+        let mut candidates = 0;
+        loop {
+            candidates += 1;
+            if candidates > max_candidates {
+                return Err(format!(
+                    "gave up after {} candidates without finding a predecessor",
+                    max_candidates
+                ));
+            }
+
+            // Trying the cell at (row, col) settles the cell of the region
+            // up and to its left.
+            let (row, col) = (idx / cols, idx % cols);
+            if row < 2 || col < 2 || fits(&cells, row - 1, col - 1) {
+                if idx + 1 == cells.len() {
+                    return Ok(Some((height + 2, width + 2, cells)));
+                }
+                idx += 1;
+                cells[idx] = Cell::Dead;
+                continue;
+            }
+
+            // Back up to the last cell still to be tried alive.
+            while cells[idx] == Cell::Alive {
+                if idx == 0 {
+                    return Ok(None);
+                }
+                idx -= 1;
+            }
+            cells[idx] = Cell::Alive;
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Searches for a previous generation of the rectangle between two
+    // opposite corners, in either order: cells that tick into what's there
+    // now by the universe's rule. Returns them as a universe two cells wider
+    // and taller than the rectangle, since cells just outside it matter too,
+    // or nothing if there can't be any, making the rectangle a Garden of Eden
+    // whatever surrounds it. Corners past the edges are pulled back inside
+    // the universe.
+    //
+    // The search is a plain backtracking one and can take a very long time,
+    // so it tries at most `max_candidates` cells and throws if that isn't
+    // enough to decide. Small regions and sparse patterns go quickest.
+    pub fn find_predecessor(
+        &self,
+        row1: u32,
+        column1: u32,
+        row2: u32,
+        column2: u32,
+        max_candidates: u32,
+    ) -> Result<Option<Universe>, JsValue> {
+        let (max_row, max_col) = (self.height - 1, self.width - 1);
+        let region = Selection {
+            top: row1.min(row2).min(max_row),
+            left: column1.min(column2).min(max_col),
+            bottom: row1.max(row2).min(max_row),
+            right: column1.max(column2).min(max_col),
+        };
+
+        let found = self
+            .predecessor(region, max_candidates)
+            .map_err(|err| JsValue::from_str(&err))?;
+        Ok(found.map(|(height, width, cells)| {
+            let mut predecessor = Universe::with_cells(width, height, cells);
+            predecessor.rule = self.rule;
+            predecessor
+        }))
+    }
+}
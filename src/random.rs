@@ -0,0 +1,71 @@
+// Random numbers for filling universes and picking replica ids. The numbers
+// come from SplitMix64, so the same seed gives the same numbers whichever
+// features are on. Only where unseeded generators start from depends on
+// them: the system's entropy with `rand`, or the clock without it, so builds
+// that leave it out don't need getrandom at all.
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+pub(crate) struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub(crate) fn seeded(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub(crate) fn new() -> Random {
+        Random::seeded(entropy())
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // A number from 0 up to but not including 1.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(feature = "rand")]
+fn entropy() -> u64 {
+    rand::random()
+}
+
+// Without `rand` every generator gets the next seed along, so they differ
+// from each other, mixed with the clock so they differ from run to run and
+// from peer to peer.
+#[cfg(not(feature = "rand"))]
+fn entropy() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_SEED: AtomicU64 = AtomicU64::new(0x853c_49e6_748f_ea9b);
+    let seed = NEXT_SEED.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed);
+    Random::seeded(seed ^ clock()).next_u64()
+}
+
+// Math.random() stands in for the clock in the browser, where the time can be
+// coarse.
+#[cfg(all(not(feature = "rand"), target_arch = "wasm32"))]
+fn clock() -> u64 {
+    (js_sys::Math::random() * (1u64 << 53) as f64) as u64 ^ js_sys::Date::now() as u64
+}
+
+#[cfg(all(not(feature = "rand"), not(target_arch = "wasm32")))]
+fn clock() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
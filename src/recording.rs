@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::rule::Rule;
+use crate::{check_size, Cell, Edge, Neighborhood, NutrientOptions, Pattern, Universe};
+
+// A change made to the universe from outside, rather than by ticking.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Edit {
+    // The state when recording started, or when the cells were replaced
+    // wholesale since: the size and the live cell indexes.
+    Start {
+        width: u32,
+        height: u32,
+        alive: Vec<u32>,
+    },
+    Width(u32),
+    Height(u32),
+    Toggle(u32, u32),
+    Glider(u32, u32),
+    Pulsar(u32, u32),
+    Stamp {
+        pattern: Pattern,
+        row: u32,
+        column: u32,
+        rotation: u32,
+    },
+    // A pattern's live cell indexes, in a pattern `width` wide, pasted with
+    // its top-left corner at (row, column).
+    Paste {
+        row: u32,
+        column: u32,
+        width: u32,
+        alive: Vec<u32>,
+    },
+    Kill,
+    Fill(u32, u32, u32, u32),
+    Wall(u32, u32),
+    FillWalls(u32, u32, u32, u32),
+    ClearWalls(u32, u32, u32, u32),
+    Rule(String),
+    Neighborhood(Neighborhood),
+    Topology(Edge, Edge),
+    WallsAlive(bool),
+    // A nutrient field laid at the given level, or taken away.
+    Nutrients(Option<f32>),
+    NutrientOptions(NutrientOptions),
+    FillNutrients(u32, u32, u32, u32, f32),
+}
+
+// Every edit made since recording started, each with the generation it was
+// made at.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Recording {
+    edits: Vec<(u32, Edit)>,
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edit::Start {
+                width,
+                height,
+                alive,
+            } => {
+                write!(f, "start {} {}", width, height)?;
+                for idx in alive {
+                    write!(f, " {}", idx)?;
+                }
+                Ok(())
+            }
+            Edit::Width(width) => write!(f, "width {}", width),
+            Edit::Height(height) => write!(f, "height {}", height),
+            Edit::Toggle(row, col) => write!(f, "toggle {} {}", row, col),
+            Edit::Glider(row, col) => write!(f, "glider {} {}", row, col),
+            Edit::Pulsar(row, col) => write!(f, "pulsar {} {}", row, col),
+            Edit::Stamp {
+                pattern,
+                row,
+                column,
+                rotation,
+            } => write!(
+                f,
+                "stamp {} {} {} {}",
+                *pattern as u32, row, column, rotation
+            ),
+            Edit::Paste {
+                row,
+                column,
+                width,
+                alive,
+            } => {
+                write!(f, "paste {} {} {}", row, column, width)?;
+                for idx in alive {
+                    write!(f, " {}", idx)?;
+                }
+                Ok(())
+            }
+            Edit::Kill => write!(f, "kill"),
+            Edit::Fill(top, left, bottom, right) => {
+                write!(f, "fill {} {} {} {}", top, left, bottom, right)
+            }
+            Edit::Wall(row, col) => write!(f, "wall {} {}", row, col),
+            Edit::FillWalls(top, left, bottom, right) => {
+                write!(f, "fill-walls {} {} {} {}", top, left, bottom, right)
+            }
+            Edit::ClearWalls(top, left, bottom, right) => {
+                write!(f, "clear-walls {} {} {} {}", top, left, bottom, right)
+            }
+            Edit::Rule(rule) => write!(f, "rule {}", rule),
+            Edit::Neighborhood(neighborhood) => {
+                write!(f, "neighborhood {}", *neighborhood as u32)
+            }
+            Edit::Topology(horizontal, vertical) => {
+                write!(f, "topology {} {}", horizontal.name(), vertical.name())
+            }
+            Edit::WallsAlive(alive) => write!(f, "walls-alive {}", *alive as u32),
+            Edit::Nutrients(Some(level)) => write!(f, "nutrients {}", level),
+            Edit::Nutrients(None) => write!(f, "no-nutrients"),
+            Edit::NutrientOptions(options) => {
+                write!(f, "nutrient-options")?;
+                for option in options.to_array().iter() {
+                    write!(f, " {}", option)?;
+                }
+                Ok(())
+            }
+            Edit::FillNutrients(top, left, bottom, right, level) => {
+                write!(f, "fill-nutrients {} {} {} {} {}", top, left, bottom, right, level)
+            }
+        }
+    }
+}
+
+impl Edit {
+    fn parse(words: &[&str]) -> Result<Edit, String> {
+        if words[0] == "rule" {
+            return match words {
+                [_, rule] => Ok(Edit::Rule(rule.to_string())),
+                _ => Err(String::from("`rule` takes a rule like B3/S23")),
+            };
+        }
+        if let "nutrients" | "nutrient-options" | "fill-nutrients" = words[0] {
+            return Edit::parse_nutrients(words);
+        }
+        if words[0] == "topology" {
+            return match words {
+                [_, horizontal, vertical] => Ok(Edit::Topology(
+                    Edge::from_name(horizontal)?,
+                    Edge::from_name(vertical)?,
+                )),
+                _ => Err(String::from("`topology` takes two edges like wrap flip")),
+            };
+        }
+
+        let numbers = words[1..]
+            .iter()
+            .map(|word| word.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|err| err.to_string())?;
+        let arity = |n: usize| {
+            if numbers.len() == n {
+                Ok(())
+            } else {
+                Err(format!("`{}` takes {} numbers", words[0], n))
+            }
+        };
+
+        match words[0] {
+            "start" if numbers.len() >= 2 => Ok(Edit::Start {
+                width: numbers[0],
+                height: numbers[1],
+                alive: numbers[2..].to_vec(),
+            }),
+            "width" => arity(1).map(|_| Edit::Width(numbers[0])),
+            "height" => arity(1).map(|_| Edit::Height(numbers[0])),
+            "toggle" => arity(2).map(|_| Edit::Toggle(numbers[0], numbers[1])),
+            "glider" => arity(2).map(|_| Edit::Glider(numbers[0], numbers[1])),
+            "pulsar" => arity(2).map(|_| Edit::Pulsar(numbers[0], numbers[1])),
+            "stamp" => {
+                arity(4)?;
+                let pattern = match numbers[0] {
+                    0 => Pattern::Glider,
+                    1 => Pattern::Pulsar,
+                    2 => Pattern::GosperGliderGun,
+                    other => return Err(format!("unknown pattern {}", other)),
+                };
+                Ok(Edit::Stamp {
+                    pattern,
+                    row: numbers[1],
+                    column: numbers[2],
+                    rotation: numbers[3],
+                })
+            }
+            "paste" if numbers.len() >= 3 && numbers[2] > 0 => Ok(Edit::Paste {
+                row: numbers[0],
+                column: numbers[1],
+                width: numbers[2],
+                alive: numbers[3..].to_vec(),
+            }),
+            "kill" => arity(0).map(|_| Edit::Kill),
+            "fill" => arity(4).map(|_| Edit::Fill(numbers[0], numbers[1], numbers[2], numbers[3])),
+            "wall" => arity(2).map(|_| Edit::Wall(numbers[0], numbers[1])),
+            "fill-walls" => arity(4)
+                .map(|_| Edit::FillWalls(numbers[0], numbers[1], numbers[2], numbers[3])),
+            "clear-walls" => arity(4)
+                .map(|_| Edit::ClearWalls(numbers[0], numbers[1], numbers[2], numbers[3])),
+            "neighborhood" => {
+                arity(1)?;
+                match numbers[0] {
+                    0 => Ok(Edit::Neighborhood(Neighborhood::Moore)),
+                    1 => Ok(Edit::Neighborhood(Neighborhood::Hex)),
+                    other => Err(format!("unknown neighborhood {}", other)),
+                }
+            }
+            "walls-alive" => arity(1).map(|_| Edit::WallsAlive(numbers[0] != 0)),
+            "no-nutrients" => arity(0).map(|_| Edit::Nutrients(None)),
+            other => Err(format!("unknown edit `{}`", other)),
+        }
+    }
+
+    // Nutrient edits, whose levels and options aren't whole numbers.
+    fn parse_nutrients(words: &[&str]) -> Result<Edit, String> {
+        let level = |word: &str| word.parse::<f32>().map_err(|err| err.to_string());
+        let number = |word: &str| word.parse::<u32>().map_err(|err| err.to_string());
+        match words {
+            ["nutrients", value] => Ok(Edit::Nutrients(Some(level(value)?))),
+            ["nutrient-options", birth_cost, survival_min, diffusion, regrowth, capacity] => {
+                Ok(Edit::NutrientOptions(NutrientOptions::new(
+                    level(birth_cost)?,
+                    level(survival_min)?,
+                    level(diffusion)?,
+                    level(regrowth)?,
+                    level(capacity)?,
+                )))
+            }
+            ["fill-nutrients", top, left, bottom, right, value] => {
+                Ok(Edit::FillNutrients(
+                    number(top)?,
+                    number(left)?,
+                    number(bottom)?,
+                    number(right)?,
+                    level(value)?,
+                ))
+            }
+            _ => Err(format!("`{}` takes the wrong number of numbers", words[0])),
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Adds an edit to the recording, if one is running.
+    pub(crate) fn record(&mut self, edit: Edit) {
+        if let Some(recording) = &mut self.recording {
+            recording.edits.push((self.generation, edit));
+        }
+    }
+
+    // Adds the current cells and settings to the recording, if one is
+    // running, for changes that replace the cells wholesale rather than as
+    // edits that can be replayed.
+    pub(crate) fn record_snapshot(&mut self) {
+        if self.recording.is_none() {
+            return;
+        }
+
+        let alive = self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|&(_, &cell)| cell == Cell::Alive)
+            .map(|(idx, _)| idx as u32)
+            .collect();
+        self.record(Edit::Start {
+            width: self.width,
+            height: self.height,
+            alive,
+        });
+
+        // Walls the snapshot leaves out go up again one at a time.
+        let width = self.width;
+        for idx in 0..self.cells.len() as u32 {
+            if self.cells[idx as usize] == Cell::Wall {
+                self.record(Edit::Wall(idx / width, idx % width));
+            }
+        }
+        if self.rule != Rule::default() {
+            self.record(Edit::Rule(self.rule.to_string()));
+        }
+        if self.neighborhood != Neighborhood::Moore {
+            self.record(Edit::Neighborhood(self.neighborhood));
+        }
+        if self.topology != Topology::TORUS {
+            self.record(Edit::Topology(
+                self.topology.horizontal.into(),
+                self.topology.vertical.into(),
+            ));
+        }
+        if self.walls_alive {
+            self.record(Edit::WallsAlive(true));
+        }
+        if self.nutrient_options != NutrientOptions::default() {
+            self.record(Edit::NutrientOptions(self.nutrient_options));
+        }
+        // The field's levels vary from cell to cell, so it's laid at the most
+        // common one and the rest are filled in a cell at a time.
+        if let Some(levels) = self.nutrient_levels().map(<[f32]>::to_vec) {
+            let mut counts = HashMap::new();
+            for level in &levels {
+                *counts.entry(level.to_bits()).or_insert(0) += 1;
+            }
+            let common = counts
+                .into_iter()
+                .max_by_key(|&(_, count)| count)
+                .map_or(0.0, |(bits, _)| f32::from_bits(bits));
+            self.record(Edit::Nutrients(Some(common)));
+            for (idx, level) in (0..).zip(levels) {
+                if level.to_bits() != common.to_bits() {
+                    let (row, col) = (idx / width, idx % width);
+                    self.record(Edit::FillNutrients(row, col, row, col, level));
+                }
+            }
+        }
+    }
+
+    fn apply_edit(&mut self, edit: Edit) -> Result<(), String> {
+        match edit {
+            Edit::Start {
+                width,
+                height,
+                alive,
+            } => {
+                check_size(width, height)?;
+                let mut cells = vec![Cell::Dead; width as usize * height as usize];
+                for idx in alive {
+                    *cells
+                        .get_mut(idx as usize)
+                        .ok_or_else(|| format!("cell {} is outside the universe", idx))? =
+                        Cell::Alive;
+                }
+                *self = Universe::with_cells(width, height, cells);
+            }
+            Edit::Width(width) => {
+                check_size(width, self.height)?;
+                self.resize(width, self.height);
+            }
+            Edit::Height(height) => {
+                check_size(self.width, height)?;
+                self.resize(self.width, height);
+            }
+            Edit::Toggle(row, col) => {
+                let (row, col) = self.position(row, col)?;
+                self.toggle(row, col);
+            }
+            Edit::Glider(row, col) => {
+                let (row, col) = self.position(row, col)?;
+                self.glider(row, col);
+            }
+            Edit::Pulsar(row, col) => {
+                let (row, col) = self.position(row, col)?;
+                self.pulsar(row, col);
+            }
+            Edit::Stamp {
+                pattern,
+                row,
+                column,
+                rotation,
+            } => self.stamp(pattern, row, column, rotation),
+            Edit::Paste {
+                row,
+                column,
+                width,
+                alive,
+            } => self.paste_cells(row, column, width, &alive),
+            Edit::Kill => self.kill_universe(),
+            Edit::Fill(top, left, bottom, right) => self.fill(top, left, bottom, right),
+            Edit::Wall(row, col) => {
+                let (row, col) = self.position(row, col)?;
+                self.toggle_wall_at(row, col);
+            }
+            Edit::FillWalls(top, left, bottom, right) => self.fill_walls(top, left, bottom, right),
+            Edit::ClearWalls(top, left, bottom, right) => {
+                self.clear_walls(top, left, bottom, right)
+            }
+            Edit::Rule(rule) => self.set_rule_text(&rule)?,
+            Edit::Neighborhood(neighborhood) => self.set_neighborhood(neighborhood),
+            Edit::Topology(horizontal, vertical) => self.set_topology(horizontal, vertical),
+            Edit::WallsAlive(alive) => self.set_walls_count_as_alive(alive),
+            Edit::Nutrients(Some(level)) => self.enable_nutrients(level),
+            Edit::Nutrients(None) => self.disable_nutrients(),
+            Edit::NutrientOptions(options) => self.set_nutrient_options(options),
+            Edit::FillNutrients(top, left, bottom, right, level) => {
+                self.fill_nutrients(top, left, bottom, right, level)
+            }
+        }
+        Ok(())
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Starts recording every toggle, pattern placement, fill, wall, rule,
+    // neighborhood, topology, wall or nutrient setting change, nutrient fill,
+    // resize and kill, along with the generation each happened at, starting
+    // from a snapshot of the current cells, settings and nutrient field.
+    // Stepping back, seeking the timeline, and taking in cells from a sync
+    // message, merged ops, a save slot, a link or a shared buffer record a
+    // fresh snapshot. Restarts the recording if one is already running.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::default());
+        self.record_snapshot();
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // The recording so far as text, one edit per line prefixed with its
+    // generation, and ending with the current generation. Empty when not
+    // recording.
+    pub fn recording(&self) -> String {
+        let recording = match &self.recording {
+            Some(recording) => recording,
+            None => return String::new(),
+        };
+
+        let mut out = String::new();
+        for (generation, edit) in &recording.edits {
+            out.push_str(&format!("{} {}\n", generation, edit));
+        }
+        out.push_str(&format!("{} end\n", self.generation));
+        out
+    }
+
+    // Rebuilds a universe from a recording, redoing every edit at the
+    // generation it was made and ticking in between, and ending at the
+    // generation the recording ended at.
+    pub fn replay(log: &str) -> Result<Universe, JsValue> {
+        let mut universe = Universe::with_cells(1, 1, vec![Cell::Dead]);
+        let mut started = false;
+
+        for (number, line) in log.lines().enumerate() {
+            let fail =
+                |message: String| JsValue::from_str(&format!("line {}: {}", number + 1, message));
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            if words.len() < 2 {
+                return Err(fail(String::from("expected a generation and an edit")));
+            }
+
+            let generation = words[0]
+                .parse::<u32>()
+                .map_err(|err| fail(err.to_string()))?;
+
+            // A snapshot can go back in time, after stepping back or seeking.
+            if words[1] == "start" {
+                let edit = Edit::parse(&words[1..]).map_err(fail)?;
+                universe.apply_edit(edit).map_err(fail)?;
+                universe.generation = generation;
+                started = true;
+                continue;
+            }
+            if !started {
+                return Err(fail(String::from("recordings begin with `start`")));
+            }
+
+            if generation < universe.generation {
+                return Err(fail(format!("generation {} is already past", generation)));
+            }
+            universe.advance_to(generation);
+
+            if words[1] == "end" {
+                break;
+            }
+            let edit = Edit::parse(&words[1..]).map_err(fail)?;
+            universe.apply_edit(edit).map_err(fail)?;
+        }
+
+        if started {
+            Ok(universe)
+        } else {
+            Err(JsValue::from_str("the recording is empty"))
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use wasm_bindgen::prelude::*;
+
+use crate::palette::lerp;
+use crate::{Cell, OverlayOptions, Palette, Universe, MAX_CELLS};
+
+// How cells are colored by the pixel renderers.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    // Alive or dead colors only.
+    Binary = 0,
+    // Live cells shaded along the gradient by how long they have been alive.
+    Age = 1,
+    // Every cell shaded along the gradient by how recently it changed state.
+    Heat = 2,
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // The color of the cell at `idx` under the current render mode.
+    pub(crate) fn pixel_color(&self, idx: usize) -> [u8; 4] {
+        let cell = self.cells[idx];
+        if cell == Cell::Wall {
+            return self.palette.color(cell);
+        }
+
+        match self.render_mode {
+            RenderMode::Binary => match cell {
+                Cell::Alive => self.palette.color(cell),
+                _ => self.dead_color(idx, self.palette.color(Cell::Alive)),
+            },
+            RenderMode::Age => match cell {
+                Cell::Alive => {
+                    let t = self.ages[idx] as f32 / self.palette.age_span() as f32;
+                    self.palette.gradient(t)
+                }
+                _ => self.dead_color(idx, self.palette.gradient(1.0)),
+            },
+            RenderMode::Heat => match self.heat[idx] {
+                0 => self.palette.color(Cell::Dead),
+                heat => self.palette.gradient(heat as f32 / u8::MAX as f32),
+            },
+        }
+    }
+
+    // The color of the dead cell at `idx`. With trails enabled, cells that
+    // died recently fade from `last_color` to the dead color over
+    // `trail_length` generations instead of disappearing at once.
+    fn dead_color(&self, idx: usize, last_color: [u8; 4]) -> [u8; 4] {
+        let dead = self.palette.color(Cell::Dead);
+        let age = self.ages[idx];
+
+        if age >= self.trail_length {
+            return dead;
+        }
+
+        let fade = (age + 1) as f32 / (self.trail_length + 1) as f32;
+        lerp(last_color, dead, fade)
+    }
+
+    // The color of the cell at `idx` in the RGBA renderer, part way faded in
+    // from its previous state when cross-fading between generations.
+    pub(crate) fn frame_color(&self, idx: usize) -> [u8; 4] {
+        let color = self.pixel_color(idx);
+        let previous = self.previous[idx];
+
+        if self.crossfade >= 1.0 || previous == self.cells[idx] {
+            color
+        } else {
+            lerp(self.palette.color(previous), color, self.crossfade)
+        }
+    }
+
+    // Writes one RGBA pixel per cell into `out`, stopping at whichever of the
+    // two runs out first.
+    pub(crate) fn fill_rgba(&self, out: &mut [u8]) {
+        for (idx, pixel) in out.chunks_exact_mut(4).take(self.cells.len()).enumerate() {
+            pixel.copy_from_slice(&self.frame_color(idx));
+        }
+    }
+
+    // Draws into `out` with the current overlay options and any pending
+    // pattern, filling as much of it as fits.
+    pub(crate) fn draw_rgba(&self, out: &mut [u8]) {
+        let len = (self.render_width() * self.render_height() * 4) as usize;
+
+        if self.overlay == OverlayOptions::default() && self.pending.is_none() {
+            self.fill_rgba(out);
+        } else if out.len() >= len {
+            self.draw_overlaid(&mut out[..len]);
+        } else {
+            let mut pixels = vec![0; len];
+            self.draw_overlaid(&mut pixels);
+            out.copy_from_slice(&pixels[..out.len()]);
+        }
+    }
+
+    // Bytes in an RGBA buffer rendered at `scale`, as long as it holds no
+    // more pixels than a universe can have cells.
+    fn scaled_len(&self, scale: u32) -> Result<usize, String> {
+        let width = self.render_width() as u64 * scale as u64;
+        let height = self.render_height() as u64 * scale as u64;
+        match width.checked_mul(height) {
+            Some(pixels) if pixels <= MAX_CELLS => Ok(pixels as usize * 4),
+            _ => Err(format!(
+                "rendering at a scale of {} is too big, the most is {} pixels",
+                scale, MAX_CELLS
+            )),
+        }
+    }
+
+    // Draws into `out` at `scale` pixels per rendered pixel, repeating each
+    // pixel across and each row down.
+    fn draw_rgba_scaled(&self, scale: u32, out: &mut [u8]) {
+        if scale == 1 {
+            self.draw_rgba(out);
+            return;
+        }
+
+        let width = self.render_width() as usize;
+        let mut unscaled = vec![0; width * self.render_height() as usize * 4];
+        self.draw_rgba(&mut unscaled);
+
+        let scale = scale as usize;
+        let stride = width * scale * 4;
+        for (row, line) in unscaled.chunks_exact(width * 4).enumerate() {
+            let mut scaled_line = Vec::with_capacity(stride);
+            for pixel in line.chunks_exact(4) {
+                for _ in 0..scale {
+                    scaled_line.extend_from_slice(pixel);
+                }
+            }
+
+            for dy in 0..scale {
+                let start = (row * scale + dy) * stride;
+                if start >= out.len() {
+                    return;
+                }
+                let end = (start + stride).min(out.len());
+                out[start..end].copy_from_slice(&scaled_line[..end - start]);
+            }
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn palette(&self) -> Palette {
+        self.palette.clone()
+    }
+
+    pub fn trail_length(&self) -> u32 {
+        self.trail_length
+    }
+
+    // Makes dead cells fade out over the given number of generations instead
+    // of blanking straight away, leaving trails behind moving patterns. 0
+    // turns trails off.
+    pub fn set_trail_length(&mut self, generations: u32) {
+        self.trail_length = generations;
+    }
+
+    pub fn crossfade(&self) -> f32 {
+        self.crossfade
+    }
+
+    // Blends the RGBA renderer's output between the previous generation, at
+    // 0, and the current one, at 1. Setting it every frame to the fraction of
+    // the tick interval that has passed gives smooth cross-fades when the
+    // simulation ticks slower than the display refreshes. 1 turns it off.
+    pub fn set_crossfade(&mut self, alpha: f32) {
+        self.crossfade = alpha.clamp(0.0, 1.0);
+    }
+
+    // Sets the colors used by every renderer.
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = palette.clone();
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        self.render_mode = render_mode;
+    }
+
+    // Colors every cell into the universe's own pixel buffer and returns a
+    // pointer to it. The buffer holds render_width() * render_height() * 4
+    // bytes laid out row by row, ready to wrap in a Uint8ClampedArray for
+    // `new ImageData(...)`. Without overlays that is one pixel per cell.
+    pub fn render_rgba(&mut self) -> *const u8 {
+        let len = (self.render_width() * self.render_height() * 4) as usize;
+        let mut pixels = std::mem::take(&mut self.pixels);
+        pixels.resize(len, 0);
+        self.draw_rgba(&mut pixels);
+        self.pixels = pixels;
+        self.pixels.as_ptr()
+    }
+
+    // Same as `render_rgba`, but fills a caller-provided buffer instead, e.g.
+    // the `data` of an existing ImageData.
+    pub fn render_rgba_into(&self, out: &mut [u8]) {
+        self.draw_rgba(out);
+    }
+
+    // Like `render_rgba`, but with every pixel blown up into a `scale` x
+    // `scale` square, for drawing at the display's device pixel ratio without
+    // the browser blurring the cells. The buffer holds render_width() * scale
+    // by render_height() * scale pixels. Throws if that's more pixels than a
+    // universe can have cells.
+    pub fn render_rgba_scaled(&mut self, scale: u32) -> Result<*const u8, JsValue> {
+        let scale = scale.max(1);
+        let len = self
+            .scaled_len(scale)
+            .map_err(|err| JsValue::from_str(&err))?;
+        let mut pixels = std::mem::take(&mut self.pixels);
+        pixels.resize(len, 0);
+        self.draw_rgba_scaled(scale, &mut pixels);
+        self.pixels = pixels;
+        Ok(self.pixels.as_ptr())
+    }
+
+    // Same as `render_rgba_scaled`, but fills a caller-provided buffer.
+    pub fn render_rgba_scaled_into(&self, scale: u32, out: &mut [u8]) -> Result<(), JsValue> {
+        let scale = scale.max(1);
+        self.scaled_len(scale)
+            .map_err(|err| JsValue::from_str(&err))?;
+        self.draw_rgba_scaled(scale, out);
+        Ok(())
+    }
+
+    // Shrinks the whole universe down to a target_w x target_h grayscale
+    // thumbnail, one byte per pixel. Each pixel covers a block of cells and
+    // holds the fraction of them that are alive, from 0 (all dead) to 255
+    // (all alive). Targets larger than the universe repeat cells instead.
+    pub fn minimap(&self, target_w: u32, target_h: u32) -> Vec<u8> {
+        let mut out = Vec::with_capacity((target_w * target_h) as usize);
+
+        for ty in 0..target_h {
+            let (row_start, row_end) = block_span(ty, target_h, self.height);
+            for tx in 0..target_w {
+                let (col_start, col_end) = block_span(tx, target_w, self.width);
+
+                let mut alive = 0;
+                for row in row_start..row_end {
+                    for col in col_start..col_end {
+                        alive += (self.cells[self.get_index(row, col)] == Cell::Alive) as u32;
+                    }
+                }
+
+                let total = (row_end - row_start) * (col_end - col_start);
+                out.push((alive * 255 / total) as u8);
+            }
+        }
+
+        out
+    }
+
+    // Calls `callback(row, column, age)` for every live cell, row by row, so
+    // custom renderers can draw the universe without knowing the layout of
+    // its buffers. Stops at the first exception the callback throws.
+    pub fn for_each_live_cell(&self, callback: &js_sys::Function) -> Result<(), JsValue> {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                if self.cells[idx] == Cell::Alive {
+                    callback.call3(
+                        &JsValue::NULL,
+                        &JsValue::from(row),
+                        &JsValue::from(col),
+                        &JsValue::from(self.ages[idx]),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Returns the cells that changed since the previous call as a flat list of
+    // [index, state, index, state, ...] pairs, so only dirty cells need to be
+    // redrawn. The first call, and the first call after a resize, reports
+    // every cell.
+    pub fn render_changes(&mut self) -> Vec<u32> {
+        let mut changes = Vec::new();
+
+        if self.rendered.len() != self.cells.len() {
+            for (idx, &cell) in self.cells.iter().enumerate() {
+                changes.push(idx as u32);
+                changes.push(cell as u32);
+            }
+        } else {
+            for (idx, (&cell, &previous)) in self.cells.iter().zip(self.rendered.iter()).enumerate()
+            {
+                if cell != previous {
+                    changes.push(idx as u32);
+                    changes.push(cell as u32);
+                }
+            }
+        }
+
+        self.rendered.clone_from(&self.cells);
+        changes
+    }
+}
+
+// The range of source cells covered by pixel `i` of `target` pixels, when
+// `source` cells are shrunk down to fit. Always covers at least one cell.
+fn block_span(i: u32, target: u32, source: u32) -> (u32, u32) {
+    let start = (i as u64 * source as u64 / target as u64) as u32;
+    let end = ((i as u64 + 1) * source as u64 / target as u64) as u32;
+    (start.min(source - 1), end.max(start + 1).min(source))
+}
@@ -0,0 +1,258 @@
+use wasm_bindgen::prelude::*;
+
+use crate::recording::Edit;
+use crate::rule::Rule;
+use crate::selection::Selection;
+use crate::{check_size, Cell, Universe};
+
+// Longest line `to_rle` writes, as most tools expect.
+const LINE_LENGTH: usize = 70;
+
+// A pattern read from a file: its size, its rule and where its live cells
+// are.
+pub(crate) struct ParsedPattern {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) rule: Rule,
+    pub(crate) alive: Vec<(u32, u32)>,
+}
+
+// Reads a pattern in run length encoded form, the format most pattern
+// collections use: an `x = 3, y = 3, rule = B3/S23` header followed by runs
+// like `bob$2bo$3o!`, where `b` is a dead cell, `o` a live one, `$` ends a row
+// and `!` ends the pattern. Lines starting with `#` are comments.
+pub(crate) fn parse_rle(text: &str) -> Result<ParsedPattern, String> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let header = lines
+        .next()
+        .ok_or_else(|| String::from("the pattern is empty"))?;
+    let (mut width, mut height, mut rule) = (None, None, Rule::default());
+    for field in header.split(',') {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("expected a header like `x = 3, y = 3`, not `{}`", header))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = Some(size(value)?),
+            "y" => height = Some(size(value)?),
+            "rule" => rule = parse_rule(value)?,
+            other => return Err(format!("unknown header field `{}`", other)),
+        }
+    }
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+        _ => return Err(String::from("the header needs a non-zero `x` and `y`")),
+    };
+    check_size(width, height)?;
+
+    let mut alive = Vec::new();
+    let (mut row, mut col, mut count) = (0u32, 0u32, None);
+    'runs: for line in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                count = count
+                    .unwrap_or(0u32)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit));
+                if count.is_none() {
+                    return Err(String::from("a run in the pattern is too long"));
+                }
+                continue;
+            }
+
+            let run = count.take().unwrap_or(1);
+            match c {
+                // Runs past the edges are only a problem if a live cell
+                // ends up there.
+                'b' | '.' => col = col.saturating_add(run),
+                '$' => {
+                    row = row.saturating_add(run);
+                    col = 0;
+                }
+                '!' => break 'runs,
+                // Any other letter is a live cell in some multi-state rule.
+                c if c.is_ascii_alphabetic() => {
+                    for _ in 0..run {
+                        if row >= height || col >= width {
+                            return Err(format!(
+                                "cell ({}, {}) is outside the {} by {} pattern",
+                                row, col, width, height
+                            ));
+                        }
+                        alive.push((row, col));
+                        col += 1;
+                    }
+                }
+                c if c.is_whitespace() => {}
+                c => return Err(format!("unexpected `{}` in the pattern", c)),
+            }
+        }
+    }
+
+    Ok(ParsedPattern {
+        width,
+        height,
+        rule,
+        alive,
+    })
+}
+
+fn size(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a size, not `{}`", value))
+}
+
+// Reads the rule in the header. Older files write it as survival/birth
+// counts with no letters, like "23/3".
+pub(crate) fn parse_rule(text: &str) -> Result<Rule, String> {
+    Rule::parse(text).or_else(|err| match text.split_once('/') {
+        Some((survival, birth))
+            if (survival.chars().chain(birth.chars())).all(|c| c.is_ascii_digit()) =>
+        {
+            Rule::parse(&format!("B{}/S{}", birth, survival))
+        }
+        _ => Err(err),
+    })
+}
+
+// A run of `count` of `tag`, with the count left out when it's 1.
+fn run(count: u32, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", count, tag)
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // A universe just big enough for `pattern`, ticking by its rule.
+    pub(crate) fn from_parsed(pattern: ParsedPattern) -> Universe {
+        let mut cells = vec![Cell::Dead; (pattern.width * pattern.height) as usize];
+        for (row, col) in pattern.alive {
+            cells[(row * pattern.width + col) as usize] = Cell::Alive;
+        }
+
+        let mut universe = Universe::with_cells(pattern.width, pattern.height, cells);
+        universe.rule = pattern.rule;
+        universe
+    }
+
+    // Writes the cells in `region` as RLE, with the universe's rule in the
+    // header. Dead cells at the ends of rows and empty rows at the bottom are
+    // left out, as usual.
+    pub(crate) fn region_rle(&self, region: Selection) -> String {
+        let width = region.right - region.left + 1;
+        let height = region.bottom - region.top + 1;
+
+        let mut runs = Vec::new();
+        let mut row_ends = 0;
+        for row in region.top..=region.bottom {
+            if row > region.top {
+                row_ends += 1;
+            }
+            let mut col = region.left;
+            while col <= region.right {
+                // Walls go out as dead cells.
+                let alive = |col| self.cells[self.get_index(row, col)] == Cell::Alive;
+                let start_alive = alive(col);
+                let start = col;
+                while col <= region.right && alive(col) == start_alive {
+                    col += 1;
+                }
+                // Dead cells up to the end of the row go without saying.
+                if !start_alive && col > region.right {
+                    break;
+                }
+                if row_ends > 0 {
+                    runs.push(run(row_ends, '$'));
+                    row_ends = 0;
+                }
+                let tag = if start_alive { 'o' } else { 'b' };
+                runs.push(run(col - start, tag));
+            }
+        }
+        runs.push(String::from("!"));
+
+        let mut text = format!("x = {}, y = {}, rule = {}\n", width, height, self.rule);
+        let mut line = 0;
+        for run in runs {
+            if line + run.len() > LINE_LENGTH {
+                text.push('\n');
+                line = 0;
+            }
+            line += run.len();
+            text.push_str(&run);
+        }
+        text.push('\n');
+        text
+    }
+
+    // Pastes `pattern` centered on (row, column), wrapping around the edges.
+    pub(crate) fn paste_centered(&mut self, pattern: &Universe, row: u32, column: u32) {
+        let top = row % self.height + self.height - (pattern.height / 2) % self.height;
+        let left = column % self.width + self.width - (pattern.width / 2) % self.width;
+        self.paste(pattern, top, left);
+    }
+
+    // Brings the live cells of a `width` wide pattern to life with its top-left
+    // corner at (row, column), wrapping around the edges.
+    pub(crate) fn paste_cells(&mut self, row: u32, column: u32, width: u32, alive: &[u32]) {
+        self.record(Edit::Paste {
+            row,
+            column,
+            width,
+            alive: alive.to_vec(),
+        });
+        for &idx in alive {
+            let idx = self.get_index(
+                (row + idx / width) % self.height,
+                (column + idx % width) % self.width,
+            );
+            self.set_cell(idx, Cell::Alive);
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Builds a universe just big enough for a pattern in RLE, the format most
+    // pattern collections use, ticking by the rule in its header. Throws if
+    // the pattern can't be read.
+    pub fn from_rle(text: &str) -> Result<Universe, String> {
+        parse_rle(text).map(Universe::from_parsed)
+    }
+
+    // The whole universe as RLE, the format `from_rle` reads and most other
+    // Life programs can open.
+    pub fn to_rle(&self) -> String {
+        self.region_rle(Selection {
+            top: 0,
+            left: 0,
+            bottom: self.height - 1,
+            right: self.width - 1,
+        })
+    }
+
+    // Brings the live cells of `pattern`, e.g. one from `from_rle`, to life
+    // with its top-left corner at (row, column), wrapping around the edges.
+    // Cells the pattern has dead are left as they are, and the universe keeps
+    // its own rule.
+    pub fn paste(&mut self, pattern: &Universe, row: u32, column: u32) {
+        let alive: Vec<u32> = (0..pattern.cells.len() as u32)
+            .filter(|&idx| pattern.cells[idx as usize] == Cell::Alive)
+            .collect();
+        self.paste_cells(
+            row % self.height,
+            column % self.width,
+            pattern.width,
+            &alive,
+        );
+    }
+}
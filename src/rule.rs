@@ -0,0 +1,32 @@
+use wasm_bindgen::prelude::*;
+
+pub(crate) use life_engine::Rule;
+
+use crate::recording::Edit;
+use crate::Universe;
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The current rule in B/S notation, "B3/S23" unless changed.
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    // Changes the rule the universe ticks by, given in B/S notation like
+    // "B36/S23". Throws if the rule can't be read.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        self.set_rule_text(rule)
+            .map_err(|err| JsValue::from_str(&err))
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    pub(crate) fn set_rule_text(&mut self, rule: &str) -> Result<(), String> {
+        let rule = Rule::parse(rule)?;
+        self.record(Edit::Rule(rule.to_string()));
+        self.rule = rule;
+        Ok(())
+    }
+}
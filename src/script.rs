@@ -0,0 +1,269 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::formats::parse_pattern;
+use crate::{Pattern, Universe};
+
+// Generations per second `wait` steps tick at unless they say otherwise.
+const DEFAULT_SPEED: f64 = 10.0;
+
+// A step as written in a script's JSON.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum StepJson {
+    Place {
+        pattern: String,
+        row: u32,
+        column: u32,
+        #[serde(default)]
+        rotation: u32,
+    },
+    Wait {
+        generations: u32,
+        speed: Option<f64>,
+    },
+    Pan {
+        row: u32,
+        column: u32,
+        zoom: Option<f64>,
+    },
+    Pause {
+        ms: f64,
+    },
+    Caption {
+        id: Option<String>,
+    },
+}
+
+// What to place: one of the built-in patterns, turned, or one read from a
+// pattern file's text.
+enum Placement {
+    BuiltIn(Pattern, u32),
+    Parsed(Box<Universe>),
+}
+
+enum Step {
+    Place(Placement, u32, u32),
+    Wait(u32, f64),
+    Pan(u32, u32, Option<f64>),
+    Pause(f64),
+    Caption(Option<String>),
+}
+
+impl Step {
+    fn from_json(step: StepJson) -> Result<Step, String> {
+        Ok(match step {
+            StepJson::Place {
+                pattern,
+                row,
+                column,
+                rotation,
+            } => {
+                let placement = match Pattern::from_name(pattern.trim()) {
+                    Some(built_in) => Placement::BuiltIn(built_in, rotation),
+                    None => {
+                        Placement::Parsed(Box::new(Universe::from_parsed(parse_pattern(&pattern)?)))
+                    }
+                };
+                Step::Place(placement, row, column)
+            }
+            StepJson::Wait { generations, speed } => {
+                let speed = speed.unwrap_or(DEFAULT_SPEED);
+                if speed.is_nan() || speed <= 0.0 {
+                    return Err(format!(
+                        "a wait can't tick at {} generations a second",
+                        speed
+                    ));
+                }
+                Step::Wait(generations, speed)
+            }
+            StepJson::Pan { row, column, zoom } => Step::Pan(row, column, zoom),
+            StepJson::Pause { ms } => Step::Pause(ms.max(0.0)),
+            StepJson::Caption { id } => Step::Caption(id),
+        })
+    }
+}
+
+// A guided tour of a universe: a list of steps played one after another in
+// time with the animation loop, loaded from JSON like:
+//
+//     [
+//       { "type": "caption", "id": "intro" },
+//       { "type": "place", "pattern": "gosper-glider-gun", "row": 20, "column": 30 },
+//       { "type": "pan", "row": 0, "column": 10, "zoom": 4 },
+//       { "type": "wait", "generations": 120, "speed": 15 },
+//       { "type": "pause", "ms": 2000 },
+//       { "type": "caption", "id": "gliders" }
+//     ]
+//
+// `place` stamps a pattern centered on (row, column): "glider", "pulsar" or
+// "gosper-glider-gun", turned clockwise by `rotation` quarter turns, or the
+// text of a pattern in RLE, plaintext or Life 1.05 or 1.06. `wait` ticks the
+// universe `generations` times at `speed` generations a second, 10 by
+// default. `pan` moves the viewport's top-left corner to (row, column),
+// zooming too if given. `pause` waits `ms` milliseconds without ticking.
+// `caption` shows the caption with the given id, for the page to look up,
+// or hides it without one.
+#[wasm_bindgen]
+pub struct Script {
+    steps: Vec<Step>,
+    // The step being played, `steps.len()` once they're all done.
+    current: usize,
+    playing: bool,
+    // Milliseconds spent on the current step so far, and generations
+    // ticked by it.
+    elapsed_ms: f64,
+    ticked: u32,
+    caption: Option<String>,
+}
+
+// Methods not being exported to Javascript
+impl Script {
+    // Plays the current step for up to `time` more milliseconds. If that
+    // finishes it, moves on to the next and returns the time left over.
+    fn play_step(&mut self, universe: &mut Universe, time: f64) -> Option<f64> {
+        let leftover = match &self.steps[self.current] {
+            Step::Place(placement, row, column) => {
+                match placement {
+                    Placement::BuiltIn(pattern, rotation) => {
+                        universe.stamp(*pattern, *row, *column, *rotation)
+                    }
+                    Placement::Parsed(pattern) => universe.paste_centered(pattern, *row, *column),
+                }
+                time
+            }
+            Step::Pan(row, column, zoom) => {
+                let zoom = zoom.unwrap_or_else(|| universe.viewport_zoom());
+                universe.set_viewport(*row, *column, zoom);
+                time
+            }
+            Step::Caption(id) => {
+                self.caption = id.clone();
+                time
+            }
+            &Step::Wait(generations, speed) => {
+                self.elapsed_ms += time;
+                let due = ((self.elapsed_ms * speed / 1000.0) as u32).min(generations);
+                while self.ticked < due {
+                    universe.tick();
+                    self.ticked += 1;
+                }
+                if self.ticked < generations {
+                    return None;
+                }
+                (self.elapsed_ms - generations as f64 * 1000.0 / speed).max(0.0)
+            }
+            &Step::Pause(ms) => {
+                self.elapsed_ms += time;
+                if self.elapsed_ms < ms {
+                    return None;
+                }
+                self.elapsed_ms - ms
+            }
+        };
+
+        self.current += 1;
+        self.elapsed_ms = 0.0;
+        self.ticked = 0;
+        Some(leftover)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Script {
+    // Reads a script from JSON in the form above. It starts out playing.
+    // Throws if the JSON or any step in it can't be read, including the
+    // patterns.
+    pub fn from_json(json: &str) -> Result<Script, JsValue> {
+        let value = js_sys::JSON::parse(json)?;
+        let steps: Vec<StepJson> = serde_wasm_bindgen::from_value(value)?;
+        let steps = steps
+            .into_iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                Step::from_json(step).map_err(|err| format!("step {}: {}", idx + 1, err))
+            })
+            .collect::<Result<Vec<Step>, String>>()
+            .map_err(|err| JsValue::from_str(&err))?;
+
+        Ok(Script {
+            steps,
+            current: 0,
+            playing: true,
+            elapsed_ms: 0.0,
+            ticked: 0,
+            caption: None,
+        })
+    }
+
+    // Plays the script on `universe` for `elapsed_ms` more milliseconds,
+    // e.g. the time since the last animation frame, running as many steps as
+    // fall in that time. Does nothing while paused. Returns whether there are
+    // steps still to play.
+    pub fn advance(&mut self, universe: &mut Universe, elapsed_ms: f64) -> bool {
+        let mut time = elapsed_ms.max(0.0);
+        while self.playing && self.current < self.steps.len() {
+            match self.play_step(universe, time) {
+                Some(leftover) => time = leftover,
+                None => break,
+            }
+        }
+        !self.is_finished()
+    }
+
+    // Finishes the current step straight away, e.g. from a "next" button:
+    // the rest of a wait is ticked at once and a pause is cut short. Works
+    // while paused too.
+    pub fn skip(&mut self, universe: &mut Universe) {
+        if self.current == self.steps.len() {
+            return;
+        }
+        match self.steps[self.current] {
+            Step::Wait(generations, _) => {
+                while self.ticked < generations {
+                    universe.tick();
+                    self.ticked += 1;
+                }
+            }
+            Step::Pause(ms) => self.elapsed_ms = ms,
+            _ => {}
+        }
+        self.play_step(universe, 0.0);
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current == self.steps.len()
+    }
+
+    // The index of the step being played, or the number of steps once
+    // they're all done.
+    pub fn current_step(&self) -> u32 {
+        self.current as u32
+    }
+
+    pub fn len(&self) -> u32 {
+        self.steps.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    // The id of the caption to show, if any.
+    pub fn caption(&self) -> Option<String> {
+        self.caption.clone()
+    }
+}
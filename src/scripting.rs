@@ -0,0 +1,795 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::random::Random;
+use crate::{check_size, Cell, Pattern, Universe};
+
+// How much work a script may do before it's stopped as probably never ending.
+// Every statement costs one, and every generation ticked costs one per cell.
+const MAX_WORK: u64 = 1_000_000_000;
+
+const KEYWORDS: [&str; 10] = [
+    "let", "for", "in", "while", "if", "else", "break", "continue", "true", "false",
+];
+
+// Functions scripts can call.
+const FUNCTIONS: [&str; 24] = [
+    "print",
+    "tick",
+    "place",
+    "toggle",
+    "fill",
+    "wall",
+    "unwall",
+    "clear",
+    "soup",
+    "resize",
+    "rule",
+    "set_rule",
+    "alive",
+    "population",
+    "generation",
+    "width",
+    "height",
+    "random",
+    "floor",
+    "round",
+    "abs",
+    "sqrt",
+    "min",
+    "max",
+];
+
+// Longest first, so ".." isn't read as two dots and "<=" isn't read as "<".
+const SYMBOLS: [&str; 26] = [
+    "..", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "+", "-", "*", "/", "%", "<",
+    ">", "=", "!", "(", ")", "{", "}", ",", ";",
+];
+
+// Binary operators from the loosest binding to the tightest.
+const PRECEDENCE: [&[&str]; 6] = [
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Value(Value),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Let(String, Expr),
+    // An assignment, with the operator for the likes of `+=`.
+    Assign(String, Option<&'static str>, Expr),
+    For(String, Expr, Expr, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    Break,
+    Continue,
+    Expr(Expr),
+}
+
+// A statement with the line it starts on, for error messages.
+#[derive(Debug)]
+struct Statement {
+    line: usize,
+    stmt: Stmt,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Unit,
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Str(_) => "a string",
+            Value::Bool(_) => "true or false",
+            Value::Unit => "nothing",
+        }
+    }
+
+    fn number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            other => Err(format!("expected a number, not {}", other.kind())),
+        }
+    }
+
+    // A number that can be a row, column, size or count.
+    fn whole(&self) -> Result<u32, String> {
+        let n = self.number()?;
+        if n.fract() != 0.0 || n < 0.0 || n > u32::MAX as f64 {
+            return Err(format!("expected a whole number, not {}", self));
+        }
+        Ok(n as u32)
+    }
+
+    fn text(&self) -> Result<&str, String> {
+        match self {
+            Value::Str(text) => Ok(text),
+            other => Err(format!("expected a string, not {}", other.kind())),
+        }
+    }
+
+    fn truth(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(truth) => Ok(*truth),
+            other => Err(format!("expected true or false, not {}", other.kind())),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // Whole numbers print without a trailing ".0".
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(text) => write!(f, "{}", text),
+            Value::Bool(truth) => write!(f, "{}", truth),
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
+
+// Splits a script into tokens, each with the line it's on.
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line) = (0, 1);
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            // A dot only belongs to the number if a digit follows, so `0..10`
+            // is a range.
+            if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse()
+                .map_err(|_| format!("line {}: `{}` isn't a number", line, text))?;
+            tokens.push((Token::Number(n), line));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') => break,
+                    Some('\\') => {
+                        text.push(match chars.get(i + 1) {
+                            Some('n') => '\n',
+                            Some(&escaped) => escaped,
+                            None => return Err(format!("line {}: unfinished string", line)),
+                        });
+                        i += 2;
+                    }
+                    Some(&other) => {
+                        if other == '\n' {
+                            line += 1;
+                        }
+                        text.push(other);
+                        i += 1;
+                    }
+                    None => return Err(format!("line {}: unfinished string", line)),
+                }
+            }
+            i += 1;
+            tokens.push((Token::Str(text), line));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(*symbol))
+                .ok_or_else(|| format!("line {}: unexpected `{}`", line, c))?;
+            tokens.push((Token::Symbol(symbol), line));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    // How many loops the statement being parsed is inside, so a stray
+    // `break` is caught before anything runs.
+    loops: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or_else(|| self.tokens.last()) {
+            Some(&(_, line)) => line,
+            None => 1,
+        }
+    }
+
+    fn fail<T>(&self, message: String) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), message))
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(word)) if word == keyword)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.is_symbol(symbol) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.fail(format!("expected `{}`", symbol))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.fail(format!("expected `{}`", keyword))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => self.fail(String::from("expected a name")),
+        }
+    }
+
+    // Statements up to a closing brace, or the end of the script.
+    fn statements(&mut self) -> Result<Vec<Statement>, String> {
+        let mut statements = Vec::new();
+        while self.peek().is_some() && !self.is_symbol("}") {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, String> {
+        self.expect("{")?;
+        let statements = self.statements()?;
+        self.expect("}")?;
+        Ok(statements)
+    }
+
+    fn loop_body(&mut self) -> Result<Vec<Statement>, String> {
+        self.loops += 1;
+        let body = self.block();
+        self.loops -= 1;
+        body
+    }
+
+    fn statement(&mut self) -> Result<Statement, String> {
+        let line = self.line();
+        let keyword = match self.peek() {
+            Some(Token::Ident(word)) => word.clone(),
+            _ => String::new(),
+        };
+        let stmt = match keyword.as_str() {
+            "let" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.expect("=")?;
+                let value = self.expr()?;
+                self.expect(";")?;
+                Stmt::Let(name, value)
+            }
+            "for" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.expect_keyword("in")?;
+                let start = self.expr()?;
+                self.expect("..")?;
+                let end = self.expr()?;
+                Stmt::For(name, start, end, self.loop_body()?)
+            }
+            "while" => {
+                self.pos += 1;
+                let condition = self.expr()?;
+                Stmt::While(condition, self.loop_body()?)
+            }
+            "if" => {
+                self.pos += 1;
+                let condition = self.expr()?;
+                let then = self.block()?;
+                let otherwise = if self.is_keyword("else") {
+                    self.pos += 1;
+                    if self.is_keyword("if") {
+                        vec![self.statement()?]
+                    } else {
+                        self.block()?
+                    }
+                } else {
+                    Vec::new()
+                };
+                Stmt::If(condition, then, otherwise)
+            }
+            "break" | "continue" => {
+                if self.loops == 0 {
+                    return self.fail(format!("`{}` outside a loop", keyword));
+                }
+                self.pos += 1;
+                self.expect(";")?;
+                if keyword == "break" {
+                    Stmt::Break
+                } else {
+                    Stmt::Continue
+                }
+            }
+            _ => {
+                // `x = 1;` sets `x`, and `x += 1;` and the like work `x` out
+                // from itself.
+                let assignment = match self.tokens.get(self.pos + 1) {
+                    Some((Token::Symbol(op), _)) => match *op {
+                        "=" => Some(None),
+                        "+=" => Some(Some("+")),
+                        "-=" => Some(Some("-")),
+                        "*=" => Some(Some("*")),
+                        "/=" => Some(Some("/")),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match assignment {
+                    Some(op) => {
+                        let name = self.name()?;
+                        self.pos += 1;
+                        let value = self.expr()?;
+                        self.expect(";")?;
+                        Stmt::Assign(name, op, value)
+                    }
+                    None => {
+                        let value = self.expr()?;
+                        self.expect(";")?;
+                        Stmt::Expr(value)
+                    }
+                }
+            }
+        };
+        Ok(Statement { line, stmt })
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(Token::Symbol(op)) = self.peek() {
+            let op: &'static str = op;
+            if !PRECEDENCE[level].contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for op in ["-", "!"] {
+            if self.is_symbol(op) {
+                self.pos += 1;
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return self.fail(String::from("the script ends part way through")),
+        };
+        match token {
+            Token::Number(n) => {
+                self.pos += 1;
+                Ok(Expr::Value(Value::Number(n)))
+            }
+            Token::Str(text) => {
+                self.pos += 1;
+                Ok(Expr::Value(Value::Str(text)))
+            }
+            Token::Ident(word) if word == "true" || word == "false" => {
+                self.pos += 1;
+                Ok(Expr::Value(Value::Bool(word == "true")))
+            }
+            Token::Ident(_) => {
+                let name = self.name()?;
+                if !self.is_symbol("(") {
+                    return Ok(Expr::Var(name));
+                }
+                self.pos += 1;
+                let mut args = Vec::new();
+                while !self.is_symbol(")") {
+                    args.push(self.expr()?);
+                    if !self.is_symbol(")") {
+                        self.expect(",")?;
+                    }
+                }
+                self.pos += 1;
+                Ok(Expr::Call(name, args))
+            }
+            Token::Symbol("(") => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Symbol(symbol) => self.fail(format!("unexpected `{}`", symbol)),
+        }
+    }
+}
+
+fn parse(src: &str) -> Result<Vec<Statement>, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        loops: 0,
+    };
+    let statements = parser.statements()?;
+    if parser.peek().is_some() {
+        return parser.fail(String::from("unexpected `}`"));
+    }
+    Ok(statements)
+}
+
+// Where a statement leaves the loop it's in.
+enum Flow {
+    Next,
+    Break,
+    Continue,
+}
+
+struct Interpreter<'a> {
+    universe: &'a mut Universe,
+    // Variables in scope, innermost last, so leaving a block just drops the
+    // ones it declared.
+    scope: Vec<(String, Value)>,
+    output: Vec<String>,
+    work: u64,
+    random: Random,
+}
+
+impl<'a> Interpreter<'a> {
+    fn spend(&mut self, work: u64) -> Result<(), String> {
+        self.work += work;
+        if self.work > MAX_WORK {
+            return Err(String::from("the script ran too long, so it was stopped"));
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, statements: &[Statement]) -> Result<Flow, String> {
+        let depth = self.scope.len();
+        let mut flow = Flow::Next;
+        for statement in statements {
+            match self.statement(statement) {
+                Ok(Flow::Next) => {}
+                Ok(other) => {
+                    flow = other;
+                    break;
+                }
+                Err(err) => {
+                    self.scope.truncate(depth);
+                    return Err(err);
+                }
+            }
+        }
+        self.scope.truncate(depth);
+        Ok(flow)
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<Flow, String> {
+        let at = |err: String| format!("line {}: {}", statement.line, err);
+        self.spend(1).map_err(at)?;
+        match &statement.stmt {
+            Stmt::Let(name, value) => {
+                let value = self.eval(value).map_err(at)?;
+                self.scope.push((name.clone(), value));
+            }
+            Stmt::Assign(name, op, value) => {
+                let mut value = self.eval(value).map_err(at)?;
+                let slot = self
+                    .scope
+                    .iter()
+                    .rposition(|(var, _)| var == name)
+                    .ok_or_else(|| at(format!("`{}` needs declaring with `let` first", name)))?;
+                if let Some(op) = op {
+                    value = binary(op, &self.scope[slot].1, &value).map_err(at)?;
+                }
+                self.scope[slot].1 = value;
+            }
+            Stmt::For(name, start, end, body) => {
+                let start = self.eval(start).and_then(|v| v.number()).map_err(at)?;
+                let end = self.eval(end).and_then(|v| v.number()).map_err(at)?;
+                let mut i = start;
+                while i < end {
+                    self.scope.push((name.clone(), Value::Number(i)));
+                    let flow = self.block(body);
+                    self.scope.pop();
+                    if let Flow::Break = flow? {
+                        break;
+                    }
+                    self.spend(1).map_err(at)?;
+                    i += 1.0;
+                }
+            }
+            Stmt::While(condition, body) => {
+                while self.eval(condition).and_then(|v| v.truth()).map_err(at)? {
+                    if let Flow::Break = self.block(body)? {
+                        break;
+                    }
+                    self.spend(1).map_err(at)?;
+                }
+            }
+            Stmt::If(condition, then, otherwise) => {
+                let branch = if self.eval(condition).and_then(|v| v.truth()).map_err(at)? {
+                    then
+                } else {
+                    otherwise
+                };
+                return self.block(branch);
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Continue => return Ok(Flow::Continue),
+            Stmt::Expr(value) => {
+                self.eval(value).map_err(at)?;
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, String> {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => self
+                .scope
+                .iter()
+                .rev()
+                .find(|(var, _)| var == name)
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| format!("there's no variable `{}`", name)),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, &args)
+            }
+            Expr::Unary("-", operand) => Ok(Value::Number(-self.eval(operand)?.number()?)),
+            Expr::Unary(_, operand) => Ok(Value::Bool(!self.eval(operand)?.truth()?)),
+            // The right side is only worked out when it matters.
+            Expr::Binary("&&", lhs, rhs) => Ok(Value::Bool(
+                self.eval(lhs)?.truth()? && self.eval(rhs)?.truth()?,
+            )),
+            Expr::Binary("||", lhs, rhs) => Ok(Value::Bool(
+                self.eval(lhs)?.truth()? || self.eval(rhs)?.truth()?,
+            )),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs)?;
+                let rhs = self.eval(rhs)?;
+                binary(op, &lhs, &rhs)
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str, args: &[Value]) -> Result<Value, String> {
+        let universe = &mut *self.universe;
+        match (name, args) {
+            ("print", _) => {
+                let line: Vec<String> = args.iter().map(Value::to_string).collect();
+                self.output.push(line.join(" "));
+            }
+            ("tick", []) => return self.call("tick", &[Value::Number(1.0)]),
+            ("tick", [n]) => {
+                for _ in 0..n.whole()? {
+                    self.spend(self.universe.cells.len() as u64)?;
+                    self.universe.tick();
+                }
+            }
+            ("place", [pattern, row, col]) => {
+                return self.call(
+                    "place",
+                    &[
+                        pattern.clone(),
+                        row.clone(),
+                        col.clone(),
+                        Value::Str(String::from("se")),
+                    ],
+                );
+            }
+            ("place", [pattern, row, col, direction]) => {
+                let pattern = match pattern.text()? {
+                    "glider" => Pattern::Glider,
+                    "pulsar" => Pattern::Pulsar,
+                    "gun" => Pattern::GosperGliderGun,
+                    other => return Err(format!("unknown pattern `{}`", other)),
+                };
+                let rotation = match direction.text()? {
+                    "se" => 0,
+                    "sw" => 1,
+                    "nw" => 2,
+                    "ne" => 3,
+                    other => return Err(format!("unknown direction `{}`", other)),
+                };
+                universe.stamp(pattern, row.whole()?, col.whole()?, rotation);
+            }
+            ("toggle", [row, col]) => {
+                let (row, col) = universe.position(row.whole()?, col.whole()?)?;
+                universe.toggle(row, col);
+            }
+            ("fill", [row1, col1, row2, col2]) => {
+                universe.fill(row1.whole()?, col1.whole()?, row2.whole()?, col2.whole()?);
+            }
+            ("wall", [row1, col1, row2, col2]) => {
+                universe.fill_walls(row1.whole()?, col1.whole()?, row2.whole()?, col2.whole()?);
+            }
+            ("unwall", [row1, col1, row2, col2]) => {
+                universe.clear_walls(row1.whole()?, col1.whole()?, row2.whole()?, col2.whole()?);
+            }
+            ("clear", []) => universe.kill_universe(),
+            ("soup", [density]) => {
+                let density = density.number()?;
+                if !(0.0..=1.0).contains(&density) {
+                    return Err(format!("the density must be from 0 to 1, not {}", density));
+                }
+                for idx in 0..universe.cells.len() {
+                    if universe.cells[idx] != Cell::Wall {
+                        let alive = self.random.next_f64() < density;
+                        universe.set_cell(idx, if alive { Cell::Alive } else { Cell::Dead });
+                    }
+                }
+                universe.record_snapshot();
+            }
+            ("resize", [width, height]) => {
+                let (width, height) = (width.whole()?, height.whole()?);
+                check_size(width, height)?;
+                universe.set_width(width)?;
+                universe.set_height(height)?;
+            }
+            ("rule", []) => return Ok(Value::Str(universe.rule.to_string())),
+            ("set_rule", [rule]) => universe.set_rule_text(rule.text()?)?,
+            ("alive", [row, col]) => {
+                let (row, col) = universe.position(row.whole()?, col.whole()?)?;
+                let idx = universe.get_index(row, col);
+                return Ok(Value::Bool(universe.cells[idx] == Cell::Alive));
+            }
+            ("population", []) => return Ok(Value::Number(universe.population() as f64)),
+            ("generation", []) => return Ok(Value::Number(universe.generation as f64)),
+            ("width", []) => return Ok(Value::Number(universe.width as f64)),
+            ("height", []) => return Ok(Value::Number(universe.height as f64)),
+            ("random", []) => return Ok(Value::Number(self.random.next_f64())),
+            ("floor", [n]) => return Ok(Value::Number(n.number()?.floor())),
+            ("round", [n]) => return Ok(Value::Number(n.number()?.round())),
+            ("abs", [n]) => return Ok(Value::Number(n.number()?.abs())),
+            ("sqrt", [n]) => return Ok(Value::Number(n.number()?.sqrt())),
+            ("min", [a, b]) => return Ok(Value::Number(a.number()?.min(b.number()?))),
+            ("max", [a, b]) => return Ok(Value::Number(a.number()?.max(b.number()?))),
+            (name, _) if FUNCTIONS.contains(&name) => {
+                return Err(format!("`{}` can't take {} arguments", name, args.len()))
+            }
+            (name, _) => return Err(format!("there's no function `{}`", name)),
+        }
+        Ok(Value::Unit)
+    }
+}
+
+fn binary(op: &str, lhs: &Value, rhs: &Value) -> Result<Value, String> {
+    Ok(match (op, lhs, rhs) {
+        ("==", _, _) => Value::Bool(lhs == rhs),
+        ("!=", _, _) => Value::Bool(lhs != rhs),
+        ("+", Value::Str(_), _) | ("+", _, Value::Str(_)) => Value::Str(format!("{}{}", lhs, rhs)),
+        _ => {
+            let (a, b) = (lhs.number()?, rhs.number()?);
+            match op {
+                "+" => Value::Number(a + b),
+                "-" => Value::Number(a - b),
+                "*" => Value::Number(a * b),
+                "/" | "%" if b == 0.0 => return Err(String::from("division by zero")),
+                "/" => Value::Number(a / b),
+                "%" => Value::Number(a % b),
+                "<" => Value::Bool(a < b),
+                "<=" => Value::Bool(a <= b),
+                ">" => Value::Bool(a > b),
+                _ => Value::Bool(a >= b),
+            }
+        }
+    })
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Runs a script against the universe and returns everything it printed,
+    // one line per `print`. Scripts are made of `let` variables, `for i in
+    // 0..10 { }` and `while` loops, `if`/`else`, `break`, `continue` and
+    // arithmetic on numbers, strings and booleans, with statements ending in
+    // `;`. Functions reach the universe: `tick(n)`, `place("glider", row,
+    // col, "se")`, `toggle`, `fill`, `wall`, `unwall`, `clear()`,
+    // `soup(density)`, `resize(width, height)`, `rule()`, `set_rule("B36/S23")`,
+    // `alive(row, col)`, `population()`, `generation()`, `width()` and
+    // `height()`, along with `random()`, `floor`, `round`, `abs`, `sqrt`,
+    // `min` and `max`. An error stops the script, and comes back as a last
+    // line starting with "error:", as do scripts that run too long.
+    pub fn run_script(&mut self, src: &str) -> String {
+        let mut interpreter = Interpreter {
+            universe: self,
+            scope: Vec::new(),
+            output: Vec::new(),
+            work: 0,
+            random: Random::new(),
+        };
+        let result = parse(src).and_then(|program| interpreter.block(&program));
+        let mut output = interpreter.output;
+        if let Err(err) = result {
+            output.push(format!("error: {}", err));
+        }
+        output.join("\n")
+    }
+}
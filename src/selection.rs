@@ -0,0 +1,132 @@
+use wasm_bindgen::prelude::*;
+
+use crate::recording::Edit;
+use crate::{Cell, Universe};
+
+// A rectangle of cells, with both corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Selection {
+    pub(crate) top: u32,
+    pub(crate) left: u32,
+    pub(crate) bottom: u32,
+    pub(crate) right: u32,
+}
+
+impl Selection {
+    pub(crate) fn contains(&self, row: u32, column: u32) -> bool {
+        row >= self.top && row <= self.bottom && column >= self.left && column <= self.right
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Whether the cell is under the brush centered on the hovered cell. The
+    // brush wraps around the edges like the universe does.
+    pub(crate) fn brush_contains(&self, row: u32, column: u32) -> bool {
+        let (hover_row, hover_col) = match self.hover {
+            Some(hover) => hover,
+            None => return false,
+        };
+
+        let rows = self.brush_size.min(self.height);
+        let cols = self.brush_size.min(self.width);
+        let top = (hover_row + self.height - (rows - 1) / 2) % self.height;
+        let left = (hover_col + self.width - (cols - 1) / 2) % self.width;
+
+        (row + self.height - top) % self.height < rows
+            && (column + self.width - left) % self.width < cols
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Selects the rectangle of cells between two opposite corners, in either
+    // order. Corners past the edges are pulled back inside the universe.
+    pub fn set_selection(&mut self, row1: u32, column1: u32, row2: u32, column2: u32) {
+        let (max_row, max_col) = (self.height - 1, self.width - 1);
+        self.selection = Some(Selection {
+            top: row1.min(row2).min(max_row),
+            left: column1.min(column2).min(max_col),
+            bottom: row1.max(row2).min(max_row),
+            right: column1.max(column2).min(max_col),
+        });
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    // The selected rectangle as [top, left, bottom, right], or an empty array
+    // when nothing is selected.
+    pub fn selection(&self) -> Vec<u32> {
+        match self.selection {
+            Some(s) => vec![s.top, s.left, s.bottom, s.right],
+            None => Vec::new(),
+        }
+    }
+
+    // Brings every cell in the rectangle between two opposite corners, in
+    // either order, to life. Corners past the edges are pulled back inside
+    // the universe.
+    pub fn fill(&mut self, row1: u32, column1: u32, row2: u32, column2: u32) {
+        self.record(Edit::Fill(row1, column1, row2, column2));
+
+        let (max_row, max_col) = (self.height - 1, self.width - 1);
+        for row in row1.min(row2).min(max_row)..=row1.max(row2).min(max_row) {
+            for col in column1.min(column2).min(max_col)..=column1.max(column2).min(max_col) {
+                let idx = self.get_index(row, col);
+                self.set_cell(idx, Cell::Alive);
+            }
+        }
+    }
+
+    // Marks the cell under the pointer, for the renderers to highlight along
+    // with the brush around it. Meant to be called from pointer move handlers.
+    pub fn set_hover(&mut self, row: u32, column: u32) {
+        self.hover = Some((row % self.height, column % self.width));
+    }
+
+    // Forgets the hovered cell, e.g. when the pointer leaves the canvas.
+    pub fn clear_hover(&mut self) {
+        self.hover = None;
+    }
+
+    // The hovered cell as [row, column], or an empty array when there is none.
+    pub fn hover(&self) -> Vec<u32> {
+        match self.hover {
+            Some((row, col)) => vec![row, col],
+            None => Vec::new(),
+        }
+    }
+
+    pub fn brush_size(&self) -> u32 {
+        self.brush_size
+    }
+
+    // Sets the width and height in cells of the square brush centered on the
+    // hovered cell.
+    pub fn set_brush_size(&mut self, size: u32) {
+        self.brush_size = size.max(1);
+    }
+
+    // The cells under the brush as a flat list of [row, column, row, column,
+    // ...] pairs, for canvas renderers to outline. Empty when nothing is
+    // hovered.
+    pub fn brush_cells(&self) -> Vec<u32> {
+        let mut cells = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.brush_contains(row, col) {
+                    cells.push(row);
+                    cells.push(col);
+                }
+            }
+        }
+        cells
+    }
+}
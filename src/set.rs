@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::rle::parse_rle;
+use crate::rule::Rule;
+use crate::{check_size, utils, Cell, Palette, Universe};
+
+// Several universes behind one wasm object, e.g. for a gallery of rules
+// running side by side. They're ticked together, share a palette, and can
+// all be stamped from one library of named patterns. Universes are referred
+// to by the id they were given when added.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct UniverseSet {
+    universes: BTreeMap<u32, Universe>,
+    next_id: u32,
+    patterns: BTreeMap<String, Universe>,
+    palette: Palette,
+}
+
+// Methods not being exported to Javascript
+impl UniverseSet {
+    fn get(&self, id: u32) -> Result<&Universe, JsValue> {
+        self.universes
+            .get(&id)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no universe {}", id)))
+    }
+
+    fn get_mut(&mut self, id: u32) -> Result<&mut Universe, JsValue> {
+        self.universes
+            .get_mut(&id)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no universe {}", id)))
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl UniverseSet {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> UniverseSet {
+        utils::set_panic_hook();
+
+        UniverseSet::default()
+    }
+
+    // Adds an empty universe ticking by `rule` and returns its id. Throws if
+    // the size is zero or too big, or the rule can't be read.
+    pub fn create(&mut self, width: u32, height: u32, rule: &str) -> Result<u32, JsValue> {
+        check_size(width, height).map_err(|err| JsValue::from_str(&err))?;
+        let mut universe = Universe::with_cells(
+            width,
+            height,
+            vec![Cell::Dead; width as usize * height as usize],
+        );
+        universe.rule = Rule::parse(rule).map_err(|err| JsValue::from_str(&err))?;
+        Ok(self.insert(universe))
+    }
+
+    // Takes over a universe built some other way, switching it to the shared
+    // palette, and returns its id. The universe passed in can't be used from
+    // JavaScript afterwards.
+    pub fn insert(&mut self, mut universe: Universe) -> u32 {
+        universe.palette = self.palette.clone();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.universes.insert(id, universe);
+        id
+    }
+
+    // Takes a universe out of the set and hands it back, or nothing if there
+    // was no such universe.
+    pub fn remove(&mut self, id: u32) -> Option<Universe> {
+        self.universes.remove(&id)
+    }
+
+    // The ids of every universe in the set, in the order they were added.
+    pub fn ids(&self) -> Vec<u32> {
+        self.universes.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.universes.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.universes.is_empty()
+    }
+
+    // Ticks every universe once, so they stay in step.
+    pub fn tick_all(&mut self) {
+        for universe in self.universes.values_mut() {
+            universe.tick();
+        }
+    }
+
+    pub fn tick(&mut self, id: u32) -> Result<(), JsValue> {
+        self.get_mut(id)?.tick();
+        Ok(())
+    }
+
+    pub fn width(&self, id: u32) -> Result<u32, JsValue> {
+        Ok(self.get(id)?.width)
+    }
+
+    pub fn height(&self, id: u32) -> Result<u32, JsValue> {
+        Ok(self.get(id)?.height)
+    }
+
+    pub fn generation(&self, id: u32) -> Result<u32, JsValue> {
+        Ok(self.get(id)?.generation)
+    }
+
+    pub fn population(&self, id: u32) -> Result<u32, JsValue> {
+        Ok(self.get(id)?.population())
+    }
+
+    pub fn rule(&self, id: u32) -> Result<String, JsValue> {
+        Ok(self.get(id)?.rule())
+    }
+
+    pub fn set_rule(&mut self, id: u32, rule: &str) -> Result<(), JsValue> {
+        self.get_mut(id)?.set_rule(rule)
+    }
+
+    // Pointer to a universe's cells, like `Universe.cells`.
+    pub fn cells(&self, id: u32) -> Result<*const Cell, JsValue> {
+        Ok(self.get(id)?.cells())
+    }
+
+    // Draws a universe in the shared palette and returns a pointer to the
+    // pixels, like `Universe.render_rgba`.
+    pub fn render_rgba(&mut self, id: u32) -> Result<*const u8, JsValue> {
+        Ok(self.get_mut(id)?.render_rgba())
+    }
+
+    pub fn toggle_cell(&mut self, id: u32, row: u32, column: u32) -> Result<(), JsValue> {
+        self.get_mut(id)?.toggle_cell(row, column)
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.palette.clone()
+    }
+
+    // Switches every universe in the set, and any added later, to `palette`.
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = palette.clone();
+        for universe in self.universes.values_mut() {
+            universe.palette = palette.clone();
+        }
+    }
+
+    // Adds a pattern in RLE to the library under `name`, replacing any
+    // pattern already called that. Throws if the pattern can't be read.
+    pub fn add_pattern(&mut self, name: String, rle: &str) -> Result<(), JsValue> {
+        let pattern = parse_rle(rle).map_err(|err| JsValue::from_str(&err))?;
+        self.patterns.insert(name, Universe::from_parsed(pattern));
+        Ok(())
+    }
+
+    pub fn remove_pattern(&mut self, name: &str) -> bool {
+        self.patterns.remove(name).is_some()
+    }
+
+    // The names of the patterns in the library, in alphabetical order.
+    pub fn pattern_names(&self) -> js_sys::Array {
+        self.patterns
+            .keys()
+            .map(|name| JsValue::from_str(name))
+            .collect()
+    }
+
+    // Stamps the library's pattern called `name` into a universe, centered on
+    // (row, column) and wrapping around the edges.
+    pub fn place_pattern(
+        &mut self,
+        id: u32,
+        name: &str,
+        row: u32,
+        column: u32,
+    ) -> Result<(), JsValue> {
+        let pattern = self
+            .patterns
+            .get(name)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no pattern called `{}`", name)))?;
+        let universe = self
+            .universes
+            .get_mut(&id)
+            .ok_or_else(|| JsValue::from_str(&format!("there's no universe {}", id)))?;
+        universe.paste_centered(pattern, row, column);
+        Ok(())
+    }
+}
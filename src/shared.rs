@@ -0,0 +1,143 @@
+use js_sys::{Atomics, Int32Array, SharedArrayBuffer, Uint8Array};
+use wasm_bindgen::prelude::*;
+
+use crate::{check_size, Cell, Universe};
+
+// A shared buffer starts with four i32s, followed by one byte per cell, row
+// by row. The sequence number is odd while a generation is being written and
+// even once it is complete, so readers can tell a torn copy from a good one.
+const HEADER_BYTES: u32 = 16;
+const SEQUENCE: u32 = 0;
+const GENERATION: u32 = 1;
+const WIDTH: u32 = 2;
+const HEIGHT: u32 = 3;
+
+// A SharedArrayBuffer the cells are published to after every tick.
+pub(crate) struct SharedCells {
+    header: Int32Array,
+    cells: Uint8Array,
+}
+
+impl SharedCells {
+    fn new(buffer: &SharedArrayBuffer) -> Result<SharedCells, JsValue> {
+        if buffer.byte_length() < HEADER_BYTES {
+            return Err(JsValue::from_str("the buffer is too small for the header"));
+        }
+
+        Ok(SharedCells {
+            header: Int32Array::new_with_byte_offset_and_length(buffer, 0, 4),
+            cells: Uint8Array::new_with_byte_offset(buffer, HEADER_BYTES),
+        })
+    }
+
+    pub(crate) fn fits(&self, cells: usize) -> bool {
+        self.cells.length() as usize >= cells
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Bytes a SharedArrayBuffer needs to hold a width x height universe.
+    // Throws if a universe that size can't be made.
+    pub fn shared_buffer_len(width: u32, height: u32) -> Result<u32, String> {
+        check_size(width, height)?;
+        Ok(HEADER_BYTES + width * height)
+    }
+
+    // Mirrors the cells into `buffer` after every tick, so another worker can
+    // watch them without any postMessage traffic. Wasm memory itself can't be
+    // shared without threads, so each generation is copied in once; the
+    // buffer starts with [sequence, generation, width, height] as i32s,
+    // followed by one byte per cell. The sequence is odd while a copy is in
+    // progress, and is bumped and notified once it is done, so readers can
+    // `Atomics.wait` on it and then take a copy with `load_shared_buffer`.
+    // Call `publish_shared` to push edits made between ticks. Throws if the
+    // buffer is too small; resizing past its size detaches it.
+    pub fn attach_shared_buffer(&mut self, buffer: &SharedArrayBuffer) -> Result<(), JsValue> {
+        let shared = SharedCells::new(buffer)?;
+        if !shared.fits(self.cells.len()) {
+            return Err(JsValue::from_str(&format!(
+                "the buffer needs {} bytes for a {}x{} universe",
+                HEADER_BYTES as usize + self.cells.len(),
+                self.width,
+                self.height
+            )));
+        }
+
+        self.shared = Some(shared);
+        self.publish_shared();
+        Ok(())
+    }
+
+    pub fn detach_shared_buffer(&mut self) {
+        self.shared = None;
+    }
+
+    // Writes the current cells to the attached buffer now, rather than
+    // waiting for the next tick, and wakes anyone waiting on its sequence
+    // number. Does nothing without a buffer.
+    pub fn publish_shared(&self) {
+        let shared = match &self.shared {
+            Some(shared) => shared,
+            None => return,
+        };
+
+        // Atomics only throw for views that aren't integer arrays, so the
+        // results are safe to ignore here.
+        let _ = Atomics::add(&shared.header, SEQUENCE, 1);
+        shared
+            .cells
+            .subarray(0, self.cells.len() as u32)
+            .copy_from(self.cell_bytes());
+        let _ = Atomics::store(&shared.header, GENERATION, self.generation as i32);
+        let _ = Atomics::store(&shared.header, WIDTH, self.width as i32);
+        let _ = Atomics::store(&shared.header, HEIGHT, self.height as i32);
+        let _ = Atomics::add(&shared.header, SEQUENCE, 1);
+        let _ = Atomics::notify(&shared.header, SEQUENCE);
+    }
+
+    // Takes the latest generation published to `buffer` by another universe,
+    // resizing to match if needed. Returns false, leaving the cells alone,
+    // when the other side was part way through writing; try again on the
+    // next frame.
+    pub fn load_shared_buffer(&mut self, buffer: &SharedArrayBuffer) -> Result<bool, JsValue> {
+        let shared = SharedCells::new(buffer)?;
+
+        let before = Atomics::load(&shared.header, SEQUENCE)?;
+        if before % 2 != 0 {
+            return Ok(false);
+        }
+        let generation = Atomics::load(&shared.header, GENERATION)? as u32;
+        let width = Atomics::load(&shared.header, WIDTH)? as u32;
+        let height = Atomics::load(&shared.header, HEIGHT)? as u32;
+        check_size(width, height).map_err(|err| JsValue::from_str(&err))?;
+        let len = width as usize * height as usize;
+        if !shared.fits(len) {
+            return Err(JsValue::from_str("the buffer doesn't hold a universe"));
+        }
+        let bytes = shared.cells.subarray(0, len as u32).to_vec();
+        if Atomics::load(&shared.header, SEQUENCE)? != before {
+            return Ok(false);
+        }
+
+        if width != self.width || height != self.height {
+            self.width = width;
+            self.height = height;
+            self.reset_cells();
+        }
+        self.previous.clone_from(&self.cells);
+        self.previous_valid = false;
+        for (idx, byte) in bytes.into_iter().enumerate() {
+            let cell = match byte {
+                0 => Cell::Dead,
+                2 => Cell::Wall,
+                _ => Cell::Alive,
+            };
+            self.set_cell(idx, cell);
+        }
+        self.generation = generation;
+        self.record_snapshot();
+        Ok(true)
+    }
+}
@@ -0,0 +1,748 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::{Rc, Weak};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::fragment::fragment_fields;
+use crate::import::{fetch_pattern, read_pattern_file};
+use crate::timing::{Samples, TimingStats};
+use crate::utils::{log_error, now_ms};
+use crate::{Cell, PauseCondition, Universe};
+
+// Longest gap between two frames that is caught up on, in milliseconds. After
+// a longer one, e.g. when the tab was in the background, the simulation picks
+// up where it left off instead of running every missed generation at once.
+const MAX_FRAME_GAP_MS: f64 = 250.0;
+
+// Longest cycle, in generations, that counts as the universe having
+// stabilized.
+const MAX_CYCLE_PERIOD: usize = 64;
+
+// Hashes of the most recent generations, oldest first, for spotting when the
+// universe starts repeating itself.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CycleDetector {
+    recent_hashes: VecDeque<u64>,
+}
+
+impl CycleDetector {
+    pub(crate) fn clear(&mut self) {
+        self.recent_hashes.clear();
+    }
+
+    // Adds the hash of the generation just reached. Returns the length of
+    // the cycle if one of the last MAX_CYCLE_PERIOD generations was the same,
+    // 1 for standing still.
+    pub(crate) fn push(&mut self, hash: u64) -> Option<u32> {
+        let seen = self.recent_hashes.iter().rposition(|&h| h == hash);
+        let period = seen.map(|position| (self.recent_hashes.len() - position) as u32);
+        self.recent_hashes.push_back(hash);
+        if self.recent_hashes.len() > MAX_CYCLE_PERIOD {
+            self.recent_hashes.pop_front();
+        }
+        period
+    }
+}
+
+// How much each new measurement moves the average time per generation.
+const TICK_TIME_SMOOTHING: f64 = 0.2;
+
+// Runs a universe on its own, ticking it from `requestAnimationFrame` at a
+// set number of generations per second. Owns the universe while it runs and
+// passes the calls the page needs for drawing and editing through to it.
+#[wasm_bindgen]
+pub struct Simulation {
+    state: Rc<RefCell<SimulationState>>,
+}
+
+// How the simulation decides how many generations to run each frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pacing {
+    // A steady number of generations per second, whatever the frame rate.
+    Speed(f64),
+    // `ticks` generations on every `frames`th frame.
+    Frames { ticks: u32, frames: u32 },
+    // As many generations as fit in a frame budget, in milliseconds.
+    Adaptive(f64),
+}
+
+struct SimulationState {
+    universe: Universe,
+    running: bool,
+    // Whether to play backwards through the universe's history.
+    reverse: bool,
+    pacing: Pacing,
+    // Frames since generations were last run, when pacing by frames.
+    frames_waited: u32,
+    // Running average of how long one generation takes, in milliseconds.
+    tick_ms: Option<f64>,
+    // Recent time per generation and time spent drawing, per frame.
+    tick_samples: Samples,
+    render_samples: Samples,
+    // Time owed to the simulation but not yet run, carried over between
+    // frames. Generations are run in fixed steps of 1000 / speed
+    // milliseconds, so the speed is the same at any refresh rate.
+    accumulated_ms: f64,
+    // Whether to cross-fade the universe's RGBA rendering between
+    // generations by how far into the next one the accumulator is.
+    interpolate: bool,
+    // Timestamp of the previous animation frame, if there was one.
+    last_frame: Option<f64>,
+    // Handle of the requested animation frame while running.
+    frame_id: Option<i32>,
+    on_frame: Option<js_sys::Function>,
+    // Generation to pause at, and what to call when it's reached.
+    target_generation: Option<u32>,
+    on_target: Option<js_sys::Function>,
+    on_extinct: Option<js_sys::Function>,
+    on_stable: Option<js_sys::Function>,
+    // Conditions to pause on, each with whether it was met last generation
+    // so it only pauses when it becomes met.
+    pause_conditions: Vec<(PauseCondition, bool)>,
+    on_pause_condition: Option<js_sys::Function>,
+    // Spots cycles. Only kept up while there's an on_stable callback.
+    cycles: CycleDetector,
+    // Callbacks due to run once the current frame's ticks are done, with the
+    // arguments to pass each.
+    due_callbacks: Vec<(js_sys::Function, js_sys::Array)>,
+    // The animation frame callback. It only holds a weak reference back to
+    // this state, so dropping the simulation frees both.
+    frame_callback: Option<Closure<dyn FnMut(f64)>>,
+}
+
+impl SimulationState {
+    // Works out how many generations are due on the frame `now`
+    // milliseconds in, keeping any fraction of a generation for the next one.
+    fn due_ticks(&mut self, now: f64) -> u32 {
+        let elapsed = match self.last_frame {
+            Some(last) => (now - last).clamp(0.0, MAX_FRAME_GAP_MS),
+            None => 0.0,
+        };
+        self.last_frame = Some(now);
+
+        match self.pacing {
+            Pacing::Speed(speed) if speed > 0.0 => {
+                let step_ms = 1000.0 / speed;
+                self.accumulated_ms += elapsed;
+                let ticks = (self.accumulated_ms / step_ms).floor();
+                self.accumulated_ms -= ticks * step_ms;
+                ticks as u32
+            }
+            Pacing::Speed(_) => 0,
+            Pacing::Frames { ticks, frames } => {
+                self.frames_waited += 1;
+                if self.frames_waited >= frames {
+                    self.frames_waited = 0;
+                    ticks
+                } else {
+                    0
+                }
+            }
+            // Start with a single generation until there's a measurement.
+            Pacing::Adaptive(budget) => match self.tick_ms {
+                Some(tick_ms) if tick_ms > 0.0 => (budget / tick_ms).floor().max(1.0) as u32,
+                Some(_) => u32::MAX,
+                None => 1,
+            },
+        }
+    }
+
+    // Runs up to `n` generations, timing them to keep the average time per
+    // generation up to date. With adaptive pacing, stops early once the frame
+    // budget is used up in case the average was off.
+    fn run_ticks(&mut self, n: u32, now: impl Fn() -> f64) {
+        if n == 0 {
+            return;
+        }
+
+        let budget = match self.pacing {
+            Pacing::Adaptive(budget) => budget,
+            _ => f64::INFINITY,
+        };
+
+        let start = now();
+        let mut elapsed = 0.0;
+        let mut ran = 0;
+        while ran < n && elapsed < budget && self.running {
+            if self.reverse {
+                if !self.universe.step_back() {
+                    self.running = false;
+                    break;
+                }
+            } else {
+                self.universe.tick();
+                self.after_tick();
+            }
+            ran += 1;
+            elapsed = now() - start;
+        }
+
+        if ran == 0 {
+            return;
+        }
+
+        let sample = elapsed / ran as f64;
+        self.tick_samples.push(sample);
+        self.tick_ms = Some(match self.tick_ms {
+            Some(average) => average + (sample - average) * TICK_TIME_SMOOTHING,
+            None => sample,
+        });
+    }
+
+    // Checks what should happen now that another generation has run,
+    // queuing callbacks as needed. Returns whether the simulation should
+    // pause, and pauses it if it's running.
+    fn after_tick(&mut self) -> bool {
+        let generation = JsValue::from(self.universe.generation());
+        let mut pause = false;
+
+        if self.target_generation == Some(self.universe.generation()) {
+            self.target_generation = None;
+            pause = true;
+            if let Some(on_target) = self.on_target.take() {
+                self.due_callbacks
+                    .push((on_target, js_sys::Array::of1(&generation)));
+            }
+        }
+
+        for (index, (condition, was_met)) in self.pause_conditions.iter_mut().enumerate() {
+            let met = condition.is_met(&self.universe);
+            if met && !*was_met {
+                pause = true;
+                if let Some(on_pause_condition) = &self.on_pause_condition {
+                    let index = JsValue::from(index as u32);
+                    self.due_callbacks.push((
+                        on_pause_condition.clone(),
+                        js_sys::Array::of2(&generation, &index),
+                    ));
+                }
+            }
+            *was_met = met;
+        }
+
+        if let Some(on_extinct) = &self.on_extinct {
+            if self.universe.population() == 0 {
+                pause = true;
+                self.cycles.clear();
+                self.due_callbacks
+                    .push((on_extinct.clone(), js_sys::Array::of1(&generation)));
+            }
+        }
+
+        if let (Some(on_stable), false) = (&self.on_stable, pause) {
+            if let Some(period) = self.cycles.push(self.universe.cells_hash()) {
+                pause = true;
+                self.cycles.clear();
+                self.due_callbacks.push((
+                    on_stable.clone(),
+                    js_sys::Array::of2(&generation, &JsValue::from(period)),
+                ));
+            }
+        }
+
+        if pause {
+            self.running = false;
+        }
+        pause
+    }
+
+    // How far the accumulator is into the next generation, from 0 to 1.
+    // Always 1 unless pacing by speed, when generations land on frames.
+    fn interpolation(&self) -> f64 {
+        match self.pacing {
+            Pacing::Speed(speed) if speed > 0.0 => {
+                (self.accumulated_ms * speed / 1000.0).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    // The frame based pacing, starting from one generation every frame when
+    // switching over from a speed.
+    fn frame_pacing(&self) -> (u32, u32) {
+        match self.pacing {
+            Pacing::Frames { ticks, frames } => (ticks, frames),
+            _ => (1, 1),
+        }
+    }
+
+    fn request_frame(&mut self) -> Result<(), JsValue> {
+        let callback = match &self.frame_callback {
+            Some(callback) => callback,
+            None => return Ok(()),
+        };
+        let id = window()?.request_animation_frame(callback.as_ref().unchecked_ref())?;
+        self.frame_id = Some(id);
+        Ok(())
+    }
+}
+
+// Methods not being exported to Javascript
+impl Simulation {
+    // Runs one animation frame: ticks as many generations as are due, lets
+    // the page draw, then asks for the next frame.
+    fn on_animation_frame(state: &Rc<RefCell<SimulationState>>, now: f64) {
+        {
+            let mut state = state.borrow_mut();
+            state.frame_id = None;
+            let ticks = state.due_ticks(now);
+            state.run_ticks(ticks, now_ms);
+            if state.interpolate {
+                let alpha = state.interpolation() as f32;
+                state.universe.set_crossfade(alpha);
+            }
+        }
+
+        Simulation::run_due_callbacks(state);
+
+        let render_start = now_ms();
+        if let Err(err) = Simulation::notify_frame(state) {
+            log_error(&err);
+        }
+
+        let mut state = state.borrow_mut();
+        state.render_samples.push(now_ms() - render_start);
+        if state.running && state.frame_id.is_none() {
+            if let Err(err) = state.request_frame() {
+                log_error(&err);
+            }
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Simulation {
+    // Delivers the universe's events and calls the callbacks queued up while
+    // ticking, after the borrow on the state is released so they can call
+    // back into the simulation.
+    fn run_due_callbacks(state: &Rc<RefCell<SimulationState>>) {
+        let (notifications, due) = {
+            let mut state = state.borrow_mut();
+            let notifications = state.universe.take_notifications();
+            (notifications, std::mem::take(&mut state.due_callbacks))
+        };
+        for notification in notifications {
+            notification.deliver();
+        }
+        for (callback, args) in due {
+            if let Err(err) = callback.apply(&JsValue::NULL, &args) {
+                log_error(&err);
+            }
+        }
+    }
+
+    // Runs `f` on the universe being simulated.
+    pub(crate) fn with_universe<R>(&self, f: impl FnOnce(&mut Universe) -> R) -> R {
+        f(&mut self.state.borrow_mut().universe)
+    }
+
+    // Calls the on_frame callback, if there is one. The callback is free to
+    // call back into the simulation, so no borrow is held while it runs.
+    fn notify_frame(state: &Rc<RefCell<SimulationState>>) -> Result<(), JsValue> {
+        let on_frame = state.borrow().on_frame.clone();
+        match on_frame {
+            Some(on_frame) => on_frame.call0(&JsValue::NULL).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Simulation {
+    // Wraps a universe in a stopped simulation running at 60 generations per
+    // second.
+    pub fn new(mut universe: Universe) -> Simulation {
+        universe.defer_notifications();
+        let state = Rc::new(RefCell::new(SimulationState {
+            universe,
+            running: false,
+            reverse: false,
+            pacing: Pacing::Speed(60.0),
+            frames_waited: 0,
+            tick_ms: None,
+            tick_samples: Samples::default(),
+            render_samples: Samples::default(),
+            accumulated_ms: 0.0,
+            interpolate: false,
+            last_frame: None,
+            frame_id: None,
+            on_frame: None,
+            frame_callback: None,
+            target_generation: None,
+            on_target: None,
+            on_extinct: None,
+            on_stable: None,
+            pause_conditions: Vec::new(),
+            on_pause_condition: None,
+            cycles: CycleDetector::default(),
+            due_callbacks: Vec::new(),
+        }));
+
+        let weak: Weak<RefCell<SimulationState>> = Rc::downgrade(&state);
+        let callback = Closure::wrap(Box::new(move |now: f64| {
+            if let Some(state) = weak.upgrade() {
+                Simulation::on_animation_frame(&state, now);
+            }
+        }) as Box<dyn FnMut(f64)>);
+        state.borrow_mut().frame_callback = Some(callback);
+
+        Simulation { state }
+    }
+
+    // Starts ticking on every animation frame. Does nothing if already
+    // running.
+    pub fn start(&mut self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        if state.running {
+            return Ok(());
+        }
+
+        state.running = true;
+        state.last_frame = None;
+        state.accumulated_ms = 0.0;
+        state.request_frame()
+    }
+
+    // Stops ticking after the current frame, if any, dropping any generation
+    // `run_to_generation` was playing to.
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.running = false;
+        state.target_generation = None;
+        state.on_target = None;
+        if let Some(id) = state.frame_id.take() {
+            window()?.cancel_animation_frame(id)?;
+        }
+        Ok(())
+    }
+
+    // Plays until the universe reaches generation `n`, then pauses and calls
+    // `callback`, if given, with the generation number. Does nothing if the
+    // universe is already at that generation, and throws if it's past it.
+    pub fn run_to_generation(
+        &mut self,
+        n: u32,
+        callback: Option<js_sys::Function>,
+    ) -> Result<(), JsValue> {
+        {
+            let mut state = self.state.borrow_mut();
+            let generation = state.universe.generation();
+            if n < generation {
+                return Err(JsValue::from_str(&format!(
+                    "Generation {} has already passed; the universe is at {}",
+                    n, generation
+                )));
+            }
+            if n == generation {
+                return Ok(());
+            }
+            state.target_generation = Some(n);
+            state.on_target = callback;
+        }
+        self.start()
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.state.borrow().reverse
+    }
+
+    // Plays backwards through the universe's recorded history instead of
+    // forwards, pausing when it runs out. Needs the history turned on with
+    // `set_history_limit` first.
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.state.borrow_mut().reverse = reverse;
+    }
+
+    pub fn set_history_limit(&mut self, generations: u32) {
+        self.state
+            .borrow_mut()
+            .universe
+            .set_history_limit(generations);
+    }
+
+    // Undoes the most recent recorded generation and calls on_frame so it
+    // gets drawn. Returns false when there's nothing left to undo.
+    pub fn step_back(&mut self) -> Result<bool, JsValue> {
+        let stepped = self.state.borrow_mut().universe.step_back();
+        Simulation::notify_frame(&self.state)?;
+        Ok(stepped)
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.state.borrow().universe.generation()
+    }
+
+    // Same as `start`, named for the play button.
+    pub fn play(&mut self) -> Result<(), JsValue> {
+        self.start()
+    }
+
+    // Same as `stop`, named for the pause button.
+    pub fn pause(&mut self) -> Result<(), JsValue> {
+        self.stop()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.borrow().running
+    }
+
+    // Runs a single generation straight away and calls on_frame so it gets
+    // drawn. Mostly useful while paused.
+    pub fn step(&mut self) -> Result<(), JsValue> {
+        self.step_n(1)
+    }
+
+    // Runs `n` generations straight away and calls on_frame once afterwards.
+    // Stops early if anything that would pause the simulation happens.
+    pub fn step_n(&mut self, n: u32) -> Result<(), JsValue> {
+        {
+            let mut state = self.state.borrow_mut();
+            for _ in 0..n {
+                state.universe.tick();
+                if state.after_tick() {
+                    break;
+                }
+            }
+        }
+        Simulation::run_due_callbacks(&self.state);
+        Simulation::notify_frame(&self.state)
+    }
+
+    // Generations per second, or NaN while pacing otherwise.
+    pub fn speed(&self) -> f64 {
+        match self.state.borrow().pacing {
+            Pacing::Speed(speed) => speed,
+            _ => f64::NAN,
+        }
+    }
+
+    // Sets how many generations run per second. Below the display's refresh
+    // rate some frames run none, above it some frames run several. Takes
+    // effect from the next frame, replacing any frame based pacing.
+    pub fn set_speed(&mut self, generations_per_second: f64) {
+        let mut state = self.state.borrow_mut();
+        state.pacing = Pacing::Speed(generations_per_second.max(0.0));
+        state.accumulated_ms = 0.0;
+    }
+
+    // Reads a pattern file in RLE (`.rle`), plaintext (`.cells`) or Life 1.05
+    // or 1.06 (`.lif`), e.g. one dropped on the canvas, and stamps it into
+    // the universe centered on (row, column). Returns a promise that resolves
+    // once it's in, or rejects, changing nothing, if the file can't be read
+    // or isn't a pattern.
+    pub fn import_file(&self, file: web_sys::File, row: u32, column: u32) -> js_sys::Promise {
+        let state = Rc::clone(&self.state);
+        wasm_bindgen_futures::future_to_promise(async move {
+            let pattern = read_pattern_file(file).await?;
+            state
+                .borrow_mut()
+                .universe
+                .paste_centered(&pattern, row, column);
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    // Downloads a pattern file in any of the formats `import_file` reads, e.g.
+    // straight from a LifeWiki pattern link, and stamps it into the universe
+    // centered on (row, column). Returns a promise that resolves once it's
+    // in, or rejects, changing nothing, if the download fails, including when
+    // the server doesn't allow cross-origin requests, or it isn't a pattern.
+    pub fn import_from_url(&self, url: String, row: u32, column: u32) -> js_sys::Promise {
+        let state = Rc::clone(&self.state);
+        wasm_bindgen_futures::future_to_promise(async move {
+            let pattern = fetch_pattern(url).await?;
+            state
+                .borrow_mut()
+                .universe
+                .paste_centered(&pattern, row, column);
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    // The universe's `to_url_fragment` plus the speed, when pacing by speed,
+    // so a shared link plays back the same way.
+    pub fn to_url_fragment(&self) -> String {
+        let state = self.state.borrow();
+        let fragment = state.universe.to_url_fragment();
+        match state.pacing {
+            Pacing::Speed(speed) => format!("{}&speed={}", fragment, speed),
+            _ => fragment,
+        }
+    }
+
+    // Applies a fragment from `to_url_fragment` to the universe, and sets the
+    // speed if the fragment has one. Throws, changing nothing, if the
+    // fragment can't be read.
+    pub fn apply_url_fragment(&mut self, hash: &str) -> Result<(), JsValue> {
+        let speed = match fragment_fields(hash).find(|&(key, _)| key == "speed") {
+            Some((_, speed)) => Some(
+                speed
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|speed| speed.is_finite())
+                    .ok_or_else(|| JsValue::from_str(&format!("`{}` isn't a speed", speed)))?,
+            ),
+            None => None,
+        };
+        self.state.borrow_mut().universe.apply_url_fragment(hash)?;
+        if let Some(speed) = speed {
+            self.set_speed(speed);
+        }
+        Ok(())
+    }
+
+    // How far the simulation is into the next generation, from 0 to 1, for
+    // blending the previous and current generations when drawing. Always 1
+    // unless pacing by speed.
+    pub fn interpolation(&self) -> f64 {
+        self.state.borrow().interpolation()
+    }
+
+    // Keeps the universe's cross-fade set to `interpolation()` every frame,
+    // so slow simulations fade smoothly from one generation to the next in
+    // `render_rgba`.
+    pub fn set_interpolate(&mut self, interpolate: bool) {
+        let mut state = self.state.borrow_mut();
+        state.interpolate = interpolate;
+        if !interpolate {
+            state.universe.set_crossfade(1.0);
+        }
+    }
+
+    pub fn ticks_per_frame(&self) -> u32 {
+        self.state.borrow().frame_pacing().0
+    }
+
+    // Runs `n` generations on each frame that runs any, however long the
+    // frames take. Replaces the speed set by `set_speed`.
+    pub fn set_ticks_per_frame(&mut self, n: u32) {
+        let mut state = self.state.borrow_mut();
+        let (_, frames) = state.frame_pacing();
+        state.pacing = Pacing::Frames { ticks: n, frames };
+    }
+
+    pub fn frames_per_tick(&self) -> u32 {
+        self.state.borrow().frame_pacing().1
+    }
+
+    // Only runs generations on every `m`th frame, for slow motion without
+    // changing how often the page draws. Replaces the speed set by
+    // `set_speed`.
+    pub fn set_frames_per_tick(&mut self, m: u32) {
+        let mut state = self.state.borrow_mut();
+        let (ticks, _) = state.frame_pacing();
+        state.pacing = Pacing::Frames {
+            ticks,
+            frames: m.max(1),
+        };
+        state.frames_waited = 0;
+    }
+
+    // The frame budget in milliseconds, or NaN when not pacing adaptively.
+    pub fn frame_budget(&self) -> f64 {
+        match self.state.borrow().pacing {
+            Pacing::Adaptive(budget) => budget,
+            _ => f64::NAN,
+        }
+    }
+
+    // Runs as many generations each frame as fit in `ms` milliseconds, going
+    // by how long recent generations took, so big universes slow down rather
+    // than making the page stutter. Around 12ms leaves room for drawing at
+    // 60fps. Replaces any other pacing.
+    pub fn set_frame_budget(&mut self, ms: f64) {
+        self.state.borrow_mut().pacing = Pacing::Adaptive(ms.max(0.0));
+    }
+
+    // Average time one generation has taken recently, in milliseconds, or
+    // NaN before any have run.
+    pub fn tick_time(&self) -> f64 {
+        self.state.borrow().tick_ms.unwrap_or(f64::NAN)
+    }
+
+    // Pauses the simulation whenever `condition` becomes met, checked after
+    // every generation. Returns the condition's index, which is passed to the
+    // on_pause_condition callback.
+    pub fn add_pause_condition(&mut self, condition: &PauseCondition) -> u32 {
+        let mut state = self.state.borrow_mut();
+        let met = condition.is_met(&state.universe);
+        state.pause_conditions.push((*condition, met));
+        state.pause_conditions.len() as u32 - 1
+    }
+
+    pub fn clear_pause_conditions(&mut self) {
+        self.state.borrow_mut().pause_conditions.clear();
+    }
+
+    // Sets a function to call when a pause condition pauses the simulation,
+    // with the generation number and the index of the condition.
+    pub fn set_on_pause_condition(&mut self, callback: Option<js_sys::Function>) {
+        self.state.borrow_mut().on_pause_condition = callback;
+    }
+
+    // Sets a function to call, with the generation number, when every cell
+    // has died. The simulation pauses first.
+    pub fn set_on_extinct(&mut self, callback: Option<js_sys::Function>) {
+        self.state.borrow_mut().on_extinct = callback;
+    }
+
+    // Sets a function to call when the universe settles into a repeating
+    // cycle of up to 64 generations, including standing still. It's passed
+    // the generation number and the length of the cycle, and the simulation
+    // pauses first.
+    pub fn set_on_stable(&mut self, callback: Option<js_sys::Function>) {
+        let mut state = self.state.borrow_mut();
+        state.on_stable = callback;
+        state.cycles.clear();
+    }
+
+    // How long generations and drawing have taken over the last couple of
+    // seconds of frames.
+    pub fn timing_stats(&self) -> TimingStats {
+        let state = self.state.borrow();
+        TimingStats::new(&state.tick_samples, &state.render_samples)
+    }
+
+    // Sets a function to call after every animation frame's ticks, to draw
+    // the universe.
+    pub fn set_on_frame(&mut self, callback: Option<js_sys::Function>) {
+        self.state.borrow_mut().on_frame = callback;
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.borrow().universe.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.borrow().universe.height()
+    }
+
+    pub fn cells(&self) -> *const Cell {
+        self.state.borrow().universe.cells()
+    }
+
+    pub fn render_rgba(&mut self) -> *const u8 {
+        self.state.borrow_mut().universe.render_rgba()
+    }
+
+    pub fn toggle_cell(&mut self, row: u32, column: u32) -> Result<(), JsValue> {
+        self.state.borrow_mut().universe.toggle_cell(row, column)
+    }
+}
+
+impl Drop for Simulation {
+    // Stops ticking and cancels any requested animation frame, so freeing the
+    // simulation from JavaScript doesn't leave a frame pending.
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log_error(&err);
+        }
+    }
+}
+
+fn window() -> Result<web_sys::Window, JsValue> {
+    web_sys::window().ok_or_else(|| JsValue::from_str("Animation needs a browser window"))
+}
@@ -0,0 +1,105 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+// Where each value sits in a frame from `sonify_frame`. The row densities
+// follow, one per row.
+const POPULATION: usize = 0;
+const BIRTHS: usize = 1;
+const DEATHS: usize = 2;
+const CLUSTERS: usize = 3;
+const LARGEST_CLUSTER_NOTE: usize = 4;
+const ROWS: usize = 5;
+
+// The MIDI notes the largest cluster is mapped between, with a lone cell
+// highest and every doubling in size six semitones lower.
+const HIGHEST_NOTE: f32 = 96.0;
+const LOWEST_NOTE: f32 = 36.0;
+const SEMITONES_PER_DOUBLING: f32 = 6.0;
+
+// Offsets of the eight cells around a cell.
+const NEIGHBORS: [(i64, i64); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// Methods not being exported to Javascript
+impl Universe {
+    // The sizes of the groups of live cells touching each other, including
+    // diagonally and across the edges.
+    fn cluster_sizes(&self) -> Vec<u32> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let mut seen = vec![false; self.cells.len()];
+        let mut sizes = Vec::new();
+        let mut stack = Vec::new();
+
+        for start in 0..self.cells.len() {
+            if seen[start] || self.cells[start] != Cell::Alive {
+                continue;
+            }
+            seen[start] = true;
+            stack.push(start);
+            let mut size = 0;
+            while let Some(idx) = stack.pop() {
+                size += 1;
+                let (row, col) = (idx as i64 / width, idx as i64 % width);
+                for (dr, dc) in &NEIGHBORS {
+                    let neighbor = ((row + dr).rem_euclid(height) * width
+                        + (col + dc).rem_euclid(width)) as usize;
+                    if !seen[neighbor] && self.cells[neighbor] == Cell::Alive {
+                        seen[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+        sizes
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The current generation as numbers for a WebAudio layer to play, in a
+    // Float32Array laid out as:
+    //
+    // - 0: the share of cells alive, from 0 to 1.
+    // - 1 and 2: the share of cells born and died getting here, from 0 to 1.
+    // - 3: how many separate clusters of live cells there are.
+    // - 4: a MIDI note for the largest cluster, from 96 for a lone cell down
+    //   to 36 as it grows, or 0 with no live cells.
+    // - 5 onwards: the share of each row alive, from 0 to 1, top row first,
+    //   e.g. as the gain of a voice per row.
+    //
+    // Finding the clusters visits every live cell, so this costs about as
+    // much as a tick.
+    pub fn sonify_frame(&self) -> Vec<f32> {
+        let cells = self.cells.len() as f32;
+        let (population, births, deaths) = self.tick_counts();
+        let clusters = self.cluster_sizes();
+
+        let mut frame = vec![0.0; ROWS + self.height as usize];
+        frame[POPULATION] = population as f32 / cells;
+        frame[BIRTHS] = births as f32 / cells;
+        frame[DEATHS] = deaths as f32 / cells;
+        frame[CLUSTERS] = clusters.len() as f32;
+        if let Some(&largest) = clusters.iter().max() {
+            frame[LARGEST_CLUSTER_NOTE] = (HIGHEST_NOTE
+                - SEMITONES_PER_DOUBLING * (largest as f32).log2())
+            .max(LOWEST_NOTE)
+            .round();
+        }
+        for (row, cells) in self.cells.chunks(self.width as usize).enumerate() {
+            let alive = cells.iter().filter(|&&cell| cell == Cell::Alive).count();
+            frame[ROWS + row] = alive as f32 / self.width as f32;
+        }
+        frame
+    }
+}
@@ -0,0 +1,38 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::Universe;
+
+// Everything needed to draw or save the universe, in the shape `get_state`
+// hands to JavaScript.
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    width: u32,
+    height: u32,
+    generation: u32,
+    #[serde(with = "serde_bytes")]
+    cells: &'a [u8],
+    rule: String,
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // The universe as a plain `{ width, height, generation, cells, rule }`
+    // object, with `cells` a Uint8Array of one byte per cell, row by row, 0
+    // for dead, 1 for alive and 2 for a wall. The cells are copied out of
+    // wasm memory, so unlike a view over `cells()` they stay valid whatever
+    // the universe does next. Costs a copy per call, so the pointer is still
+    // the way to go for drawing every frame.
+    pub fn get_state(&self) -> Result<JsValue, JsValue> {
+        let snapshot = Snapshot {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            cells: self.cell_bytes(),
+            rule: self.rule.to_string(),
+        };
+
+        Ok(serde_wasm_bindgen::to_value(&snapshot)?)
+    }
+}
@@ -0,0 +1,160 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{
+    IdbDatabase, IdbFactory, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode,
+    Storage,
+};
+
+use crate::{codec, Universe};
+
+// Where saved universes are kept in IndexedDB.
+const DATABASE: &str = "wasm-game-of-life";
+const DATABASE_VERSION: u32 = 1;
+const STORE: &str = "universes";
+
+// Quick save slots are kept in localStorage under this prefix and the slot
+// number, as the version followed by the base64 of a snapshot message.
+const SLOT_PREFIX: &str = "wasm-game-of-life/slot/";
+const SLOT_VERSION: &str = "1:";
+
+fn local_storage() -> Result<Storage, JsValue> {
+    js_sys::Reflect::get(&js_sys::global(), &"localStorage".into())?
+        .dyn_into::<Storage>()
+        .map_err(|_| JsValue::from_str("localStorage isn't available here"))
+}
+
+// The IndexedDB factory of the page or worker, looked up on the global object
+// so both work.
+fn indexed_db() -> Result<IdbFactory, JsValue> {
+    js_sys::Reflect::get(&js_sys::global(), &"indexedDB".into())?
+        .dyn_into::<IdbFactory>()
+        .map_err(|_| JsValue::from_str("IndexedDB isn't available here"))
+}
+
+// Waits for a request to finish and returns its result, or the error it
+// failed with.
+async fn request_done(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let done = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(done).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+
+    match outcome {
+        Ok(_) => request.result(),
+        Err(err) => Err(request.error().ok().flatten().map_or(err, JsValue::from)),
+    }
+}
+
+// Waits for a transaction to commit.
+async fn transaction_done(transaction: &IdbTransaction) -> Result<(), JsValue> {
+    let done = js_sys::Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+    JsFuture::from(done)
+        .await
+        .map(|_| ())
+        .map_err(|err| transaction.error().map_or(err, JsValue::from))
+}
+
+// Opens the database, creating the object store the first time.
+async fn open_database() -> Result<IdbDatabase, JsValue> {
+    let request: IdbOpenDbRequest = indexed_db()?.open_with_u32(DATABASE, DATABASE_VERSION)?;
+
+    let upgrading = request.clone();
+    let on_upgrade = Closure::<dyn FnMut()>::new(move || {
+        if let Ok(database) = upgrading.result() {
+            let _ = database
+                .unchecked_into::<IdbDatabase>()
+                .create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+    let database = request_done(&request).await;
+    request.set_onupgradeneeded(None);
+
+    Ok(database?.unchecked_into())
+}
+
+async fn save(key: String, buffer: js_sys::ArrayBuffer) -> Result<JsValue, JsValue> {
+    let database = open_database().await?;
+    let transaction =
+        database.transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?;
+    let request = transaction
+        .object_store(STORE)?
+        .put_with_key(&buffer, &JsValue::from_str(&key))?;
+    request_done(&request).await?;
+    transaction_done(&transaction).await?;
+    database.close();
+    Ok(JsValue::UNDEFINED)
+}
+
+async fn load(key: String) -> Result<JsValue, JsValue> {
+    let database = open_database().await?;
+    let transaction = database.transaction_with_str(STORE)?;
+    let request = transaction
+        .object_store(STORE)?
+        .get(&JsValue::from_str(&key))?;
+    let saved = request_done(&request).await?;
+    database.close();
+
+    let buffer = saved
+        .dyn_into::<js_sys::ArrayBuffer>()
+        .map_err(|_| JsValue::from_str(&format!("nothing is saved under `{}`", key)))?;
+    Universe::import_transferable(&buffer).map(JsValue::from)
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Saves the universe to IndexedDB under `key`, in the same binary format
+    // as `export_transferable`, replacing anything saved there before. Takes
+    // a couple of bytes per cell, so unlike localStorage it copes with big
+    // boards. Returns a promise that resolves once the save has committed.
+    // Changes made after calling this aren't saved.
+    pub fn save_to_indexeddb(&self, key: String) -> js_sys::Promise {
+        future_to_promise(save(key, self.export_transferable()))
+    }
+
+    // Loads a universe saved with `save_to_indexeddb`. Returns a promise of
+    // the universe, which rejects if nothing is saved under `key`.
+    pub fn load_from_indexeddb(key: String) -> js_sys::Promise {
+        future_to_promise(load(key))
+    }
+
+    // Saves the size, generation, rule, topology and live cells to
+    // localStorage in save slot `slot`, as a compact string of base64 with a
+    // few bytes per live cell. Survives reloads without any JavaScript glue. Throws if
+    // localStorage is unavailable or full.
+    pub fn quick_save(&self, slot: u8) -> Result<(), JsValue> {
+        let saved = format!(
+            "{}{}",
+            SLOT_VERSION,
+            codec::encode(&self.snapshot_message())
+        );
+        local_storage()?.set_item(&format!("{}{}", SLOT_PREFIX, slot), &saved)
+    }
+
+    // Puts back what was saved in `slot` with `quick_save`, resizing to match.
+    // Returns false, leaving the universe alone, when the slot is empty.
+    // Throws if what is there can't be read.
+    pub fn quick_load(&mut self, slot: u8) -> Result<bool, JsValue> {
+        let saved = match local_storage()?.get_item(&format!("{}{}", SLOT_PREFIX, slot))? {
+            Some(saved) => saved,
+            None => return Ok(false),
+        };
+
+        let encoded = saved
+            .strip_prefix(SLOT_VERSION)
+            .ok_or_else(|| JsValue::from_str(&format!("slot {} holds an unknown format", slot)))?;
+        codec::decode(encoded)
+            .and_then(|message| self.apply_message(&message))
+            .map_err(|err| JsValue::from_str(&format!("slot {}: {}", slot, err)))?;
+        Ok(true)
+    }
+}
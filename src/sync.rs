@@ -0,0 +1,335 @@
+use std::convert::{TryFrom, TryInto};
+
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::rule::Rule;
+use crate::{check_size, Cell, Edge, NutrientOptions, Universe};
+
+// The first byte of every sync message.
+const SNAPSHOT: u8 = 0;
+const DIFF: u8 = 1;
+
+// Appends `value` as a LEB128 varint: seven bits per byte, low bits first,
+// with the top bit set on every byte but the last.
+pub(crate) fn put_varint(out: &mut Vec<u8>, value: impl Into<u64>) {
+    let mut value = value.into();
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+// Reads a sync message front to back.
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn byte(&mut self) -> Result<u8, String> {
+        let (&first, rest) = self
+            .bytes
+            .split_first()
+            .ok_or_else(|| String::from("the message ends too soon"))?;
+        self.bytes = rest;
+        Ok(first)
+    }
+
+    pub(crate) fn varint64(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..70).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as u64;
+            if shift >= 64 || bits << shift >> shift != bits {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(String::from("a number in the message is too big"))
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u32, String> {
+        let value = self.varint64()?;
+        u32::try_from(value).map_err(|_| String::from("a number in the message is too big"))
+    }
+
+    // Reads cells written by `put_cells` over the top of `cells`.
+    pub(crate) fn cells(&mut self, cells: &mut [Cell]) -> Result<(), String> {
+        let count = self.varint()?;
+        let mut next = 0usize;
+        for _ in 0..count {
+            let entry = self.varint()?;
+            let idx = next + (entry >> 1) as usize;
+            if idx >= cells.len() {
+                return Err(format!("cell {} is outside the universe", idx));
+            }
+            cells[idx] = if entry & 1 == 1 {
+                Cell::Alive
+            } else {
+                Cell::Dead
+            };
+            next = idx + 1;
+        }
+        for idx in self.walls(cells.len())? {
+            cells[idx] = Cell::Wall;
+        }
+        Ok(())
+    }
+
+    // Reads the walls written by `put_walls` for a universe of `len` cells,
+    // none if the message ends first.
+    pub(crate) fn walls(&mut self, len: usize) -> Result<Vec<usize>, String> {
+        if self.bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let count = self.varint()?;
+        let mut walls = Vec::with_capacity(count as usize);
+        let mut next = 0usize;
+        for _ in 0..count {
+            let idx = next + self.varint()? as usize;
+            if idx >= len {
+                return Err(format!("wall {} is outside the universe", idx));
+            }
+            walls.push(idx);
+            next = idx + 1;
+        }
+        Ok(walls)
+    }
+
+    // Reads the topology near the end of a snapshot, a torus if the message
+    // ends first.
+    pub(crate) fn topology(&mut self) -> Result<Topology, String> {
+        if self.bytes.is_empty() {
+            return Ok(Topology::TORUS);
+        }
+        Ok(Topology {
+            horizontal: Edge::from_byte(self.byte()?)?.into(),
+            vertical: Edge::from_byte(self.byte()?)?.into(),
+        })
+    }
+
+    // Reads whether walls count as alive near the end of a snapshot, false
+    // if the message ends first.
+    pub(crate) fn walls_alive(&mut self) -> Result<bool, String> {
+        if self.bytes.is_empty() {
+            return Ok(false);
+        }
+        Ok(self.byte()? != 0)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    // Reads the nutrient options and levels for a universe of `len` cells
+    // from the end of a snapshot, the default options and no field if the
+    // message ends first.
+    pub(crate) fn nutrients(
+        &mut self,
+        len: usize,
+    ) -> Result<(NutrientOptions, Option<Vec<f32>>), String> {
+        if self.bytes.is_empty() {
+            return Ok((NutrientOptions::default(), None));
+        }
+        let mut options = [0.0; 5];
+        for option in options.iter_mut() {
+            *option = self.f32()?;
+        }
+        let levels = match self.byte()? {
+            0 => None,
+            _ => Some((0..len).map(|_| self.f32()).collect::<Result<Vec<f32>, _>>()?),
+        };
+        Ok((NutrientOptions::from_array(options), levels))
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(String::from("the message ends too soon"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+}
+
+// Appends the indexes of cells that became walls as varints of the gap since
+// the last one, after the other cells. Writes nothing at all without any, so
+// messages from universes without walls are the same as they always were.
+pub(crate) fn put_walls(out: &mut Vec<u8>, walls: &[usize]) {
+    if walls.is_empty() {
+        return;
+    }
+    put_varint(out, walls.len() as u32);
+    let mut next = 0;
+    for &idx in walls {
+        put_varint(out, (idx - next) as u32);
+        next = idx + 1;
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Writes the cells that differ from `base` as varints of the gap since the
+    // last one written, shifted up a bit to make room for the new state, and
+    // then any that became walls.
+    pub(crate) fn put_cells(&self, out: &mut Vec<u8>, base: impl Fn(usize) -> Cell) {
+        let (walls, changed): (Vec<usize>, Vec<usize>) = (0..self.cells.len())
+            .filter(|&idx| self.cells[idx] != base(idx))
+            .partition(|&idx| self.cells[idx] == Cell::Wall);
+
+        put_varint(out, changed.len() as u32);
+        let mut next = 0;
+        for idx in changed {
+            put_varint(out, ((idx - next) as u32) << 1 | self.cells[idx] as u32);
+            next = idx + 1;
+        }
+        put_walls(out, &walls);
+    }
+
+    // The snapshot message for the current state, without marking it as
+    // sent. Settings other than the defaults go at the very end, so snapshots
+    // without any read the same as they always have: a byte for each edge,
+    // whether walls count as alive, and the nutrients as `put_nutrients`
+    // writes them, each left off when it and everything after are the
+    // defaults. The walls section before them is written even when empty so
+    // the two can't be mixed up.
+    pub(crate) fn snapshot_message(&self) -> Vec<u8> {
+        let mut out = vec![SNAPSHOT];
+        put_varint(&mut out, self.generation);
+        put_varint(&mut out, self.width);
+        put_varint(&mut out, self.height);
+        let rule = self.rule.to_string();
+        out.push(rule.len() as u8);
+        out.extend_from_slice(rule.as_bytes());
+        self.put_cells(&mut out, |_| Cell::Dead);
+        let nutrients =
+            self.nutrients.is_some() || self.nutrient_options != NutrientOptions::default();
+        let walls_alive = self.walls_alive || nutrients;
+        if self.topology != Topology::TORUS || walls_alive {
+            if !self.cells.contains(&Cell::Wall) {
+                put_varint(&mut out, 0u32);
+            }
+            out.push(Edge::from(self.topology.horizontal) as u8);
+            out.push(Edge::from(self.topology.vertical) as u8);
+        }
+        if walls_alive {
+            out.push(self.walls_alive as u8);
+        }
+        if nutrients {
+            self.put_nutrients(&mut out);
+        }
+        out
+    }
+
+    // Applies a snapshot or diff message, without marking it as synced.
+    pub(crate) fn apply_message(&mut self, message: &[u8]) -> Result<(), String> {
+        let mut reader = Reader { bytes: message };
+        let kind = reader.byte()?;
+        let generation = reader.varint()?;
+        let width = reader.varint()?;
+        let height = reader.varint()?;
+
+        // Cells the message doesn't mention are dead in a snapshot and
+        // unchanged in a diff.
+        let (rule, mut cells) = match kind {
+            SNAPSHOT => {
+                let rule_len = reader.byte()? as usize;
+                let rule = std::str::from_utf8(reader.take(rule_len)?)
+                    .map_err(|err| err.to_string())
+                    .and_then(Rule::parse)?;
+                check_size(width, height)?;
+                (Some(rule), vec![Cell::Dead; width as usize * height as usize])
+            }
+            DIFF => {
+                if width != self.width || height != self.height {
+                    return Err(String::from(
+                        "the diff is for a different size, ask for a snapshot",
+                    ));
+                }
+                (None, self.cells.clone())
+            }
+            other => return Err(format!("unknown message kind {}", other)),
+        };
+
+        reader.cells(&mut cells)?;
+
+        if let Some(rule) = rule {
+            let topology = reader.topology()?;
+            let walls_alive = reader.walls_alive()?;
+            let (nutrient_options, nutrient_levels) = reader.nutrients(cells.len())?;
+            if width != self.width || height != self.height {
+                self.width = width;
+                self.height = height;
+                self.reset_cells();
+            }
+            self.rule = rule;
+            self.topology = topology;
+            self.walls_alive = walls_alive;
+            self.nutrient_options = nutrient_options;
+            self.set_nutrient_levels(nutrient_levels);
+        }
+        self.previous.clone_from(&self.cells);
+        for (idx, cell) in cells.into_iter().enumerate() {
+            self.set_cell(idx, cell);
+        }
+        self.previous_valid = false;
+        self.generation = generation;
+        self.record_snapshot();
+        Ok(())
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // A message with the whole universe, its size, generation, rule,
+    // topology and live cells, for bringing a peer that just connected up to date. Later
+    // changes are sent with `sync_diff`.
+    pub fn sync_snapshot(&mut self) -> Vec<u8> {
+        let out = self.snapshot_message();
+        self.synced.clone_from(&self.cells);
+        out
+    }
+
+    // A message with every cell that changed since the last one sent or
+    // applied, whether by ticking or editing, and the current generation.
+    // Only the changed cells go over the wire, a byte or two each, so this
+    // is cheap to send after every tick. Falls back to a snapshot when there
+    // is no earlier message to go from or the universe was resized.
+    //
+    // The messages are plain bytes for JavaScript to send however it likes,
+    // e.g. `socket.send(universe.sync_diff())` on a binary WebSocket, with the
+    // other side passing what arrives to `apply_sync`. Only one side should
+    // tick; the other watches and edits.
+    pub fn sync_diff(&mut self) -> Vec<u8> {
+        if self.synced.len() != self.cells.len() {
+            return self.sync_snapshot();
+        }
+
+        let mut out = vec![DIFF];
+        put_varint(&mut out, self.generation);
+        put_varint(&mut out, self.width);
+        put_varint(&mut out, self.height);
+        self.put_cells(&mut out, |idx| self.synced[idx]);
+
+        self.synced.clone_from(&self.cells);
+        out
+    }
+
+    // Applies a message from another universe's `sync_snapshot` or
+    // `sync_diff`. Throws if the message can't be read, or is a diff for a
+    // universe of a different size, in which case a snapshot is needed.
+    pub fn apply_sync(&mut self, message: &[u8]) -> Result<(), JsValue> {
+        self.apply_message(message)
+            .map_err(|err| JsValue::from_str(&err))?;
+        // What came in is already known on the other side, so it isn't sent
+        // back in the next diff.
+        self.synced.clone_from(&self.cells);
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+
+use crate::Universe;
+
+// Draws universes to an ANSI terminal for native builds. Every cell is two
+// characters wide so cells come out roughly square, and is colored with a
+// 24-bit background color taken from the universe's color map and render mode.
+// Each draw moves the cursor back to the top-left corner and overwrites the
+// previous frame, so calling `draw` after every tick gives an animation.
+pub struct Terminal<W: Write> {
+    out: W,
+    started: bool,
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn new(out: W) -> Terminal<W> {
+        Terminal {
+            out,
+            started: false,
+        }
+    }
+
+    pub fn draw(&mut self, universe: &Universe) -> io::Result<()> {
+        let mut frame = String::new();
+
+        if !self.started {
+            // Clear the screen and hide the cursor for the first frame.
+            frame.push_str("\x1b[2J\x1b[?25l");
+            self.started = true;
+        }
+        frame.push_str("\x1b[H");
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                let [r, g, b, _] = universe.pixel_color(universe.get_index(row, col));
+                frame.push_str(&format!("\x1b[48;2;{};{};{}m  ", r, g, b));
+            }
+            frame.push_str("\x1b[0m\n");
+        }
+
+        self.out.write_all(frame.as_bytes())?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for Terminal<W> {
+    // Leaves the terminal the way we found it: default colors and a visible
+    // cursor.
+    fn drop(&mut self) {
+        if self.started {
+            let _ = self.out.write_all(b"\x1b[0m\x1b[?25h");
+            let _ = self.out.flush();
+        }
+    }
+}
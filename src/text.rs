@@ -0,0 +1,165 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+// Bit for each dot of a braille character, indexed by [row][column] within
+// the 2x4 block of cells it covers.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Whether the cell at (row, column) is alive, treating anything past the
+    // edges as dead rather than wrapping.
+    fn is_alive_at(&self, row: u32, column: u32) -> bool {
+        row < self.height
+            && column < self.width
+            && self.cells[self.get_index(row, column)] == Cell::Alive
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Renders the universe as text, one line per row, using the given
+    // characters for live and dead cells and putting `separator` between the
+    // cells of a row. `render_with('#', '.', "")` gives plain-text output that
+    // common tools can read.
+    pub fn render_with(&self, alive_char: char, dead_char: char, separator: &str) -> String {
+        let mut out = String::new();
+
+        for line in self.cells.chunks(self.width as usize) {
+            for (col, &cell) in line.iter().enumerate() {
+                if col > 0 {
+                    out.push_str(separator);
+                }
+                out.push(if cell == Cell::Alive {
+                    alive_char
+                } else {
+                    dead_char
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Renders the universe with each Unicode braille character covering a
+    // block of 2x4 cells, so a 100x100 universe fits in 50x25 characters.
+    pub fn render_braille(&self) -> String {
+        let mut out = String::new();
+
+        for block_row in (0..self.height).step_by(4) {
+            for block_col in (0..self.width).step_by(2) {
+                let mut bits = 0;
+                for (dy, dots) in BRAILLE_DOTS.iter().enumerate() {
+                    for (dx, dot) in dots.iter().enumerate() {
+                        if self.is_alive_at(block_row + dy as u32, block_col + dx as u32) {
+                            bits |= dot;
+                        }
+                    }
+                }
+                out.push(std::char::from_u32(0x2800 + bits).unwrap_or(' '));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Renders the universe with each character covering two cells stacked
+    // on top of each other, using the half block characters. Text cells are
+    // about twice as tall as they are wide, so this keeps cells roughly
+    // square, like on the canvas.
+    pub fn render_halfblocks(&self) -> String {
+        let mut out = String::new();
+
+        for row in (0..self.height).step_by(2) {
+            for col in 0..self.width {
+                out.push(
+                    match (self.is_alive_at(row, col), self.is_alive_at(row + 1, col)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    },
+                );
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Renders the universe as a self-contained HTML snippet: a CSS grid with
+    // every cell `cell_px` pixels square. Only cells that differ from the
+    // background are emitted, and all styling is inline so the snippet
+    // survives being pasted into blog posts and emails.
+    pub fn render_html(&self, cell_px: u32) -> String {
+        let background = self.palette.background_color();
+        let mut out = format!(
+            "<div style=\"display:grid;grid-template-columns:repeat({}, {}px);grid-template-rows:repeat({}, {}px);width:{}px;height:{}px;background:{}\">",
+            self.width,
+            cell_px,
+            self.height,
+            cell_px,
+            self.width * cell_px,
+            self.height * cell_px,
+            css_color(background),
+        );
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let color = self.pixel_color(self.get_index(row, col));
+                if color != background {
+                    out.push_str(&format!(
+                        "<div style=\"grid-area:{}/{};background:{}\"></div>",
+                        row + 1,
+                        col + 1,
+                        css_color(color),
+                    ));
+                }
+            }
+        }
+
+        out.push_str("</div>");
+        out
+    }
+
+    // Returns each row as a hex-encoded bitstring. The first cell of a row is
+    // the most significant bit of its first byte, and rows are padded with
+    // dead cells to a whole number of bytes.
+    pub fn render_scanlines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width as usize)
+            .map(|line| hex_bits(line.iter().map(|&cell| cell == Cell::Alive)))
+            .collect()
+    }
+}
+
+// Hex digits of `bits`, most significant bit first, with the bits padded
+// with zeroes up to a whole number of bytes.
+fn hex_bits(bits: impl Iterator<Item = bool>) -> String {
+    let mut out = String::new();
+    let mut byte = 0u8;
+    let mut count = 0;
+
+    for bit in bits {
+        byte = (byte << 1) | bit as u8;
+        count += 1;
+        if count == 8 {
+            out.push_str(&format!("{:02x}", byte));
+            byte = 0;
+            count = 0;
+        }
+    }
+    if count > 0 {
+        out.push_str(&format!("{:02x}", byte << (8 - count)));
+    }
+
+    out
+}
+
+fn css_color([r, g, b, a]: [u8; 4]) -> String {
+    format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
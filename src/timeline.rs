@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::nutrients::NutrientField;
+use crate::{Cell, Universe};
+
+// A frame of the timeline after the oldest: its generation, the cells that
+// changed since the frame before, in their new states, and the whole
+// nutrient field if there was one.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Delta {
+    generation: u32,
+    changes: Vec<(u32, Cell)>,
+    nutrients: Option<Vec<f32>>,
+}
+
+// The last few generations ticked through, to scrub back and forth over.
+// The oldest frame is kept in full and every later one as what changed since
+// the one before, so a quiet universe takes little memory to keep.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Timeline {
+    limit: usize,
+    // The oldest frame, empty when there are none.
+    base: Vec<Cell>,
+    base_generation: u32,
+    base_nutrients: Option<Vec<f32>>,
+    deltas: VecDeque<Delta>,
+    // The newest frame in full, to work out the next delta from.
+    newest: Vec<Cell>,
+    // The frame on show, counting from the oldest, when scrubbed back from
+    // the newest.
+    cursor: Option<usize>,
+}
+
+impl Timeline {
+    fn len(&self) -> usize {
+        if self.base.is_empty() {
+            0
+        } else {
+            self.deltas.len() + 1
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.base.clear();
+        self.base_nutrients = None;
+        self.deltas.clear();
+        self.newest.clear();
+        self.cursor = None;
+    }
+
+    fn position(&self) -> usize {
+        self.cursor.unwrap_or(self.deltas.len())
+    }
+
+    // The cells of a frame, counting from the oldest.
+    fn frame(&self, frame: usize) -> Vec<Cell> {
+        let mut cells = self.base.clone();
+        for delta in self.deltas.iter().take(frame) {
+            for &(idx, cell) in &delta.changes {
+                cells[idx as usize] = cell;
+            }
+        }
+        cells
+    }
+
+    fn generation(&self, frame: usize) -> u32 {
+        match frame {
+            0 => self.base_generation,
+            frame => self.deltas[frame - 1].generation,
+        }
+    }
+
+    fn nutrients(&self, frame: usize) -> Option<&[f32]> {
+        match frame {
+            0 => self.base_nutrients.as_deref(),
+            frame => self.deltas[frame - 1].nutrients.as_deref(),
+        }
+    }
+
+    // Whether the next tick recorded starts the timeline over, from the
+    // generation before it.
+    pub(crate) fn is_restarting(&self, len: usize) -> bool {
+        self.limit > 0 && self.base.len() != len
+    }
+
+    // Makes `cells` and `nutrients` the newest frame. Frames after the one
+    // on show go first, as the universe has moved on from it some other way,
+    // and the oldest go once over the limit.
+    fn push(&mut self, cells: &[Cell], generation: u32, nutrients: Option<&[f32]>) {
+        if let Some(cursor) = self.cursor.take() {
+            self.deltas.truncate(cursor);
+            self.newest = self.frame(cursor);
+        }
+
+        let changes = (0..cells.len())
+            .filter(|&idx| cells[idx] != self.newest[idx])
+            .map(|idx| (idx as u32, cells[idx]))
+            .collect();
+        self.newest.copy_from_slice(cells);
+        self.deltas.push_back(Delta {
+            generation,
+            changes,
+            nutrients: nutrients.map(<[f32]>::to_vec),
+        });
+        self.trim();
+    }
+
+    // Forgets the oldest frames until there are no more than the limit.
+    fn trim(&mut self) {
+        while self.len() > self.limit {
+            match self.deltas.pop_front() {
+                Some(oldest) => {
+                    for (idx, cell) in oldest.changes {
+                        self.base[idx as usize] = cell;
+                    }
+                    self.base_generation = oldest.generation;
+                    self.base_nutrients = oldest.nutrients;
+                    self.cursor = self.cursor.map(|cursor| cursor.saturating_sub(1));
+                }
+                None => self.clear(),
+            }
+        }
+    }
+
+    // Keeps the generation a tick just made, starting from the one before
+    // it if there are no frames yet. `nutrients_before` is only needed then.
+    pub(crate) fn record(
+        &mut self,
+        before: &[Cell],
+        after: &[Cell],
+        generation: u32,
+        nutrients_before: Option<Vec<f32>>,
+        nutrients: Option<&[f32]>,
+    ) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.base.len() != after.len() {
+            self.clear();
+            self.base = before.to_vec();
+            self.base_generation = generation.wrapping_sub(1);
+            self.base_nutrients = nutrients_before;
+            self.newest = before.to_vec();
+        }
+        self.push(after, generation, nutrients);
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    pub fn timeline_limit(&self) -> u32 {
+        self.timeline.limit as u32
+    }
+
+    // Starts keeping the last `frames` generations ticked through, for a
+    // scrubber to move over with `seek_relative`. Unlike `step_back`, which
+    // undoes ticks, scrubbing leaves every frame in place to come back to.
+    // With a nutrient field, every frame keeps the whole field as well. 0
+    // turns the timeline off and forgets it.
+    pub fn set_timeline_limit(&mut self, frames: u32) {
+        self.timeline.limit = frames as usize;
+        self.timeline.trim();
+    }
+
+    // Number of frames on the timeline, the current one included.
+    pub fn timeline_length(&self) -> u32 {
+        self.timeline.len() as u32
+    }
+
+    // The frame on show, counting from the oldest. `timeline_length() - 1`
+    // unless scrubbed back.
+    pub fn timeline_position(&self) -> u32 {
+        match self.timeline.len() {
+            0 => 0,
+            _ => self.timeline.position() as u32,
+        }
+    }
+
+    // Shows the frame `offset` frames later on the timeline, or earlier if
+    // negative, stopping at either end, and returns how far it moved. The
+    // cells and generation are those of the frame, but ages aren't kept on
+    // the timeline, so cells that change on the way start their age over as
+    // if edited. Ticking from a frame before the newest drops the frames
+    // after it and carries on from there. Edits made to the frame on show are kept as a frame of their
+    // own before moving, so scrubbing never loses them. The nutrient field,
+    // if any, goes back to how it was in the frame too, and there's nothing
+    // left to `step_back` through.
+    pub fn seek_relative(&mut self, offset: i32) -> i32 {
+        if self.timeline.len() == 0 {
+            return 0;
+        }
+
+        let shown = self.timeline.position();
+        let nutrients = self.nutrients.as_ref().map(NutrientField::levels);
+        if self.cells != self.timeline.frame(shown) || nutrients != self.timeline.nutrients(shown) {
+            self.timeline.push(&self.cells, self.generation, nutrients);
+        }
+
+        let from = self.timeline.position();
+        let to = (from as i64 + offset as i64).clamp(0, self.timeline.deltas.len() as i64) as usize;
+        let cells = self.timeline.frame(to);
+        self.timeline.cursor = if to == self.timeline.deltas.len() {
+            None
+        } else {
+            Some(to)
+        };
+
+        self.previous.clone_from(&self.cells);
+        self.previous_valid = false;
+        for (idx, cell) in cells.into_iter().enumerate() {
+            self.set_cell(idx, cell);
+        }
+        self.generation = self.timeline.generation(to);
+        let nutrients = self.timeline.nutrients(to).map(<[f32]>::to_vec);
+        self.set_nutrient_levels(nutrients);
+        // Stepping back from here would undo ticks made before the jump.
+        self.history.clear();
+        self.record_snapshot();
+        to as i32 - from as i32
+    }
+}
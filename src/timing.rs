@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+// Number of recent measurements kept for the timing stats, about two seconds'
+// worth of frames at 60fps.
+const TIMING_WINDOW: usize = 120;
+
+// The most recent durations of something, in milliseconds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Samples {
+    values: VecDeque<f64>,
+}
+
+impl Samples {
+    pub(crate) fn push(&mut self, ms: f64) {
+        self.values.push_back(ms);
+        if self.values.len() > TIMING_WINDOW {
+            self.values.pop_front();
+        }
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        if self.values.is_empty() {
+            return f64::NAN;
+        }
+        self.values.iter().sum::<f64>() / self.values.len() as f64
+    }
+
+    // The smallest sample at least `p` percent of the samples are no bigger
+    // than.
+    pub(crate) fn percentile(&self, p: f64) -> f64 {
+        if self.values.is_empty() {
+            return f64::NAN;
+        }
+
+        let mut sorted: Vec<f64> = self.values.iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+// How long recent generations and renders took, in milliseconds, for showing
+// in a performance overlay. Values are NaN until something has been measured.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimingStats {
+    tick_mean: f64,
+    tick_p95: f64,
+    render_mean: f64,
+    render_p95: f64,
+}
+
+// Methods not being exported to Javascript
+impl TimingStats {
+    pub(crate) fn new(ticks: &Samples, renders: &Samples) -> TimingStats {
+        TimingStats {
+            tick_mean: ticks.mean(),
+            tick_p95: ticks.percentile(95.0),
+            render_mean: renders.mean(),
+            render_p95: renders.percentile(95.0),
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl TimingStats {
+    // Average time a single generation took.
+    pub fn tick_mean(&self) -> f64 {
+        self.tick_mean
+    }
+
+    // Time a single generation took in the slowest 5% of frames.
+    pub fn tick_p95(&self) -> f64 {
+        self.tick_p95
+    }
+
+    // Average time the on_frame callback took to draw.
+    pub fn render_mean(&self) -> f64 {
+        self.render_mean
+    }
+
+    // Time the on_frame callback took in the slowest 5% of frames.
+    pub fn render_p95(&self) -> f64 {
+        self.render_p95
+    }
+}
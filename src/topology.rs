@@ -0,0 +1,102 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::recording::Edit;
+use crate::Universe;
+
+// What a neighbor past one pair of opposite edges of the universe turns out
+// to be. Named "wrap", "bounded" or "flip" in configs, links and recordings.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    // The cell on the other side, as if the edges were glued together.
+    Wrap = 0,
+    // Nothing: cells past the edge are always dead.
+    Bounded = 1,
+    // The cell on the other side, mirrored along the edge, as if the
+    // universe were given a half twist before gluing.
+    Flip = 2,
+}
+
+impl From<Edge> for life_engine::Edge {
+    fn from(edge: Edge) -> Self {
+        match edge {
+            Edge::Wrap => life_engine::Edge::Wrap,
+            Edge::Bounded => life_engine::Edge::Bounded,
+            Edge::Flip => life_engine::Edge::Flip,
+        }
+    }
+}
+
+impl From<life_engine::Edge> for Edge {
+    fn from(edge: life_engine::Edge) -> Self {
+        match edge {
+            life_engine::Edge::Wrap => Edge::Wrap,
+            life_engine::Edge::Bounded => Edge::Bounded,
+            life_engine::Edge::Flip => Edge::Flip,
+        }
+    }
+}
+
+impl Edge {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Edge::Wrap => "wrap",
+            Edge::Bounded => "bounded",
+            Edge::Flip => "flip",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Result<Edge, String> {
+        match name {
+            "wrap" => Ok(Edge::Wrap),
+            "bounded" => Ok(Edge::Bounded),
+            "flip" => Ok(Edge::Flip),
+            other => Err(format!("unknown edge `{}`", other)),
+        }
+    }
+
+    // The edge stored as a byte, `edge as u8`.
+    pub(crate) fn from_byte(byte: u8) -> Result<Edge, String> {
+        match byte {
+            0 => Ok(Edge::Wrap),
+            1 => Ok(Edge::Bounded),
+            2 => Ok(Edge::Flip),
+            other => Err(format!("unknown edge {}", other)),
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // What happens past the left and right edges when ticking.
+    pub fn horizontal_edge(&self) -> Edge {
+        self.topology.horizontal.into()
+    }
+
+    // What happens past the top and bottom edges when ticking.
+    pub fn vertical_edge(&self) -> Edge {
+        self.topology.vertical.into()
+    }
+
+    // Sets how the edges join up when ticking, `horizontal` for the left and
+    // right edges and `vertical` for the top and bottom. Wrapping both, the
+    // default, makes a torus; other shapes are:
+    //
+    // - cylinder: `Wrap`, `Bounded`.
+    // - Möbius strip: `Flip`, `Bounded`.
+    // - Klein bottle: `Flip`, `Wrap`.
+    // - projective plane: `Flip`, `Flip`.
+    // - plain rectangle: `Bounded`, `Bounded`.
+    pub fn set_topology(&mut self, horizontal: Edge, vertical: Edge) {
+        self.record(Edit::Topology(horizontal, vertical));
+        self.topology = Topology {
+            horizontal: horizontal.into(),
+            vertical: vertical.into(),
+        };
+    }
+}
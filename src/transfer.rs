@@ -0,0 +1,180 @@
+use std::convert::TryInto;
+
+use wasm_bindgen::prelude::*;
+
+use life_engine::Topology;
+
+use crate::rule::Rule;
+use crate::{check_size, Cell, Edge, Neighborhood, NutrientOptions, Universe};
+
+// Marks the start of an exported universe, followed by the format version.
+// Version 1 had no topology, version 2 no wall setting and version 3 no
+// nutrients.
+const MAGIC: &[u8; 4] = b"LIFE";
+const VERSION: u8 = 4;
+
+// Reads an exported universe front to back.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(String::from("the buffer ends too soon"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Packs the cells, their ages and heat, the generation, rule,
+    // neighborhood, topology and wall setting into bytes: the magic and
+    // version, then width, height and generation as little-endian u32s, the
+    // neighborhood, the horizontal and vertical edges and whether walls count
+    // as alive as a byte each, the rule as a length-prefixed string, the
+    // cells and heat at a byte each and the ages at four, and finally the
+    // nutrients as `put_nutrients` writes them.
+    fn to_bytes(&self) -> Vec<u8> {
+        let rule = self.rule.to_string();
+        let mut bytes = Vec::with_capacity(32 + rule.len() + self.cells.len() * 6);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        bytes.push(self.neighborhood as u8);
+        bytes.push(Edge::from(self.topology.horizontal) as u8);
+        bytes.push(Edge::from(self.topology.vertical) as u8);
+        bytes.push(self.walls_alive as u8);
+        bytes.push(rule.len() as u8);
+        bytes.extend_from_slice(rule.as_bytes());
+        bytes.extend_from_slice(self.cell_bytes());
+        bytes.extend_from_slice(&self.heat);
+        for age in &self.ages {
+            bytes.extend_from_slice(&age.to_le_bytes());
+        }
+        self.put_nutrients(&mut bytes);
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Universe, String> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(4)? != MAGIC {
+            return Err(String::from("not an exported universe"));
+        }
+        let version = reader.u8()?;
+        if version == 0 || version > VERSION {
+            return Err(format!("can't read version {} exports", version));
+        }
+
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let generation = reader.u32()?;
+        let neighborhood = match reader.u8()? {
+            0 => Neighborhood::Moore,
+            1 => Neighborhood::Hex,
+            other => return Err(format!("unknown neighborhood {}", other)),
+        };
+        let topology = if version >= 2 {
+            Topology {
+                horizontal: Edge::from_byte(reader.u8()?)?.into(),
+                vertical: Edge::from_byte(reader.u8()?)?.into(),
+            }
+        } else {
+            Topology::TORUS
+        };
+        let walls_alive = version >= 3 && reader.u8()? != 0;
+        let rule_len = reader.u8()? as usize;
+        let rule = std::str::from_utf8(reader.take(rule_len)?)
+            .map_err(|err| err.to_string())
+            .and_then(Rule::parse)?;
+
+        check_size(width, height)?;
+        let len = width as usize * height as usize;
+        let cells = reader
+            .take(len)?
+            .iter()
+            .map(|&byte| match byte {
+                0 => Ok(Cell::Dead),
+                1 => Ok(Cell::Alive),
+                2 => Ok(Cell::Wall),
+                other => Err(format!("{} isn't a cell state", other)),
+            })
+            .collect::<Result<Vec<Cell>, String>>()?;
+        let heat = reader.take(len)?.to_vec();
+        let ages = reader
+            .take(len * 4)?
+            .chunks_exact(4)
+            .map(|age| u32::from_le_bytes(age.try_into().unwrap()))
+            .collect();
+        let (nutrient_options, nutrient_levels) = if version >= 4 {
+            let mut options = [0.0; 5];
+            for option in options.iter_mut() {
+                *option = reader.f32()?;
+            }
+            let levels = match reader.u8()? {
+                0 => None,
+                _ => Some((0..len).map(|_| reader.f32()).collect::<Result<Vec<f32>, _>>()?),
+            };
+            (NutrientOptions::from_array(options), levels)
+        } else {
+            (NutrientOptions::default(), None)
+        };
+
+        let mut universe = Universe::with_cells(width, height, cells);
+        universe.generation = generation;
+        universe.neighborhood = neighborhood;
+        universe.topology = topology;
+        universe.walls_alive = walls_alive;
+        universe.nutrient_options = nutrient_options;
+        universe.set_nutrient_levels(nutrient_levels);
+        universe.rule = rule;
+        universe.heat = heat;
+        universe.ages = ages;
+        Ok(universe)
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Packs the whole state of the universe, its cells, their ages and heat,
+    // the generation, rule, neighborhood, topology, wall setting and nutrients,
+    // into a fresh ArrayBuffer. The
+    // buffer lives outside wasm memory, so it can be listed as a transferable
+    // in `postMessage` and handed to or from a Web Worker without another
+    // copy. Rebuild the universe on the other side with
+    // `Universe.import_transferable`. Display settings stay behind.
+    pub fn export_transferable(&self) -> js_sys::ArrayBuffer {
+        let bytes = self.to_bytes();
+        let buffer = js_sys::ArrayBuffer::new(bytes.len() as u32);
+        js_sys::Uint8Array::new(&buffer).copy_from(&bytes);
+        buffer
+    }
+
+    // Rebuilds a universe from a buffer made by `export_transferable`.
+    // Throws if the buffer isn't one.
+    pub fn import_transferable(buffer: &js_sys::ArrayBuffer) -> Result<Universe, JsValue> {
+        let bytes = js_sys::Uint8Array::new(buffer).to_vec();
+        Universe::from_bytes(&bytes).map_err(|err| JsValue::from_str(&err))
+    }
+}
@@ -0,0 +1,279 @@
+use wasm_bindgen::prelude::*;
+
+use life_engine::Rule3D;
+
+use crate::random::Random;
+use crate::{check_size_3d, utils, Cell};
+
+// Which axis a slice through a `Universe3D` is taken across.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X = 0,
+    Y = 1,
+    Z = 2,
+}
+
+// The neighbors each face of a voxel looks out to, and the corners of those
+// faces as offsets within the voxel's unit cube, wound counter-clockwise
+// seen from outside.
+const FACE_NEIGHBORS: [(i32, i32, i32); 6] = [
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, -1, 0),
+    (0, 1, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+const FACE_CORNERS: [[(f32, f32, f32); 4]; 6] = [
+    [(0., 0., 0.), (0., 0., 1.), (0., 1., 1.), (0., 1., 0.)],
+    [(1., 0., 0.), (1., 1., 0.), (1., 1., 1.), (1., 0., 1.)],
+    [(0., 0., 0.), (1., 0., 0.), (1., 0., 1.), (0., 0., 1.)],
+    [(0., 1., 0.), (0., 1., 1.), (1., 1., 1.), (1., 1., 0.)],
+    [(0., 0., 0.), (0., 1., 0.), (1., 1., 0.), (1., 0., 0.)],
+    [(0., 0., 1.), (1., 0., 1.), (1., 1., 1.), (0., 1., 1.)],
+];
+
+// Life in three dimensions: a width x height x depth block of voxels, each
+// with the 26 around it as neighbors and the edges wrapping around, ticking
+// by a 3D rule like Bays' 5766 or 4555. The voxels are kept in one flat
+// buffer, x fastest, then y, then z.
+#[wasm_bindgen]
+pub struct Universe3D {
+    width: u32,
+    height: u32,
+    depth: u32,
+    cells: Vec<Cell>,
+    // The buffer the next generation is written into.
+    next: Vec<Cell>,
+    rule: Rule3D,
+    generation: u32,
+    // Cells born and died getting to the current generation.
+    births: u32,
+    deaths: u32,
+}
+
+// Methods not being exported to Javascript
+impl Universe3D {
+    fn get_index(&self, x: u32, y: u32, z: u32) -> usize {
+        ((z * self.height + y) * self.width + x) as usize
+    }
+
+    // The voxel `dx`, `dy`, `dz` away from (x, y, z), wrapping around.
+    fn offset(&self, x: u32, y: u32, z: u32, (dx, dy, dz): (i32, i32, i32)) -> usize {
+        let wrap = |value: u32, delta: i32, size: u32| {
+            (value as i32 + delta).rem_euclid(size as i32) as u32
+        };
+        self.get_index(
+            wrap(x, dx, self.width),
+            wrap(y, dy, self.height),
+            wrap(z, dz, self.depth),
+        )
+    }
+
+    fn live_neighbors(&self, x: u32, y: u32, z: u32) -> u8 {
+        let mut count = 0;
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy, dz) != (0, 0, 0)
+                        && self.cells[self.offset(x, y, z, (dx, dy, dz))] == Cell::Alive
+                    {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn position(&self, x: u32, y: u32, z: u32) -> Result<usize, JsValue> {
+        if x >= self.width || y >= self.height || z >= self.depth {
+            return Err(JsValue::from_str(&format!(
+                "({}, {}, {}) is outside the {} by {} by {} universe",
+                x, y, z, self.width, self.height, self.depth
+            )));
+        }
+        Ok(self.get_index(x, y, z))
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe3D {
+    // An empty universe ticking by `rule`, in Bays' notation like "5766" or
+    // B/S notation like "B6/S567". Throws if the size is zero or too big, or
+    // the rule can't be read.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32, depth: u32, rule: &str) -> Result<Universe3D, JsValue> {
+        utils::set_panic_hook();
+
+        check_size_3d(width, height, depth).map_err(|err| JsValue::from_str(&err))?;
+        let len = width as usize * height as usize * depth as usize;
+        Ok(Universe3D {
+            width,
+            height,
+            depth,
+            cells: vec![Cell::Dead; len],
+            next: vec![Cell::Dead; len],
+            rule: Rule3D::parse(rule).map_err(|err| JsValue::from_str(&err))?,
+            generation: 0,
+            births: 0,
+            deaths: 0,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Alive)
+            .count() as u32
+    }
+
+    // Voxels that came to life getting to the current generation.
+    pub fn births(&self) -> u32 {
+        self.births
+    }
+
+    // Voxels that died getting to the current generation.
+    pub fn deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    // The rule in B/S notation, like "B6/S5,6,7".
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        self.rule = Rule3D::parse(rule).map_err(|err| JsValue::from_str(&err))?;
+        Ok(())
+    }
+
+    // Pointer to the voxels, one byte each, x fastest, then y, then z.
+    pub fn cells(&self) -> *const Cell {
+        self.cells.as_ptr()
+    }
+
+    pub fn is_alive(&self, x: u32, y: u32, z: u32) -> Result<bool, JsValue> {
+        Ok(self.cells[self.position(x, y, z)?] == Cell::Alive)
+    }
+
+    pub fn set_alive(&mut self, x: u32, y: u32, z: u32, alive: bool) -> Result<(), JsValue> {
+        let idx = self.position(x, y, z)?;
+        self.cells[idx] = if alive { Cell::Alive } else { Cell::Dead };
+        Ok(())
+    }
+
+    // Brings each voxel to life with chance `density`, from 0 to 1, using
+    // `seed`, a whole number, to get the same soup every time if given.
+    pub fn randomize(&mut self, density: f64, seed: Option<f64>) {
+        let mut random = seed.map_or_else(Random::new, |seed| Random::seeded(seed as u64));
+        for cell in self.cells.iter_mut() {
+            *cell = if random.next_f64() < density {
+                Cell::Alive
+            } else {
+                Cell::Dead
+            };
+        }
+    }
+
+    pub fn tick(&mut self) {
+        let (mut births, mut deaths) = (0, 0);
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let idx = self.get_index(x, y, z);
+                    let cell = self.cells[idx];
+                    let next = self.rule.next(cell, self.live_neighbors(x, y, z));
+                    match (cell, next) {
+                        (Cell::Dead, Cell::Alive) => births += 1,
+                        (Cell::Alive, Cell::Dead) => deaths += 1,
+                        _ => {}
+                    }
+                    self.next[idx] = next;
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation = self.generation.wrapping_add(1);
+        self.births = births;
+        self.deaths = deaths;
+    }
+
+    // One layer of voxels across `axis` at `index`, as 0 for dead and 1 for
+    // alive, row by row. Across Z a row runs along x; across X along y and
+    // across Y along x, with z giving the rows. Throws if `index` is past
+    // the end of the axis.
+    pub fn slice(&self, axis: Axis, index: u32) -> Result<Vec<u8>, JsValue> {
+        let (size, rows, columns) = match axis {
+            Axis::X => (self.width, self.depth, self.height),
+            Axis::Y => (self.height, self.depth, self.width),
+            Axis::Z => (self.depth, self.height, self.width),
+        };
+        if index >= size {
+            return Err(JsValue::from_str(&format!(
+                "slice {} is past the end of the axis",
+                index
+            )));
+        }
+
+        let mut slice = Vec::with_capacity(rows as usize * columns as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let idx = match axis {
+                    Axis::X => self.get_index(index, column, row),
+                    Axis::Y => self.get_index(column, index, row),
+                    Axis::Z => self.get_index(column, row, index),
+                };
+                slice.push(self.cells[idx] as u8);
+            }
+        }
+        Ok(slice)
+    }
+
+    // The surface of the live voxels as a triangle mesh for WebGL or
+    // three.js: x, y, z for each corner of each triangle, with voxel
+    // (x, y, z) filling the unit cube from (x, y, z) to (x + 1, y + 1,
+    // z + 1). Faces between two live voxels are left out, as are faces
+    // looking across an edge at a live voxel on the other side.
+    pub fn surface_mesh(&self) -> Vec<f32> {
+        let mut mesh = Vec::new();
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if self.cells[self.get_index(x, y, z)] == Cell::Dead {
+                        continue;
+                    }
+                    for (facing, corners) in FACE_NEIGHBORS.iter().zip(FACE_CORNERS.iter()) {
+                        if self.cells[self.offset(x, y, z, *facing)] == Cell::Alive {
+                            continue;
+                        }
+                        for &corner in [0, 1, 2, 0, 2, 3].iter() {
+                            let (cx, cy, cz) = corners[corner];
+                            mesh.extend_from_slice(&[x as f32 + cx, y as f32 + cy, z as f32 + cz]);
+                        }
+                    }
+                }
+            }
+        }
+        mesh
+    }
+}
@@ -9,3 +9,70 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+// Milliseconds from some fixed point in the past, for timing things. Uses
+// `performance.now()` where there is one. It is looked up on the global object
+// rather than the window, so this also works in workers, Node and Deno.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    use wasm_bindgen::JsCast;
+
+    let global = js_sys::global();
+    let now = js_sys::Reflect::get(&global, &"performance".into())
+        .ok()
+        .filter(|performance| performance.is_object())
+        .and_then(|performance| {
+            let now = js_sys::Reflect::get(&performance, &"now".into()).ok()?;
+            now.dyn_into::<js_sys::Function>()
+                .ok()?
+                .call0(&performance)
+                .ok()?
+                .as_f64()
+        });
+
+    now.unwrap_or_else(js_sys::Date::now)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+// Writes a message to the console.
+#[allow(dead_code)]
+#[cfg(feature = "browser")]
+pub fn log(message: &wasm_bindgen::JsValue) {
+    web_sys::console::log_1(message);
+}
+
+// Writes an error to the console, e.g. one thrown by a callback that can't be
+// passed back up.
+#[cfg(feature = "browser")]
+pub fn log_error(err: &wasm_bindgen::JsValue) {
+    web_sys::console::error_1(err);
+}
+
+// Without `web-sys`, the console is bound directly. Node and Deno both have
+// one, so headless builds still get their logs.
+#[allow(unused_imports)]
+#[cfg(not(feature = "browser"))]
+pub use self::console::{error as log_error, log};
+
+#[cfg(not(feature = "browser"))]
+mod console {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = console)]
+        pub fn log(message: &JsValue);
+
+        #[wasm_bindgen(js_namespace = console)]
+        pub fn error(err: &JsValue);
+    }
+}
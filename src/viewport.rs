@@ -0,0 +1,143 @@
+use wasm_bindgen::prelude::*;
+
+use crate::Universe;
+
+// The window of the universe currently on screen. The origin is the cell shown
+// in the top-left corner and zoom is the number of screen pixels per cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Viewport {
+    pub(crate) origin_row: u32,
+    pub(crate) origin_col: u32,
+    pub(crate) zoom: f64,
+    pub(crate) screen_width: u32,
+    pub(crate) screen_height: u32,
+}
+
+impl Default for Viewport {
+    // Until told otherwise the viewport shows the universe from the top-left
+    // corner at one pixel per cell, with no limit on the screen size.
+    fn default() -> Self {
+        Viewport {
+            origin_row: 0,
+            origin_col: 0,
+            zoom: 1.0,
+            screen_width: u32::MAX,
+            screen_height: u32::MAX,
+        }
+    }
+}
+
+// Methods not being exported to Javascript
+impl Universe {
+    // Calls `f` with the index of each visible cell, row by row from the
+    // top-left corner of the viewport. The viewport wraps around the edges
+    // like the universe does.
+    pub(crate) fn for_each_visible_cell<F: FnMut(usize)>(&self, mut f: F) {
+        let rows = self.visible_rows();
+        let cols = self.visible_columns();
+
+        for r in 0..rows {
+            let row = (self.viewport.origin_row + r) % self.height;
+            for c in 0..cols {
+                let col = (self.viewport.origin_col + c) % self.width;
+                f(self.get_index(row, col));
+            }
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Moves the viewport so the cell at (origin_row, origin_col) is in the
+    // top-left corner, drawn `zoom` screen pixels wide.
+    pub fn set_viewport(&mut self, origin_row: u32, origin_col: u32, zoom: f64) {
+        self.viewport.origin_row = origin_row % self.height;
+        self.viewport.origin_col = origin_col % self.width;
+        self.viewport.zoom = if zoom > 0.0 { zoom } else { 1.0 };
+    }
+
+    // Sets the size in pixels of the area the viewport is drawn into, usually
+    // the canvas.
+    pub fn set_viewport_size(&mut self, width_px: u32, height_px: u32) {
+        self.viewport.screen_width = width_px;
+        self.viewport.screen_height = height_px;
+    }
+
+    pub fn viewport_origin_row(&self) -> u32 {
+        self.viewport.origin_row
+    }
+
+    pub fn viewport_origin_col(&self) -> u32 {
+        self.viewport.origin_col
+    }
+
+    pub fn viewport_zoom(&self) -> f64 {
+        self.viewport.zoom
+    }
+
+    // Number of cell rows that fit in the viewport, including a partially
+    // visible one at the bottom, but never more than the universe has.
+    pub fn visible_rows(&self) -> u32 {
+        let rows = (self.viewport.screen_height as f64 / self.viewport.zoom).ceil();
+        (rows as u32).min(self.height)
+    }
+
+    // Number of cell columns that fit in the viewport, including a partially
+    // visible one on the right, but never more than the universe has.
+    pub fn visible_columns(&self) -> u32 {
+        let cols = (self.viewport.screen_width as f64 / self.viewport.zoom).ceil();
+        (cols as u32).min(self.width)
+    }
+
+    // Converts a position in screen pixels, relative to the top-left of the
+    // viewport, into the [row, column] of the cell under it.
+    pub fn screen_to_cell(&self, x: f64, y: f64) -> Vec<u32> {
+        let row_offset = (y.max(0.0) / self.viewport.zoom).floor() as u32;
+        let col_offset = (x.max(0.0) / self.viewport.zoom).floor() as u32;
+
+        vec![
+            (self.viewport.origin_row + row_offset % self.height) % self.height,
+            (self.viewport.origin_col + col_offset % self.width) % self.width,
+        ]
+    }
+
+    // Converts a cell into the screen position, in pixels, of its top-left
+    // corner as [x, y]. Cells outside the viewport may land off screen.
+    pub fn cell_to_screen(&self, row: u32, column: u32) -> Vec<f64> {
+        let row_offset = (row + self.height - self.viewport.origin_row) % self.height;
+        let col_offset = (column + self.width - self.viewport.origin_col) % self.width;
+
+        vec![
+            col_offset as f64 * self.viewport.zoom,
+            row_offset as f64 * self.viewport.zoom,
+        ]
+    }
+
+    // The states of the visible cells, visible_columns() wide and
+    // visible_rows() tall.
+    pub fn viewport_cells(&self) -> Vec<u8> {
+        let mut cells = Vec::new();
+        self.for_each_visible_cell(|idx| cells.push(self.cells[idx] as u8));
+        cells
+    }
+
+    // Like `render_rgba`, but only for the visible cells. The result is one
+    // pixel per cell, visible_columns() wide and visible_rows() tall, for the
+    // frontend to scale up by the zoom factor.
+    pub fn render_viewport_rgba(&mut self) -> *const u8 {
+        let len = (self.visible_rows() * self.visible_columns() * 4) as usize;
+        let mut pixels = std::mem::take(&mut self.pixels);
+        pixels.resize(len, 0);
+
+        let mut pixel = pixels.chunks_exact_mut(4);
+        self.for_each_visible_cell(|idx| {
+            if let Some(out) = pixel.next() {
+                out.copy_from_slice(&self.pixel_color(idx));
+            }
+        });
+
+        self.pixels = pixels;
+        self.pixels.as_ptr()
+    }
+}
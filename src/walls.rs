@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+
+use crate::recording::Edit;
+use crate::{Cell, Universe};
+
+// Methods not being exported to Javascript
+impl Universe {
+    pub(crate) fn toggle_wall_at(&mut self, row: u32, column: u32) {
+        self.record(Edit::Wall(row, column));
+        let idx = self.get_index(row, column);
+        let cell = if self.cells[idx] == Cell::Wall {
+            Cell::Dead
+        } else {
+            Cell::Wall
+        };
+        self.set_cell(idx, cell);
+    }
+
+    // Turns every cell in the rectangle between two opposite corners, in
+    // either order, into a wall, or every wall in it back into a dead cell.
+    // Corners past the edges are pulled back inside the universe.
+    pub(crate) fn set_walls(
+        &mut self,
+        row1: u32,
+        column1: u32,
+        row2: u32,
+        column2: u32,
+        wall: bool,
+    ) {
+        let (max_row, max_col) = (self.height - 1, self.width - 1);
+        for row in row1.min(row2).min(max_row)..=row1.max(row2).min(max_row) {
+            for col in column1.min(column2).min(max_col)..=column1.max(column2).min(max_col) {
+                let idx = self.get_index(row, col);
+                if wall {
+                    self.set_cell(idx, Cell::Wall);
+                } else if self.cells[idx] == Cell::Wall {
+                    self.set_cell(idx, Cell::Dead);
+                }
+            }
+        }
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl Universe {
+    // Whether walls count as live neighbors of the cells around them. Off to
+    // begin with, so walls are like dead cells that can never come to life.
+    pub fn walls_count_as_alive(&self) -> bool {
+        self.walls_alive
+    }
+
+    pub fn set_walls_count_as_alive(&mut self, alive: bool) {
+        self.record(Edit::WallsAlive(alive));
+        self.walls_alive = alive;
+    }
+
+    // Number of walls.
+    pub fn wall_count(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Wall)
+            .count() as u32
+    }
+
+    // Builds a wall on the cell, whatever was there, or takes the wall down
+    // if there is one. Throws in strict mode if it's outside the universe,
+    // and otherwise wraps it around.
+    pub fn toggle_wall(&mut self, row: u32, column: u32) -> Result<(), JsValue> {
+        let (row, column) = self
+            .position(row, column)
+            .map_err(|err| JsValue::from_str(&err))?;
+        self.toggle_wall_at(row, column);
+        Ok(())
+    }
+
+    // Walls off the rectangle between two opposite corners, like `fill`.
+    pub fn fill_walls(&mut self, row1: u32, column1: u32, row2: u32, column2: u32) {
+        self.record(Edit::FillWalls(row1, column1, row2, column2));
+        self.set_walls(row1, column1, row2, column2, true);
+    }
+
+    // Takes down the walls in the rectangle between two opposite corners,
+    // leaving dead cells, and leaves every other cell in it alone.
+    pub fn clear_walls(&mut self, row1: u32, column1: u32, row2: u32, column2: u32) {
+        self.record(Edit::ClearWalls(row1, column1, row2, column2));
+        self.set_walls(row1, column1, row2, column2, false);
+    }
+}
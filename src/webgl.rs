@@ -0,0 +1,268 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlCanvasElement, OffscreenCanvas, WebGlProgram, WebGlRenderingContext as Gl, WebGlShader,
+    WebGlTexture, WebGlUniformLocation,
+};
+
+use crate::palette::to_floats;
+use crate::Universe;
+
+// Grid lines are only drawn once a cell is at least this many pixels wide,
+// otherwise they would swamp the cells themselves.
+const MIN_GRID_CELL_PX: f32 = 4.0;
+
+// Draws a single quad covering the whole canvas. Row 0 of the universe ends up
+// at the top of the canvas.
+const VERTEX_SHADER: &str = r#"
+attribute vec2 a_position;
+varying vec2 v_uv;
+
+void main() {
+    v_uv = vec2(a_position.x * 0.5 + 0.5, 0.5 - a_position.y * 0.5);
+    gl_Position = vec4(a_position, 0.0, 1.0);
+}
+"#;
+
+// Looks up the cell under each fragment in the cell texture. The texture holds
+// the raw cell bytes, so a live cell samples as 1/255, a wall as 2/255 and a
+// dead one as 0.
+const FRAGMENT_SHADER: &str = r#"
+precision mediump float;
+
+uniform sampler2D u_cells;
+uniform vec2 u_size;
+uniform vec2 u_resolution;
+uniform float u_min_grid_px;
+uniform vec4 u_alive;
+uniform vec4 u_dead;
+uniform vec4 u_wall;
+uniform vec4 u_grid;
+varying vec2 v_uv;
+
+void main() {
+    float cell = texture2D(u_cells, v_uv).r * 255.0;
+    vec4 color = cell > 1.5 ? u_wall : cell > 0.5 ? u_alive : u_dead;
+
+    vec2 cell_px = u_resolution / u_size;
+    if (min(cell_px.x, cell_px.y) >= u_min_grid_px) {
+        vec2 offset = fract(v_uv * u_size) * cell_px;
+        if (offset.x < 1.0 || offset.y < 1.0) {
+            color = u_grid;
+        }
+    }
+
+    gl_FragColor = color;
+}
+"#;
+
+// Renders a universe onto a canvas with WebGL. The cell buffer is uploaded as a
+// single-channel texture each frame and a fragment shader colors every pixel,
+// so the cost of drawing no longer grows with the number of cells on the JS side.
+#[wasm_bindgen]
+pub struct WebGlRenderer {
+    context: Gl,
+    program: WebGlProgram,
+    texture: WebGlTexture,
+    texture_size: (u32, u32),
+}
+
+// Methods not being exported to Javascript
+impl WebGlRenderer {
+    fn uniform(&self, name: &str) -> Option<WebGlUniformLocation> {
+        self.context.get_uniform_location(&self.program, name)
+    }
+
+    fn upload_cells(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        let gl = &self.context;
+        let size = (universe.width(), universe.height());
+
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&self.texture));
+
+        if size == self.texture_size {
+            gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                size.0 as i32,
+                size.1 as i32,
+                Gl::LUMINANCE,
+                Gl::UNSIGNED_BYTE,
+                Some(universe.cell_bytes()),
+            )?;
+        } else {
+            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::LUMINANCE as i32,
+                size.0 as i32,
+                size.1 as i32,
+                0,
+                Gl::LUMINANCE,
+                Gl::UNSIGNED_BYTE,
+                Some(universe.cell_bytes()),
+            )?;
+            self.texture_size = size;
+        }
+
+        Ok(())
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl WebGlRenderer {
+    // Creates a renderer drawing onto the given canvas.
+    pub fn new(canvas: &HtmlCanvasElement) -> Result<WebGlRenderer, JsValue> {
+        let context = canvas
+            .get_context("webgl")?
+            .ok_or_else(|| JsValue::from_str("WebGL is not supported by this browser"))?;
+        WebGlRenderer::from_context(context.dyn_into::<Gl>()?)
+    }
+
+    // Creates a renderer drawing onto an OffscreenCanvas, for running the
+    // whole tick and render loop inside a Web Worker.
+    pub fn from_offscreen_canvas(canvas: &OffscreenCanvas) -> Result<WebGlRenderer, JsValue> {
+        let context = canvas
+            .get_context("webgl")?
+            .ok_or_else(|| JsValue::from_str("WebGL is not supported in this context"))?;
+        WebGlRenderer::from_context(context.dyn_into::<Gl>()?)
+    }
+}
+
+// Methods not being exported to Javascript
+impl WebGlRenderer {
+    // Sets up the shaders, geometry and cell texture on a fresh context. Only
+    // touches the context itself, so works the same on and off the main thread.
+    fn from_context(context: Gl) -> Result<WebGlRenderer, JsValue> {
+        let vertex_shader = compile_shader(&context, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment_shader = compile_shader(&context, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+        let program = link_program(&context, &vertex_shader, &fragment_shader)?;
+        context.use_program(Some(&program));
+
+        // Two triangles covering clip space.
+        let vertices: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+        let buffer = context
+            .create_buffer()
+            .ok_or_else(|| JsValue::from_str("Unable to create vertex buffer"))?;
+        context.bind_buffer(Gl::ARRAY_BUFFER, Some(&buffer));
+        let vertex_array = js_sys::Float32Array::from(&vertices[..]);
+        context.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &vertex_array,
+            Gl::STATIC_DRAW,
+        );
+
+        let position = context.get_attrib_location(&program, "a_position") as u32;
+        context.vertex_attrib_pointer_with_i32(position, 2, Gl::FLOAT, false, 0, 0);
+        context.enable_vertex_attrib_array(position);
+
+        // Cells are one byte each, so rows are not 4-byte aligned in general.
+        // Universe dimensions are rarely powers of two, which WebGL 1 only
+        // supports without mipmaps and with clamped wrapping.
+        let texture = context
+            .create_texture()
+            .ok_or_else(|| JsValue::from_str("Unable to create cell texture"))?;
+        context.pixel_storei(Gl::UNPACK_ALIGNMENT, 1);
+        context.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        context.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, Gl::NEAREST as i32);
+        context.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, Gl::NEAREST as i32);
+        context.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32);
+        context.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32);
+
+        Ok(WebGlRenderer {
+            context,
+            program,
+            texture,
+            texture_size: (0, 0),
+        })
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl WebGlRenderer {
+    // Uploads the current generation and draws it to the canvas.
+    pub fn render(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        self.upload_cells(universe)?;
+
+        let gl = &self.context;
+        let resolution = (gl.drawing_buffer_width(), gl.drawing_buffer_height());
+        gl.viewport(0, 0, resolution.0, resolution.1);
+
+        gl.uniform1i(self.uniform("u_cells").as_ref(), 0);
+        gl.uniform2f(
+            self.uniform("u_size").as_ref(),
+            universe.width() as f32,
+            universe.height() as f32,
+        );
+        gl.uniform2f(
+            self.uniform("u_resolution").as_ref(),
+            resolution.0 as f32,
+            resolution.1 as f32,
+        );
+        gl.uniform1f(self.uniform("u_min_grid_px").as_ref(), MIN_GRID_CELL_PX);
+        let palette = universe.palette();
+        gl.uniform4fv_with_f32_array(
+            self.uniform("u_alive").as_ref(),
+            &to_floats(palette.alive()),
+        );
+        gl.uniform4fv_with_f32_array(self.uniform("u_dead").as_ref(), &to_floats(palette.dead()));
+        gl.uniform4fv_with_f32_array(self.uniform("u_wall").as_ref(), &to_floats(palette.wall()));
+        gl.uniform4fv_with_f32_array(self.uniform("u_grid").as_ref(), &to_floats(palette.grid()));
+
+        gl.draw_arrays(Gl::TRIANGLE_STRIP, 0, 4);
+
+        Ok(())
+    }
+}
+
+fn compile_shader(context: &Gl, shader_type: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = context
+        .create_shader(shader_type)
+        .ok_or_else(|| JsValue::from_str("Unable to create shader"))?;
+    context.shader_source(&shader, source);
+    context.compile_shader(&shader);
+
+    if context
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(JsValue::from_str(
+            &context
+                .get_shader_info_log(&shader)
+                .unwrap_or_else(|| String::from("Unknown error compiling shader")),
+        ))
+    }
+}
+
+fn link_program(
+    context: &Gl,
+    vertex_shader: &WebGlShader,
+    fragment_shader: &WebGlShader,
+) -> Result<WebGlProgram, JsValue> {
+    let program = context
+        .create_program()
+        .ok_or_else(|| JsValue::from_str("Unable to create shader program"))?;
+    context.attach_shader(&program, vertex_shader);
+    context.attach_shader(&program, fragment_shader);
+    context.link_program(&program);
+
+    if context
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(JsValue::from_str(
+            &context
+                .get_program_info_log(&program)
+                .unwrap_or_else(|| String::from("Unknown error linking shader program")),
+        ))
+    }
+}
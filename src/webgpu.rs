@@ -0,0 +1,452 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    gpu_buffer_usage, gpu_shader_stage, Gpu, GpuBindGroup, GpuBindGroupDescriptor,
+    GpuBindGroupEntry, GpuBindGroupLayout, GpuBindGroupLayoutDescriptor, GpuBindGroupLayoutEntry,
+    GpuBuffer, GpuBufferBindingLayout, GpuBufferBindingType, GpuBufferDescriptor,
+    GpuCanvasConfiguration, GpuCanvasContext, GpuColorTargetState, GpuComputePipeline,
+    GpuComputePipelineDescriptor, GpuDevice, GpuFragmentState, GpuLoadOp, GpuPipelineLayout,
+    GpuPipelineLayoutDescriptor, GpuPrimitiveState, GpuPrimitiveTopology, GpuProgrammableStage,
+    GpuRenderPassColorAttachment, GpuRenderPassDescriptor, GpuRenderPipeline,
+    GpuRenderPipelineDescriptor, GpuShaderModuleDescriptor, GpuStoreOp, GpuTextureFormat,
+    GpuVertexState, HtmlCanvasElement, OffscreenCanvas, Window, WorkerGlobalScope,
+};
+
+use life_engine::Topology;
+
+use crate::palette::to_floats;
+use crate::rule::Rule;
+use crate::{Cell, Neighborhood, Palette, Universe};
+
+// Matches `@workgroup_size` in the step shader.
+const WORKGROUP_SIZE: u32 = 8;
+
+// Binding 0 holds the universe size followed by the rule, as masks with bit
+// `n` set when `n` live neighbors cause a birth or let a cell survive. Binding
+// 1 holds the current generation and binding 2 the generation being written.
+// Cells are stored as one u32 each since WGSL storage buffers can't address
+// individual bytes. Walls stay put and never count as neighbors.
+const STEP_SHADER: &str = r#"
+struct Size {
+    width: u32,
+    height: u32,
+    birth: u32,
+    survival: u32,
+}
+
+@group(0) @binding(0) var<uniform> size: Size;
+@group(0) @binding(1) var<storage, read> cells: array<u32>;
+@group(0) @binding(2) var<storage, read_write> next: array<u32>;
+
+fn cell(row: u32, col: u32) -> u32 {
+    return select(0u, 1u, cells[(row % size.height) * size.width + (col % size.width)] == 1u);
+}
+
+@compute @workgroup_size(8, 8)
+fn step(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= size.width || id.y >= size.height) {
+        return;
+    }
+    let idx = id.y * size.width + id.x;
+    if (cells[idx] == 2u) {
+        next[idx] = 2u;
+        return;
+    }
+
+    // Offsetting by a full width/height instead of -1 keeps the wrapping
+    // arithmetic unsigned.
+    let up = id.y + size.height - 1u;
+    let left = id.x + size.width - 1u;
+    let count = cell(up, left) + cell(up, id.x) + cell(up, id.x + 1u)
+        + cell(id.y, left) + cell(id.y, id.x + 1u)
+        + cell(id.y + 1u, left) + cell(id.y + 1u, id.x) + cell(id.y + 1u, id.x + 1u);
+
+    let rule = select(size.birth, size.survival, cell(id.y, id.x) == 1u);
+    next[idx] = (rule >> count) & 1u;
+}
+"#;
+
+// Draws a quad over the whole canvas and looks up the cell under each fragment
+// straight from the storage buffer the step shader writes to. Binding 3 holds
+// the live, dead and wall colors from the palette.
+const RENDER_SHADER: &str = r#"
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+struct Colors {
+    alive: vec4<f32>,
+    dead: vec4<f32>,
+    wall: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> size: Size;
+@group(0) @binding(1) var<storage, read> cells: array<u32>;
+@group(0) @binding(3) var<uniform> colors: Colors;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 4>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(-1.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x * 0.5 + 0.5, 0.5 - corner.y * 0.5);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let col = min(u32(in.uv.x * f32(size.width)), size.width - 1u);
+    let row = min(u32(in.uv.y * f32(size.height)), size.height - 1u);
+    let cell = cells[row * size.width + col];
+    if (cell == 2u) {
+        return colors.wall;
+    }
+    return select(colors.dead, colors.alive, cell == 1u);
+}
+"#;
+
+// Experimental renderer that also owns the simulation while it runs on the GPU.
+// Cells live in a pair of storage buffers: a compute pass reads one and writes
+// the next generation into the other, and the render pass draws directly from
+// whichever holds the current generation. Once uploaded, ticking and drawing
+// never copy cells back through CPU memory.
+#[wasm_bindgen]
+pub struct WebGpuRenderer {
+    device: GpuDevice,
+    context: GpuCanvasContext,
+    layout: GpuBindGroupLayout,
+    step_pipeline: GpuComputePipeline,
+    render_pipeline: GpuRenderPipeline,
+    size_buffer: GpuBuffer,
+    color_buffer: GpuBuffer,
+    cell_buffers: Vec<GpuBuffer>,
+    // Bind group `i` reads from cell buffer `i` and writes to the other one.
+    bind_groups: Vec<GpuBindGroup>,
+    current: usize,
+    width: u32,
+    height: u32,
+    // Why the uploaded universe can't be ticked here, if it can't.
+    unsupported: Option<String>,
+}
+
+// Methods not being exported to Javascript
+impl WebGpuRenderer {
+    fn create_cell_buffer(&self, size: u32) -> Result<GpuBuffer, JsValue> {
+        let descriptor =
+            GpuBufferDescriptor::new(size, gpu_buffer_usage::STORAGE | gpu_buffer_usage::COPY_DST);
+        self.device.create_buffer(&descriptor)
+    }
+
+    fn create_bind_group(&self, read: &GpuBuffer, write: &GpuBuffer) -> GpuBindGroup {
+        let entries = [
+            GpuBindGroupEntry::new_with_gpu_buffer(0, &self.size_buffer),
+            GpuBindGroupEntry::new_with_gpu_buffer(1, read),
+            GpuBindGroupEntry::new_with_gpu_buffer(2, write),
+            GpuBindGroupEntry::new_with_gpu_buffer(3, &self.color_buffer),
+        ];
+        self.device
+            .create_bind_group(&GpuBindGroupDescriptor::new(&entries, &self.layout))
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl WebGpuRenderer {
+    // Requests a GPU device and sets up the pipelines for drawing onto the
+    // given canvas. Rejects when WebGPU is unavailable.
+    pub async fn new(canvas: HtmlCanvasElement) -> Result<WebGpuRenderer, JsValue> {
+        let context = canvas
+            .get_context("webgpu")?
+            .ok_or_else(|| JsValue::from_str("Unable to get a WebGPU canvas context"))?;
+        WebGpuRenderer::from_context(context.dyn_into::<GpuCanvasContext>()?).await
+    }
+
+    // Same as `new`, but draws onto an OffscreenCanvas so the renderer can
+    // live in a Web Worker.
+    pub async fn from_offscreen_canvas(canvas: OffscreenCanvas) -> Result<WebGpuRenderer, JsValue> {
+        let context = canvas
+            .get_context("webgpu")?
+            .ok_or_else(|| JsValue::from_str("Unable to get a WebGPU canvas context"))?;
+        WebGpuRenderer::from_context(context.dyn_into::<GpuCanvasContext>()?).await
+    }
+}
+
+// Methods not being exported to Javascript
+impl WebGpuRenderer {
+    async fn from_context(context: GpuCanvasContext) -> Result<WebGpuRenderer, JsValue> {
+        let gpu = gpu()?;
+        let adapter = gpu
+            .request_adapter()
+            .await?
+            .into_option()
+            .ok_or_else(|| JsValue::from_str("WebGPU is not supported by this browser"))?;
+        let device = adapter.request_device().await?;
+
+        let format = gpu.get_preferred_canvas_format();
+        context.configure(&GpuCanvasConfiguration::new(&device, format))?;
+
+        let layout = device.create_bind_group_layout(&GpuBindGroupLayoutDescriptor::new(&[
+            buffer_layout_entry(
+                0,
+                gpu_shader_stage::COMPUTE | gpu_shader_stage::FRAGMENT,
+                GpuBufferBindingType::Uniform,
+            ),
+            buffer_layout_entry(
+                1,
+                gpu_shader_stage::COMPUTE | gpu_shader_stage::FRAGMENT,
+                GpuBufferBindingType::ReadOnlyStorage,
+            ),
+            buffer_layout_entry(2, gpu_shader_stage::COMPUTE, GpuBufferBindingType::Storage),
+            buffer_layout_entry(3, gpu_shader_stage::FRAGMENT, GpuBufferBindingType::Uniform),
+        ]))?;
+        let pipeline_layout = device.create_pipeline_layout(&GpuPipelineLayoutDescriptor::new(&[
+            js_sys::JsNullable::wrap(layout.clone()),
+        ]));
+
+        let step_pipeline = create_step_pipeline(&device, &pipeline_layout);
+        let render_pipeline = create_render_pipeline(&device, &pipeline_layout, format)?;
+
+        let size_buffer = device.create_buffer(&GpuBufferDescriptor::new(
+            16,
+            gpu_buffer_usage::UNIFORM | gpu_buffer_usage::COPY_DST,
+        ))?;
+        let color_buffer = device.create_buffer(&GpuBufferDescriptor::new(
+            48,
+            gpu_buffer_usage::UNIFORM | gpu_buffer_usage::COPY_DST,
+        ))?;
+        device.queue().write_buffer_with_u32_and_u8_slice(
+            &color_buffer,
+            0,
+            &palette_bytes(&Palette::default()),
+        )?;
+
+        Ok(WebGpuRenderer {
+            device,
+            context,
+            layout,
+            step_pipeline,
+            render_pipeline,
+            size_buffer,
+            color_buffer,
+            cell_buffers: Vec::new(),
+            bind_groups: Vec::new(),
+            current: 0,
+            width: 0,
+            height: 0,
+            unsupported: None,
+        })
+    }
+}
+
+// Public methods, exported to JavaScript.
+#[wasm_bindgen]
+impl WebGpuRenderer {
+    // Copies the universe onto the GPU, replacing whatever generation was
+    // there, and picks up its rule and palette. Needed after the universe is
+    // edited or resized on the CPU side.
+    pub fn upload(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        let (width, height) = (universe.width(), universe.height());
+        let queue = self.device.queue();
+
+        if (width, height) != (self.width, self.height) || self.cell_buffers.is_empty() {
+            let size = width * height * 4;
+            self.cell_buffers = vec![
+                self.create_cell_buffer(size)?,
+                self.create_cell_buffer(size)?,
+            ];
+            self.bind_groups = vec![
+                self.create_bind_group(&self.cell_buffers[0], &self.cell_buffers[1]),
+                self.create_bind_group(&self.cell_buffers[1], &self.cell_buffers[0]),
+            ];
+            self.current = 0;
+            self.width = width;
+            self.height = height;
+        }
+
+        let (birth, survival) = rule_masks(&universe.rule);
+        let size: Vec<u8> = [width, height, birth, survival]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        queue.write_buffer_with_u32_and_u8_slice(&self.size_buffer, 0, &size)?;
+        self.unsupported = unsupported(universe);
+        self.set_palette(&universe.palette())?;
+
+        let cells = cells_as_words(universe.get_cells());
+        queue.write_buffer_with_u32_and_u8_slice(&self.cell_buffers[self.current], 0, &cells)
+    }
+
+    // Changes the colors used to draw cells.
+    pub fn set_palette(&self, palette: &Palette) -> Result<(), JsValue> {
+        self.device.queue().write_buffer_with_u32_and_u8_slice(
+            &self.color_buffer,
+            0,
+            &palette_bytes(palette),
+        )
+    }
+
+    // Advances the GPU-side universe by the given number of generations.
+    // Throws if the uploaded universe uses settings only the CPU supports:
+    // anything but a wrapping square grid of plain cells.
+    pub fn tick(&mut self, generations: u32) -> Result<(), JsValue> {
+        if let Some(reason) = &self.unsupported {
+            return Err(JsValue::from_str(&format!("Unable to tick on the GPU: {}", reason)));
+        }
+        if self.bind_groups.is_empty() {
+            return Ok(());
+        }
+
+        let encoder = self.device.create_command_encoder();
+        let pass = encoder.begin_compute_pass();
+        pass.set_pipeline(&self.step_pipeline);
+        for _ in 0..generations {
+            pass.set_bind_group(0, Some(&self.bind_groups[self.current]));
+            pass.dispatch_workgroups_with_workgroup_count_y(
+                self.width.div_ceil(WORKGROUP_SIZE),
+                self.height.div_ceil(WORKGROUP_SIZE),
+            );
+            self.current = 1 - self.current;
+        }
+        pass.end();
+
+        self.device.queue().submit(&[encoder.finish()]);
+        Ok(())
+    }
+
+    // Draws the current GPU-side generation to the canvas.
+    pub fn render(&self) -> Result<(), JsValue> {
+        if self.bind_groups.is_empty() {
+            return Ok(());
+        }
+
+        let view = self.context.get_current_texture()?;
+        let attachment =
+            GpuRenderPassColorAttachment::new(GpuLoadOp::Clear, GpuStoreOp::Store, &view);
+        let encoder = self.device.create_command_encoder();
+        let pass = encoder.begin_render_pass(&GpuRenderPassDescriptor::new(&[
+            js_sys::JsNullable::wrap(attachment),
+        ]))?;
+        pass.set_pipeline(&self.render_pipeline);
+        pass.set_bind_group(0, Some(&self.bind_groups[self.current]));
+        pass.draw(4);
+        pass.end();
+
+        self.device.queue().submit(&[encoder.finish()]);
+
+        Ok(())
+    }
+}
+
+// The GPU object from whichever global scope we are running in, so the
+// renderer works both on the main thread and inside a Web Worker.
+fn gpu() -> Result<Gpu, JsValue> {
+    let global = js_sys::global();
+
+    if let Some(window) = global.dyn_ref::<Window>() {
+        Ok(window.navigator().gpu())
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        Ok(worker.navigator().gpu())
+    } else {
+        Err(JsValue::from_str("WebGPU is not available in this context"))
+    }
+}
+
+fn buffer_layout_entry(
+    binding: u32,
+    visibility: u32,
+    binding_type: GpuBufferBindingType,
+) -> GpuBindGroupLayoutEntry {
+    let entry = GpuBindGroupLayoutEntry::new(binding, visibility);
+    let buffer = GpuBufferBindingLayout::new();
+    buffer.set_type(binding_type);
+    entry.set_buffer(&buffer);
+    entry
+}
+
+fn create_step_pipeline(device: &GpuDevice, layout: &GpuPipelineLayout) -> GpuComputePipeline {
+    let module = device.create_shader_module(&GpuShaderModuleDescriptor::new(STEP_SHADER));
+    let stage = GpuProgrammableStage::new(&module);
+    stage.set_entry_point("step");
+    device.create_compute_pipeline(&GpuComputePipelineDescriptor::new(layout, &stage))
+}
+
+fn create_render_pipeline(
+    device: &GpuDevice,
+    layout: &GpuPipelineLayout,
+    format: GpuTextureFormat,
+) -> Result<GpuRenderPipeline, JsValue> {
+    let module = device.create_shader_module(&GpuShaderModuleDescriptor::new(RENDER_SHADER));
+
+    let vertex = GpuVertexState::new(&module);
+    vertex.set_entry_point("vertex");
+
+    let fragment = GpuFragmentState::new(
+        &module,
+        &[js_sys::JsNullable::wrap(GpuColorTargetState::new(format))],
+    );
+    fragment.set_entry_point("fragment");
+
+    let primitive = GpuPrimitiveState::new();
+    primitive.set_topology(GpuPrimitiveTopology::TriangleStrip);
+
+    let descriptor = GpuRenderPipelineDescriptor::new(layout, &vertex);
+    descriptor.set_fragment(&fragment);
+    descriptor.set_primitive(&primitive);
+    device.create_render_pipeline(&descriptor)
+}
+
+// The live, dead and wall colors laid out as the shader's `Colors` struct.
+fn palette_bytes(palette: &Palette) -> Vec<u8> {
+    to_floats(palette.alive())
+        .iter()
+        .chain(to_floats(palette.dead()).iter())
+        .chain(to_floats(palette.wall()).iter())
+        .flat_map(|channel| channel.to_le_bytes())
+        .collect()
+}
+
+// The rule as the step shader's birth and survival masks.
+fn rule_masks(rule: &Rule) -> (u32, u32) {
+    (0..=8u8).fold((0, 0), |(birth, survival), neighbors| {
+        (
+            birth | (rule.is_born(neighbors) as u32) << neighbors,
+            survival | (rule.survives(neighbors) as u32) << neighbors,
+        )
+    })
+}
+
+// What about `universe` the step shader can't reproduce, if anything.
+fn unsupported(universe: &Universe) -> Option<String> {
+    if universe.neighborhood != Neighborhood::Moore {
+        Some(String::from("only square grids are supported"))
+    } else if universe.topology != Topology::TORUS {
+        Some(String::from("only wrapping edges are supported"))
+    } else if universe.walls_alive {
+        Some(String::from("walls can't count as alive"))
+    } else if universe.nutrients.is_some() {
+        Some(String::from("nutrients aren't supported"))
+    } else {
+        None
+    }
+}
+
+// Widens each cell to the little-endian u32 the shaders expect.
+fn cells_as_words(cells: &[Cell]) -> Vec<u8> {
+    cells
+        .iter()
+        .flat_map(|&cell| (cell as u32).to_le_bytes())
+        .collect()
+}
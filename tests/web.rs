@@ -6,26 +6,40 @@
 extern crate wasm_bindgen_test;
 use wasm_bindgen_test::*;
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
 extern crate wasm_game_of_life;
-use wasm_game_of_life::Universe;
+use wasm_game_of_life::{
+    Axis, Battle, BattlePhase, Cell, Edge, FollowMode, LayerStack, MethuselahScore,
+    MethuselahSearch, Neighborhood, NutrientOptions, OverlayOptions, Palette, Pattern, Player,
+    Script, Universe, Universe3D, UniverseBuilder, UniverseSet,
+};
+#[cfg(feature = "browser")]
+use wasm_game_of_life::{register_life_element, PauseCondition, Simulation};
 
+// Without the `browser` feature the tests run headless under Node instead.
+#[cfg(feature = "browser")]
 wasm_bindgen_test_configure!(run_in_browser);
 
 #[cfg(test)]
 pub fn input_spaceship() -> Universe {
     let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]);
+    universe.set_width(6).unwrap();
+    universe.set_height(6).unwrap();
+    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]).unwrap();
     universe
 }
 
 #[cfg(test)]
 pub fn expected_spaceship() -> Universe {
     let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]);
+    universe.set_width(6).unwrap();
+    universe.set_height(6).unwrap();
+    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]).unwrap();
     universe
 }
 
@@ -41,4 +55,1708 @@ pub fn test_tick() {
     // Call `tick` and then see if the cells in the `Universe`s are the same.
     input_universe.tick();
     assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_rgba() {
+    let universe = input_spaceship();
+    let mut pixels = vec![0; 6 * 6 * 4];
+    universe.render_rgba_into(&mut pixels);
+
+    // (0, 0) is dead and (1, 2) is alive in the default black-on-white map.
+    assert_eq!(&pixels[0..4], &[0xff, 0xff, 0xff, 0xff]);
+    let alive = (6 + 2) * 4;
+    assert_eq!(&pixels[alive..alive + 4], &[0x00, 0x00, 0x00, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_changes() {
+    let mut universe = input_spaceship();
+
+    // The first call reports every cell.
+    assert_eq!(universe.render_changes().len(), 6 * 6 * 2);
+    assert!(universe.render_changes().is_empty());
+
+    universe.toggle_cell(0, 0).unwrap();
+    assert_eq!(universe.render_changes(), vec![0, 1]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_viewport_screen_to_cell() {
+    let mut universe = input_spaceship();
+    universe.set_viewport(4, 5, 10.0);
+    universe.set_viewport_size(30, 20);
+
+    assert_eq!(universe.visible_rows(), 2);
+    assert_eq!(universe.visible_columns(), 3);
+
+    // The viewport wraps around the edges of the universe.
+    assert_eq!(universe.screen_to_cell(25.0, 15.0), vec![5, 1]);
+    assert_eq!(universe.cell_to_screen(5, 1), vec![20.0, 10.0]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_with() {
+    let mut universe = Universe::new();
+    universe.set_width(3).unwrap();
+    universe.set_height(2).unwrap();
+    universe.set_cells(&[(0, 1), (1, 2)]).unwrap();
+
+    assert_eq!(universe.render_with('#', '.', ""), ".#.\n..#\n");
+    assert_eq!(universe.render_with('#', '.', " "), ". # .\n. . #\n");
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_braille() {
+    let mut universe = Universe::new();
+    universe.set_width(3).unwrap();
+    universe.set_height(4).unwrap();
+    universe.set_cells(&[(0, 0), (3, 1), (1, 2)]).unwrap();
+
+    assert_eq!(universe.render_braille(), "\u{2881}\u{2802}\n");
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_halfblocks() {
+    let mut universe = Universe::new();
+    universe.set_width(3).unwrap();
+    universe.set_height(3).unwrap();
+    universe.set_cells(&[(0, 0), (1, 0), (0, 1), (1, 2), (2, 2)]).unwrap();
+
+    assert_eq!(universe.render_halfblocks(), "█▀▄\n  ▀\n");
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_html() {
+    let mut universe = Universe::new();
+    universe.set_width(2).unwrap();
+    universe.set_height(1).unwrap();
+    universe.set_cells(&[(0, 1)]).unwrap();
+
+    let html = universe.render_html(4);
+    assert!(html.contains("width:8px;height:4px;background:#ffffffff"));
+    assert!(html.contains("<div style=\"grid-area:1/2;background:#000000ff\"></div>"));
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_scanlines() {
+    let mut universe = Universe::new();
+    universe.set_width(10).unwrap();
+    universe.set_height(2).unwrap();
+    universe.set_cells(&[(0, 0), (0, 9), (1, 3)]).unwrap();
+
+    assert_eq!(universe.render_scanlines(), vec!["8040", "1000"]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_minimap() {
+    let mut universe = Universe::new();
+    universe.set_width(4).unwrap();
+    universe.set_height(2).unwrap();
+    universe.set_cells(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+
+    assert_eq!(universe.minimap(2, 1), vec![255, 63]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_rgba_grid_overlay() {
+    let mut universe = Universe::new();
+    universe.set_width(2).unwrap();
+    universe.set_height(1).unwrap();
+    universe.set_cells(&[(0, 1)]).unwrap();
+    universe.set_overlay_options(OverlayOptions::new(2, true, false));
+
+    assert_eq!(universe.render_width(), 7);
+    assert_eq!(universe.render_height(), 4);
+
+    let mut pixels = vec![0; 7 * 4 * 4];
+    universe.render_rgba_into(&mut pixels);
+    let pixel = |x: usize, y: usize| &pixels[(y * 7 + x) * 4..(y * 7 + x) * 4 + 4];
+
+    assert_eq!(pixel(0, 0), &[0xcc, 0xcc, 0xcc, 0xff]);
+    assert_eq!(pixel(1, 1), &[0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(pixel(4, 2), &[0x00, 0x00, 0x00, 0xff]);
+    assert_eq!(pixel(3, 2), &[0xcc, 0xcc, 0xcc, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_set_palette() {
+    let mut universe = input_spaceship();
+    universe.set_palette(&Palette::dark());
+
+    let mut pixels = vec![0; 6 * 6 * 4];
+    universe.render_rgba_into(&mut pixels);
+    assert_eq!(&pixels[0..4], &[0x11, 0x11, 0x11, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_trails() {
+    let mut universe = Universe::new();
+    universe.set_width(2).unwrap();
+    universe.set_height(1).unwrap();
+    universe.set_trail_length(3);
+    universe.toggle_cell(0, 0).unwrap();
+    universe.toggle_cell(0, 0).unwrap();
+
+    let mut pixels = vec![0; 2 * 4];
+    universe.render_rgba_into(&mut pixels);
+
+    // The cell that just died is a quarter of the way to white, the one that
+    // was never alive is white.
+    assert_eq!(&pixels[0..4], &[0x40, 0x40, 0x40, 0xff]);
+    assert_eq!(&pixels[4..8], &[0xff, 0xff, 0xff, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_pixel_to_hex() {
+    let mut universe = input_spaceship();
+    universe.set_neighborhood(Neighborhood::Hex);
+
+    // Odd rows are shifted half a hexagon to the right.
+    let width = 3f64.sqrt() * 10.0;
+    assert_eq!(universe.pixel_to_hex(width / 2.0, 10.0, 10.0), vec![0, 0]);
+    assert_eq!(universe.pixel_to_hex(width * 2.0, 25.0, 10.0), vec![1, 1]);
+    assert_eq!(universe.pixel_to_hex(-5.0, 10.0, 10.0), Vec::<u32>::new());
+
+    let corners = universe.hex_polygon(0, 0, 10.0);
+    assert_eq!(corners.len(), 12);
+    assert!((corners[1] - 0.0).abs() < 1e-9);
+}
+
+#[wasm_bindgen_test]
+pub fn test_camera_follow() {
+    let mut universe = Universe::new();
+    universe.set_width(10).unwrap();
+    universe.set_height(10).unwrap();
+    universe.set_viewport_size(4, 4);
+    universe.set_cells(&[(9, 9), (0, 0)]).unwrap();
+
+    // The two cells sit either side of the corner, so the box around them
+    // wraps and the viewport centers on the corner itself.
+    universe.set_camera_follow(FollowMode::BoundingBox);
+    assert_eq!(universe.viewport_origin_row(), 8);
+    assert_eq!(universe.viewport_origin_col(), 8);
+}
+
+#[wasm_bindgen_test]
+pub fn test_hover_brush_overlay() {
+    let mut universe = Universe::new();
+    universe.set_width(4).unwrap();
+    universe.set_height(4).unwrap();
+    universe.set_overlay_options(OverlayOptions::new(1, false, false).with_cursor(true));
+    universe.set_brush_size(3);
+    universe.set_hover(0, 0);
+
+    // A 3x3 brush on the corner wraps around to the far edges.
+    assert_eq!(universe.brush_cells().len(), 9 * 2);
+
+    let mut pixels = vec![0; 4 * 4 * 4];
+    universe.render_rgba_into(&mut pixels);
+    let pixel = |row: usize, col: usize| &pixels[(row * 4 + col) * 4..(row * 4 + col) * 4 + 4];
+    assert_ne!(pixel(0, 0), pixel(0, 1));
+    assert_ne!(pixel(0, 1), &[0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(pixel(3, 3), pixel(0, 1));
+    assert_eq!(pixel(2, 2), &[0xff, 0xff, 0xff, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_pending_pattern() {
+    let mut universe = Universe::new();
+    universe.set_width(8).unwrap();
+    universe.set_height(8).unwrap();
+    universe.kill_universe();
+    universe.set_pending_pattern(Pattern::Glider, 4, 4, 1);
+
+    // The preview is drawn half way to the live color without touching the
+    // cells.
+    let mut pixels = vec![0; 8 * 8 * 4];
+    universe.render_rgba_into(&mut pixels);
+    assert_eq!(pixels.chunks(4).filter(|p| p == &[0x80, 0x80, 0x80, 0xff]).count(), 5);
+    assert_eq!(universe.get_cells().iter().filter(|&&c| c == Cell::Alive).count(), 0);
+
+    let preview = universe.pending_cells();
+    universe.commit_pending();
+    assert!(!universe.has_pending());
+    for pair in preview.chunks(2) {
+        let idx = (pair[0] * 8 + pair[1]) as usize;
+        assert_eq!(universe.get_cells()[idx], Cell::Alive);
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_crossfade() {
+    let mut universe = Universe::new();
+    universe.set_width(3).unwrap();
+    universe.set_height(3).unwrap();
+    universe.set_cells(&[(1, 1)]).unwrap();
+    universe.tick();
+    universe.set_crossfade(0.5);
+
+    // The lone cell just died, so it's half way from black to white.
+    let mut pixels = vec![0; 3 * 3 * 4];
+    universe.render_rgba_into(&mut pixels);
+    assert_eq!(&pixels[16..20], &[0x80, 0x80, 0x80, 0xff]);
+    assert_eq!(&pixels[0..4], &[0xff, 0xff, 0xff, 0xff]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_for_each_live_cell() {
+    let mut universe = Universe::new();
+    universe.set_width(4).unwrap();
+    universe.set_height(4).unwrap();
+    universe.set_cells(&[(0, 0), (0, 1), (1, 0), (1, 1)]).unwrap();
+    universe.tick();
+    universe.set_cells(&[(3, 3)]).unwrap();
+
+    let visited = Rc::new(RefCell::new(Vec::new()));
+    let sink = visited.clone();
+    let callback = Closure::wrap(Box::new(move |row: u32, col: u32, age: u32| {
+        sink.borrow_mut().push((row, col, age));
+    }) as Box<dyn FnMut(u32, u32, u32)>);
+
+    universe
+        .for_each_live_cell(callback.as_ref().unchecked_ref())
+        .unwrap();
+    assert_eq!(
+        *visited.borrow(),
+        vec![(0, 0, 1), (0, 1, 1), (1, 0, 1), (1, 1, 1), (3, 3, 0)]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_render_rgba_scaled() {
+    let mut universe = Universe::new();
+    universe.set_width(2).unwrap();
+    universe.set_height(1).unwrap();
+    universe.set_cells(&[(0, 1)]).unwrap();
+
+    let mut pixels = vec![0; 4 * 2 * 4];
+    universe.render_rgba_scaled_into(2, &mut pixels).unwrap();
+    let white = [0xff, 0xff, 0xff, 0xff];
+    let black = [0x00, 0x00, 0x00, 0xff];
+    let row = [white, white, black, black].concat();
+    assert_eq!(pixels, [row.clone(), row].concat());
+    assert!(universe.render_rgba_scaled(u32::MAX).is_err());
+    assert!(universe.render_rgba_scaled_into(1 << 16, &mut pixels).is_err());
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_step() {
+    let mut universe = input_spaceship();
+    universe.tick();
+    let mut simulation = Simulation::new(input_spaceship());
+
+    assert!(!simulation.is_running());
+    simulation.step_n(1).unwrap();
+
+    let cells = unsafe {
+        std::slice::from_raw_parts(
+            simulation.cells(),
+            (simulation.width() * simulation.height()) as usize,
+        )
+    };
+    assert_eq!(cells, universe.get_cells());
+
+    // Generations already reached can't be run to.
+    assert!(simulation.run_to_generation(1, None).is_ok());
+    assert!(simulation.run_to_generation(0, None).is_err());
+    assert!(!simulation.is_running());
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_frame_pacing() {
+    let mut simulation = Simulation::new(Universe::new());
+    assert_eq!(simulation.speed(), 60.0);
+
+    simulation.set_frames_per_tick(30);
+    simulation.set_ticks_per_frame(10);
+    assert_eq!(simulation.ticks_per_frame(), 10);
+    assert_eq!(simulation.frames_per_tick(), 30);
+    assert!(simulation.speed().is_nan());
+
+    simulation.set_speed(5.0);
+    assert_eq!(simulation.speed(), 5.0);
+    assert_eq!(simulation.ticks_per_frame(), 1);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_frame_budget() {
+    let mut simulation = Simulation::new(Universe::new());
+    assert!(simulation.tick_time().is_nan());
+
+    simulation.set_frame_budget(12.0);
+    assert_eq!(simulation.frame_budget(), 12.0);
+    assert!(simulation.speed().is_nan());
+}
+
+#[wasm_bindgen_test]
+pub fn test_generation_counter() {
+    let mut universe = input_spaceship();
+    assert_eq!(universe.generation(), 0);
+    universe.tick();
+    universe.tick();
+    assert_eq!(universe.generation(), 2);
+
+    universe.set_width(4).unwrap();
+    assert_eq!(universe.generation(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_advance_to() {
+    let mut ticked = input_spaceship();
+    for _ in 0..4 {
+        ticked.tick();
+    }
+
+    let mut advanced = input_spaceship();
+    assert!(advanced.advance_to(4) >= 0.0);
+    assert_eq!(advanced.generation(), 4);
+    assert_eq!(advanced.get_cells(), ticked.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_step_back() {
+    let mut universe = input_spaceship();
+    universe.set_history_limit(2);
+    for _ in 0..3 {
+        universe.tick();
+    }
+    assert_eq!(universe.history_len(), 2);
+
+    let mut expected = input_spaceship();
+    expected.tick();
+
+    assert!(universe.step_back());
+    assert!(universe.step_back());
+    assert!(!universe.step_back());
+    assert_eq!(universe.generation(), 1);
+    assert_eq!(universe.get_cells(), expected.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_recording_replay() {
+    let mut universe = Universe::new();
+    universe.set_width(16).unwrap();
+    universe.set_height(16).unwrap();
+    universe.start_recording();
+    universe.create_glider(2, 2).unwrap();
+    universe.tick();
+    universe.tick();
+    universe.toggle_cell(10, 10).unwrap();
+    universe.set_pending_pattern(Pattern::Glider, 8, 3, 2);
+    universe.commit_pending();
+    universe.tick();
+
+    let replayed = Universe::replay(&universe.recording()).unwrap();
+    assert_eq!(replayed.generation(), 3);
+    assert_eq!(replayed.get_cells(), universe.get_cells());
+
+    // Stepping back and switching neighborhoods are recorded too.
+    universe.set_history_limit(4);
+    universe.tick();
+    universe.tick();
+    assert!(universe.step_back());
+    universe.set_neighborhood(Neighborhood::Hex);
+    universe.tick();
+
+    let replayed = Universe::replay(&universe.recording()).unwrap();
+    assert_eq!(replayed.generation(), 5);
+    assert_eq!(replayed.neighborhood(), Neighborhood::Hex);
+    assert_eq!(replayed.get_cells(), universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_on_tick() {
+    let mut universe = input_spaceship();
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let sink = seen.clone();
+    let callback = Closure::wrap(Box::new(move |generation: u32, stats: JsValue| {
+        let population = js_sys::Reflect::get(&stats, &"population".into()).unwrap();
+        sink.borrow_mut()
+            .push((generation, population.as_f64().unwrap() as u32));
+    }) as Box<dyn FnMut(u32, JsValue)>);
+    universe.set_on_tick(Some(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ));
+
+    universe.tick();
+    universe.advance_to(3);
+    assert_eq!(*seen.borrow(), vec![(1, 5), (2, 5), (3, 5)]);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_on_tick() {
+    let mut universe = input_spaceship();
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let sink = seen.clone();
+    let callback = Closure::wrap(Box::new(move |generation: u32| {
+        sink.borrow_mut().push(generation);
+    }) as Box<dyn FnMut(u32)>);
+    universe.set_on_tick(Some(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ));
+
+    // Held back until the generations are done, then called for each.
+    let mut simulation = Simulation::new(universe);
+    simulation.step_n(3).unwrap();
+    assert_eq!(*seen.borrow(), vec![1, 2, 3]);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_on_stable() {
+    let mut universe = Universe::new();
+    universe.set_width(5).unwrap();
+    universe.set_height(5).unwrap();
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+    let mut simulation = Simulation::new(universe);
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let sink = seen.clone();
+    let callback = Closure::wrap(Box::new(move |generation: u32, period: u32| {
+        sink.borrow_mut().push((generation, period));
+    }) as Box<dyn FnMut(u32, u32)>);
+    simulation.set_on_stable(Some(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ));
+
+    // A blinker repeats every other generation.
+    simulation.step_n(3).unwrap();
+    assert_eq!(*seen.borrow(), vec![(3, 2)]);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_pause_condition() {
+    let mut universe = Universe::new();
+    universe.set_width(8).unwrap();
+    universe.set_height(8).unwrap();
+    universe.create_glider(0, 0).unwrap();
+    let mut simulation = Simulation::new(universe);
+
+    // The glider's leading edge reaches row 4 after a few generations.
+    simulation.add_pause_condition(&PauseCondition::cell_alive(4, 3));
+    simulation.add_pause_condition(&PauseCondition::generation(50));
+    simulation.step_n(100).unwrap();
+    assert_eq!(simulation.generation(), 7);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_timing_stats_start_empty() {
+    let simulation = Simulation::new(Universe::new());
+    let stats = simulation.timing_stats();
+    assert!(stats.tick_mean().is_nan());
+    assert!(stats.render_p95().is_nan());
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_interpolation() {
+    let mut simulation = Simulation::new(Universe::new());
+    assert_eq!(simulation.interpolation(), 0.0);
+
+    simulation.set_ticks_per_frame(2);
+    assert_eq!(simulation.interpolation(), 1.0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_step_back_once() {
+    let mut universe = input_spaceship();
+    assert!(!universe.step_back_once());
+
+    universe.tick();
+    assert!(universe.step_back_once());
+    assert!(!universe.step_back_once());
+    assert_eq!(universe.generation(), 0);
+    assert_eq!(universe.get_cells(), input_spaceship().get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_execute() {
+    let mut universe = Universe::new();
+    universe.set_width(8).unwrap();
+    universe.set_height(8).unwrap();
+    universe.kill_universe();
+
+    assert_eq!(universe.execute("fill 1 1 2 2"), "ok");
+    assert_eq!(universe.execute("population"), "4");
+    assert_eq!(universe.execute("tick 3"), "generation 3");
+    assert_eq!(universe.execute("population"), "4");
+    assert_eq!(universe.execute("rule B36/S23"), "B36/S23");
+    assert_eq!(universe.execute("place glider 5 5 nw"), "ok");
+    assert_eq!(universe.execute("population"), "9");
+    assert!(universe.execute("warp 9").starts_with("error:"));
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "scripting")]
+pub fn test_run_script() {
+    let mut universe = Universe::new();
+    universe.set_width(8).unwrap();
+    universe.set_height(8).unwrap();
+
+    let script = r#"
+        clear();
+        // A blinker, a cell at a time.
+        let total = 0;
+        for row in 0..3 {
+            toggle(row, 1);
+            total += population();
+        }
+        print("total", total);
+        tick();
+        print(generation(), rule(), alive(1, 0) && !alive(0, 1));
+        let n = 0;
+        while true {
+            n = n + 1;
+            if n % 2 == 0 { continue; }
+            if n > 4 { break; }
+        }
+        print("n is " + n / 2);
+    "#;
+    assert_eq!(universe.run_script(script), "total 6\n1 B3/S23 true\nn is 2.5");
+    assert_eq!(universe.generation(), 1);
+
+    assert_eq!(
+        universe.run_script("set_rule(\"B36/S23\");\nsoup(1);\nprint(population());"),
+        "64"
+    );
+    assert_eq!(universe.rule(), "B36/S23");
+
+    // Output so far is kept when a script fails.
+    assert_eq!(
+        universe.run_script("print(1);\nwarp(9);"),
+        "1\nerror: line 2: there's no function `warp`"
+    );
+    assert_eq!(
+        universe.run_script("tick(1.5);"),
+        "error: line 1: expected a whole number, not 1.5"
+    );
+    assert_eq!(
+        universe.run_script("break;"),
+        "error: line 1: `break` outside a loop"
+    );
+    assert_eq!(
+        universe.run_script("x = 1;"),
+        "error: line 1: `x` needs declaring with `let` first"
+    );
+    assert_eq!(universe.generation(), 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_from_rle() {
+    let glider =
+        Universe::from_rle("#N Glider\nx = 3, y = 3, rule = B36/S23\nbob$2bo$3o!").unwrap();
+    assert_eq!(glider.width(), 3);
+    assert_eq!(glider.height(), 3);
+    assert_eq!(glider.rule(), "B36/S23");
+    assert_eq!(glider.render(), " ◻ ◼ ◻\n ◻ ◻ ◼\n ◼ ◼ ◼\n");
+
+    let blinker = Universe::from_rle("x = 5, y = 1, rule = 23/3\nb3o!").unwrap();
+    assert_eq!(blinker.rule(), "B3/S23");
+    assert_eq!(blinker.population(), 3);
+
+    assert!(Universe::from_rle("x = 2, y = 2\n3o!").is_err());
+    // Sizes and runs too big to hold are refused rather than overflowing.
+    assert!(Universe::from_rle("x = 70000, y = 70000\no!").is_err());
+    assert!(Universe::from_rle("x = 2, y = 2\n99999999999o!").is_err());
+    assert!(Universe::from_rle("x = 2, y = 2\n4294967295b4294967295$o!").is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_get_state() {
+    let mut universe = input_spaceship();
+    universe.tick();
+
+    let state = universe.get_state().unwrap();
+    let field = |name: &str| js_sys::Reflect::get(&state, &name.into()).unwrap();
+    assert_eq!(field("width").as_f64(), Some(6.0));
+    assert_eq!(field("height").as_f64(), Some(6.0));
+    assert_eq!(field("generation").as_f64(), Some(1.0));
+    assert_eq!(field("rule").as_string().as_deref(), Some("B3/S23"));
+
+    let cells = field("cells").dyn_into::<js_sys::Uint8Array>().unwrap();
+    let expected: Vec<u8> = universe
+        .get_cells()
+        .iter()
+        .map(|&cell| cell as u8)
+        .collect();
+    assert_eq!(cells.to_vec(), expected);
+}
+
+#[wasm_bindgen_test]
+pub fn test_transferable_round_trip() {
+    let mut universe = input_spaceship();
+    universe.set_rule("B36/S23").unwrap();
+    universe.set_neighborhood(Neighborhood::Hex);
+    universe.tick();
+    universe.tick();
+
+    let buffer = universe.export_transferable();
+    let copy = Universe::import_transferable(&buffer).unwrap();
+    assert_eq!(copy.get_cells(), universe.get_cells());
+    assert_eq!(copy.generation(), 2);
+    assert_eq!(copy.rule(), "B36/S23");
+    assert_eq!(copy.neighborhood(), Neighborhood::Hex);
+
+    let truncated = buffer.slice_with_end(0, buffer.byte_length() - 1);
+    assert!(Universe::import_transferable(&truncated).is_err());
+    assert!(Universe::import_transferable(&js_sys::ArrayBuffer::new(8)).is_err());
+
+    // A width and height whose product overflows are turned away.
+    let huge = buffer.slice(0);
+    let bytes = js_sys::Uint8Array::new(&huge);
+    bytes.subarray(5, 13).fill(0xff, 0, 8);
+    assert!(Universe::import_transferable(&huge).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_shared_buffer() {
+    assert_eq!(Universe::shared_buffer_len(6, 6).unwrap(), 16 + 36);
+    assert!(Universe::shared_buffer_len(1 << 16, 1 << 16).is_err());
+
+    // SharedArrayBuffer needs a cross-origin isolated page in browsers, but
+    // Atomics work on a plain ArrayBuffer too, so the header and sequence
+    // handling is checked over one of those everywhere, and over a real
+    // SharedArrayBuffer where there is one.
+    let global = js_sys::global();
+    let mut kinds = vec![false];
+    if js_sys::Reflect::has(&global, &"SharedArrayBuffer".into()).unwrap() {
+        kinds.push(true);
+    }
+    for shared in kinds {
+        let new_buffer = |len: u32| -> js_sys::SharedArrayBuffer {
+            if shared {
+                js_sys::SharedArrayBuffer::new(len)
+            } else {
+                js_sys::ArrayBuffer::new(len).unchecked_into()
+            }
+        };
+
+        let buffer = new_buffer(Universe::shared_buffer_len(6, 6).unwrap());
+        let mut universe = input_spaceship();
+        universe.attach_shared_buffer(&buffer).unwrap();
+        universe.tick();
+
+        let header = js_sys::Int32Array::new(&buffer);
+        assert_eq!(header.get_index(0) % 2, 0);
+        assert_eq!(header.get_index(1), 1);
+
+        let mut reader = Universe::new();
+        assert!(reader.load_shared_buffer(&buffer).unwrap());
+        assert_eq!(reader.width(), 6);
+        assert_eq!(reader.generation(), 1);
+        assert_eq!(reader.get_cells(), universe.get_cells());
+
+        // A copy in progress is skipped.
+        header.set_index(0, header.get_index(0) + 1);
+        assert!(!reader.load_shared_buffer(&buffer).unwrap());
+
+        let small = new_buffer(Universe::shared_buffer_len(2, 2).unwrap());
+        assert!(universe.attach_shared_buffer(&small).is_err());
+    }
+}
+
+#[wasm_bindgen_test]
+pub fn test_sync_messages() {
+    let mut host = input_spaceship();
+    host.set_rule("B36/S23").unwrap();
+    let mut guest = Universe::new();
+
+    guest.apply_sync(&host.sync_diff()).unwrap();
+    assert_eq!(guest.width(), 6);
+    assert_eq!(guest.rule(), "B36/S23");
+    assert_eq!(guest.get_cells(), host.get_cells());
+
+    host.tick();
+    host.toggle_cell(0, 0).unwrap();
+    let diff = host.sync_diff();
+    assert!(diff.len() < 16);
+    guest.apply_sync(&diff).unwrap();
+    assert_eq!(guest.generation(), 1);
+    assert_eq!(guest.get_cells(), host.get_cells());
+
+    // Edits on the guest go back the other way.
+    guest.toggle_cell(5, 5).unwrap();
+    host.apply_sync(&guest.sync_diff()).unwrap();
+    assert_eq!(host.get_cells(), guest.get_cells());
+
+    assert!(guest.apply_sync(&[7]).is_err());
+    assert!(Universe::new().apply_sync(&diff).is_err());
+
+    // A snapshot for a 4096 by 8192 universe is too big to take in.
+    let mut huge = vec![0, 0, 0x80, 0x20, 0x80, 0x40, 6];
+    huge.extend_from_slice(b"B3/S23");
+    huge.push(0);
+    assert!(guest.apply_sync(&huge).is_err());
+    assert_eq!(guest.width(), 6);
+}
+
+#[wasm_bindgen_test]
+pub fn test_merge_remote_ops() {
+    let mut alice = input_spaceship();
+    alice.set_replica_id(1);
+    let mut bob = input_spaceship();
+    bob.kill_universe();
+    bob.set_replica_id(2);
+
+    assert_eq!(bob.merge_remote_ops(&alice.local_ops()).unwrap(), 5);
+    assert_eq!(bob.get_cells(), alice.get_cells());
+    assert!(alice.local_ops().is_empty());
+
+    // Alice ticks while Bob edits a cell the tick also changes. Whichever
+    // order the ops arrive in, both end up the same.
+    alice.tick();
+    bob.toggle_cell(2, 1).unwrap();
+    let from_alice = alice.local_ops();
+    let from_bob = bob.local_ops();
+    alice.merge_remote_ops(&from_bob).unwrap();
+    bob.merge_remote_ops(&from_alice).unwrap();
+    assert_eq!(alice.get_cells(), bob.get_cells());
+
+    assert!(bob.merge_remote_ops(&[1, 1]).is_err());
+
+    // Ops from a peer with the same id can't be told apart from local ones.
+    alice.toggle_cell(0, 0).unwrap();
+    let from_alice = alice.local_ops();
+    bob.set_replica_id(1);
+    let before = bob.get_cells().to_vec();
+    assert!(bob.merge_remote_ops(&from_alice).is_err());
+    assert_eq!(bob.get_cells(), &before[..]);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_quick_save_load() {
+    let global = js_sys::global();
+    if !js_sys::Reflect::has(&global, &"localStorage".into()).unwrap() {
+        return;
+    }
+
+    let mut universe = input_spaceship();
+    universe.set_rule("B36/S23").unwrap();
+    universe.tick();
+    universe.quick_save(7).unwrap();
+
+    let mut loaded = Universe::new();
+    assert!(loaded.quick_load(7).unwrap());
+    assert_eq!(loaded.width(), 6);
+    assert_eq!(loaded.generation(), 1);
+    assert_eq!(loaded.rule(), "B36/S23");
+    assert_eq!(loaded.get_cells(), universe.get_cells());
+
+    assert!(!loaded.quick_load(200).unwrap());
+}
+
+#[wasm_bindgen_test]
+pub fn test_url_fragment() {
+    let mut universe = input_spaceship();
+    universe.set_rule("B36/S23").unwrap();
+    universe.tick();
+    let fragment = universe.to_url_fragment();
+    assert!(fragment.starts_with("#size=6x6&rule=B36/S23&cells="));
+
+    let mut loaded = Universe::new();
+    loaded.apply_url_fragment(&fragment).unwrap();
+    assert_eq!(loaded.width(), 6);
+    assert_eq!(loaded.height(), 6);
+    assert_eq!(loaded.generation(), 0);
+    assert_eq!(loaded.rule(), "B36/S23");
+    assert_eq!(loaded.get_cells(), universe.get_cells());
+
+    // Unknown fields are skipped; a broken fragment changes nothing.
+    loaded
+        .apply_url_fragment(&format!("{}&speed=30", &fragment[1..]))
+        .unwrap();
+    assert_eq!(loaded.get_cells(), universe.get_cells());
+    assert!(loaded.apply_url_fragment("#size=6x6&cells=!").is_err());
+    assert!(loaded.apply_url_fragment("#rule=B3/S23").is_err());
+    assert!(loaded.apply_url_fragment("#size=65536x65536").is_err());
+    assert_eq!(loaded.get_cells(), universe.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_with_config() {
+    let config = |json: &str| js_sys::JSON::parse(json).unwrap().unchecked_into();
+
+    let universe = Universe::with_config(config(
+        r#"{ "width": 8, "height": 6, "initialPattern": "x = 3, y = 1, rule = B36/S23\n3o!" }"#,
+    ))
+    .unwrap();
+    assert_eq!(universe.width(), 8);
+    assert_eq!(universe.height(), 6);
+    assert_eq!(universe.rule(), "B36/S23");
+    let alive: Vec<usize> = universe
+        .get_cells()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &cell)| cell == Cell::Alive)
+        .map(|(idx, _)| idx)
+        .collect();
+    assert_eq!(alive, vec![2 * 8 + 2, 2 * 8 + 3, 2 * 8 + 4]);
+
+    // The same seed fills the universe the same way.
+    let seeded = r#"{ "width": 16, "height": 16, "seed": 42, "density": 0.5, "boundary": "wrap" }"#;
+    let first = Universe::with_config(config(seeded)).unwrap();
+    let second = Universe::with_config(config(seeded)).unwrap();
+    assert_eq!(first.get_cells(), second.get_cells());
+    assert!(first.get_cells().contains(&Cell::Alive));
+
+    assert!(Universe::with_config(config(r#"{ "density": 2 }"#)).is_err());
+    assert!(Universe::with_config(config(r#"{ "boundary": "wall" }"#)).is_err());
+    assert!(Universe::with_config(config(r#"{ "width": "wide" }"#)).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_universe_builder() {
+    let builder = UniverseBuilder::new()
+        .width(8)
+        .height(6)
+        .rule(String::from("B36/S23"))
+        .pattern(String::from("x = 3, y = 1\n3o!"));
+    let universe = builder.build().unwrap();
+    assert_eq!(universe.width(), 8);
+    assert_eq!(universe.height(), 6);
+    assert_eq!(universe.rule(), "B36/S23");
+    assert_eq!(universe.get_cells()[2 * 8 + 2..2 * 8 + 5], [Cell::Alive; 3]);
+
+    let seeded = UniverseBuilder::new().seed(7.0).density(0.3);
+    assert_eq!(
+        seeded.build().unwrap().get_cells(),
+        seeded.build().unwrap().get_cells()
+    );
+
+    assert!(UniverseBuilder::new().width(0).build().is_err());
+    assert!(UniverseBuilder::new()
+        .width(1 << 16)
+        .height(1 << 16)
+        .build()
+        .is_err());
+    assert!(UniverseBuilder::new()
+        .rule(String::from("B9"))
+        .build()
+        .is_err());
+    assert!(UniverseBuilder::new()
+        .width(2)
+        .pattern(String::from("x = 3, y = 1\n3o!"))
+        .build()
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_live_cells_iter() {
+    let mut universe = input_spaceship();
+    let iter = universe.live_cells_iter();
+    universe.tick();
+
+    // Spreading goes through `[Symbol.iterator]`, like `for...of` does.
+    let cells = js_sys::Array::from(&iter);
+    let positions: Vec<(u32, u32)> = cells
+        .iter()
+        .map(|cell| {
+            let get = |key: &str| {
+                js_sys::Reflect::get(&cell, &key.into())
+                    .unwrap()
+                    .as_f64()
+                    .unwrap() as u32
+            };
+            (get("row"), get("col"))
+        })
+        .collect();
+    assert_eq!(positions, vec![(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)]);
+    assert_eq!(js_sys::Array::from(&iter).length(), 0);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_dom_events() {
+    let target = web_sys::EventTarget::new().unwrap();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let periods = Rc::new(RefCell::new(Vec::new()));
+    for name in ["tick", "extinct", "stable", "patternPlaced"] {
+        let seen = Rc::clone(&seen);
+        let periods = Rc::clone(&periods);
+        let listener = Closure::wrap(Box::new(move |event: web_sys::CustomEvent| {
+            if event.type_() == "stable" {
+                let period = js_sys::Reflect::get(&event.detail(), &"period".into()).unwrap();
+                periods.borrow_mut().push(period.as_f64().unwrap() as u32);
+            }
+            seen.borrow_mut().push(event.type_());
+        }) as Box<dyn FnMut(web_sys::CustomEvent)>);
+        target
+            .add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+            .unwrap();
+        listener.forget();
+    }
+
+    let mut universe = input_spaceship();
+    universe.bind_event_target(target);
+    universe.kill_universe();
+    universe.create_glider(0, 0).unwrap();
+    universe.tick();
+    universe.kill_universe();
+    universe.toggle_cell(1, 1).unwrap();
+    universe.tick();
+    assert_eq!(
+        *seen.borrow(),
+        vec!["patternPlaced", "tick", "tick", "extinct"]
+    );
+
+    // A block stands still, so "stable" comes once, when the second tick
+    // repeats the first.
+    universe.set_cells(&[(1, 1), (1, 2), (2, 1), (2, 2)]).unwrap();
+    universe.tick();
+    universe.tick();
+    universe.tick();
+    assert_eq!(seen.borrow()[4..], ["tick", "tick", "stable", "tick"]);
+    assert_eq!(*periods.borrow(), vec![1]);
+
+    // A blinker repeats every other generation.
+    universe.kill_universe();
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+    for _ in 0..4 {
+        universe.tick();
+    }
+    assert_eq!(seen.borrow()[8..], ["tick", "tick", "tick", "stable", "tick"]);
+    assert_eq!(*periods.borrow(), vec![1, 2]);
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_simulation_dom_events() {
+    let target = web_sys::EventTarget::new().unwrap();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&seen);
+    let listener = Closure::wrap(Box::new(move |event: web_sys::CustomEvent| {
+        let generation = js_sys::Reflect::get(&event.detail(), &"generation".into()).unwrap();
+        sink.borrow_mut().push(generation.as_f64().unwrap() as u32);
+    }) as Box<dyn FnMut(web_sys::CustomEvent)>);
+    target
+        .add_event_listener_with_callback("tick", listener.as_ref().unchecked_ref())
+        .unwrap();
+
+    let mut universe = input_spaceship();
+    universe.bind_event_target(target);
+    let mut simulation = Simulation::new(universe);
+    simulation.step_n(3).unwrap();
+    assert_eq!(*seen.borrow(), vec![1, 2, 3]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_sonify_frame() {
+    let mut universe = input_spaceship();
+    // A lone cell well away from the glider.
+    universe.toggle_cell(5, 0).unwrap();
+    let frame = universe.sonify_frame();
+    assert_eq!(frame.len(), 5 + 6);
+    assert_eq!(frame[0], 6.0 / 36.0);
+    assert_eq!(frame[3], 2.0);
+    assert_eq!(frame[4], (96.0f32 - 6.0 * 5.0f32.log2()).round());
+    assert_eq!(
+        frame[5..],
+        [0.0, 1.0 / 6.0, 1.0 / 6.0, 3.0 / 6.0, 0.0, 1.0 / 6.0]
+    );
+
+    universe.kill_universe();
+    let frame = universe.sonify_frame();
+    assert_eq!(frame[3], 0.0);
+    assert_eq!(frame[4], 0.0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_rle_copy_paste() {
+    let universe = input_spaceship();
+    assert_eq!(
+        universe.to_rle(),
+        "x = 6, y = 6, rule = B3/S23\n$2bo$3bo$b3o!\n"
+    );
+
+    let mut pasted = input_spaceship();
+    pasted.kill_universe();
+    pasted.start_recording();
+    let glider = Universe::from_rle("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+    pasted.paste(&glider, 1, 1);
+    assert_eq!(pasted.get_cells(), universe.get_cells());
+
+    // Pastes wrap around the edges and are recorded.
+    pasted.paste(&glider, 5, 5);
+    assert_eq!(pasted.population(), 10);
+    let replayed = Universe::replay(&pasted.recording()).unwrap();
+    assert_eq!(replayed.get_cells(), pasted.get_cells());
+}
+
+#[wasm_bindgen_test]
+pub fn test_pattern_formats() {
+    let glider = input_spaceship();
+    let formats = [
+        "x = 3, y = 3\nbo$2bo$3o!",
+        "!Name: Glider\n.O.\n..O\nOOO\n",
+        "#Life 1.05\n#D A glider\n#N\n#P -1 -1\n.*.\n..*\n***\n",
+        "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n",
+    ];
+    for text in formats.iter() {
+        let pattern = Universe::from_pattern(text).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 3), "{}", text);
+
+        let mut pasted = input_spaceship();
+        pasted.kill_universe();
+        pasted.paste(&pattern, 1, 1);
+        assert_eq!(pasted.get_cells(), glider.get_cells(), "{}", text);
+    }
+
+    // Plaintext keeps its dead edges; Life 1.05 can change the rule.
+    let padded = Universe::from_pattern("!\n....\n.O..\n").unwrap();
+    assert_eq!((padded.width(), padded.height()), (4, 2));
+    let highlife = Universe::from_pattern("#Life 1.05\n#R 23/36\n#P 0 0\n*\n").unwrap();
+    assert_eq!(highlife.rule(), "B36/S23");
+
+    assert!(Universe::from_pattern("#Life 1.06\n1 x\n").is_err());
+    assert!(Universe::from_pattern(".O.\n.X.\n").is_err());
+    assert!(Universe::from_pattern("#Life 1.06\n0 0\n100000 100000\n").is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_strict_coordinates() {
+    // Lenient by default: coordinates past the edges wrap around.
+    let mut universe = input_spaceship();
+    universe.kill_universe();
+    universe.toggle_cell(7, 8).unwrap();
+    assert_eq!(universe.get_cells()[6 + 2], Cell::Alive);
+    assert_eq!(universe.population(), 1);
+    // Even patterns bigger than the universe.
+    universe.create_pulsar(3, 3).unwrap();
+    universe.create_glider(100, 100).unwrap();
+
+    let mut strict = input_spaceship();
+    strict.set_strict(true);
+    let before = strict.get_cells().to_vec();
+    assert!(strict.toggle_cell(6, 0).is_err());
+    assert!(strict.create_glider(0, 6).is_err());
+    assert!(strict.create_pulsar(10, 10).is_err());
+    assert!(strict.set_cells(&[(1, 1), (0, 6)]).is_err());
+    assert_eq!(strict.get_cells(), &before[..]);
+    strict.toggle_cell(5, 5).unwrap();
+    assert_eq!(strict.population(), 6);
+
+    // A universe always has at least one cell.
+    assert!(strict.set_width(0).is_err());
+    assert!(strict.set_height(0).is_err());
+    assert_eq!((strict.width(), strict.height()), (6, 6));
+    assert_eq!(strict.population(), 6);
+}
+
+#[wasm_bindgen_test]
+pub fn test_buffer_info() {
+    let universe = input_spaceship();
+    let info = universe.buffer_info().unwrap();
+    let get = |path: &[&str]| {
+        path.iter()
+            .fold(info.clone(), |value, key| {
+                js_sys::Reflect::get(&value, &(*key).into()).unwrap()
+            })
+            .as_f64()
+            .unwrap()
+    };
+    assert_eq!(get(&["cells", "ptr"]), universe.cells() as usize as f64);
+    assert_eq!(get(&["cells", "len"]), 36.0);
+    assert_eq!(get(&["ages", "ptr"]), universe.ages() as usize as f64);
+    assert_eq!(get(&["ages", "elementSize"]), 4.0);
+    assert_eq!(get(&["heat", "len"]), 36.0);
+
+    // Growing memory moves the epoch on.
+    let epoch = get(&["memoryEpoch"]);
+    let grown = vec![0u8; get(&["memoryBytes"]) as usize];
+    let info = universe.buffer_info().unwrap();
+    let epoch_after = js_sys::Reflect::get(&info, &"memoryEpoch".into()).unwrap();
+    assert_eq!(epoch_after.as_f64().unwrap(), epoch + 1.0);
+    drop(grown);
+}
+
+#[wasm_bindgen_test]
+pub fn test_universe_set() {
+    let mut set = UniverseSet::new();
+    let life = set.create(6, 6, "B3/S23").unwrap();
+    let highlife = set.create(8, 8, "B36/S23").unwrap();
+    let spaceship = set.insert(input_spaceship());
+    assert_eq!(set.ids(), vec![life, highlife, spaceship]);
+    assert!(set.create(0, 6, "B3/S23").is_err());
+    assert!(set.create(1 << 16, 1 << 16, "B3/S23").is_err());
+
+    set.add_pattern(String::from("blinker"), "x = 3, y = 1\n3o!")
+        .unwrap();
+    set.place_pattern(life, "blinker", 2, 2).unwrap();
+    set.place_pattern(highlife, "blinker", 2, 2).unwrap();
+    assert!(set.place_pattern(life, "glider", 2, 2).is_err());
+    assert_eq!(set.population(life).unwrap(), 3);
+
+    set.tick_all();
+    assert_eq!(set.generation(life).unwrap(), 1);
+    assert_eq!(set.generation(spaceship).unwrap(), 1);
+    assert_eq!(set.rule(highlife).unwrap(), "B36/S23");
+
+    let spaceship = set.remove(spaceship).unwrap();
+    assert_eq!(spaceship.get_cells(), expected_spaceship().get_cells());
+    assert_eq!(set.len(), 2);
+    assert!(set.width(99).is_err());
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "browser")]
+pub fn test_life_element() {
+    assert!(register_life_element().unwrap());
+    assert!(!register_life_element().unwrap());
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let element = document.create_element("game-of-life").unwrap();
+    element.set_attribute("width", "12").unwrap();
+    element.set_attribute("height", "8").unwrap();
+    element.set_attribute("pattern", "glider").unwrap();
+    document
+        .document_element()
+        .unwrap()
+        .append_child(&element)
+        .unwrap();
+
+    let canvas = element.query_selector("canvas").unwrap().unwrap();
+    assert_eq!(canvas.get_attribute("width").as_deref(), Some("12"));
+
+    // Changing the size starts the element over with a new canvas.
+    element.set_attribute("width", "20").unwrap();
+    let canvas = element.query_selector("canvas").unwrap().unwrap();
+    assert_eq!(canvas.get_attribute("width").as_deref(), Some("20"));
+    assert_eq!(element.child_element_count(), 1);
+
+    element.remove();
+    assert!(element.query_selector("canvas").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+pub fn test_layer_stack() {
+    let mut layers = LayerStack::new(6, 6).unwrap();
+    let soup = layers.add_layer(String::from("soup"), "B3/S23").unwrap();
+    let build = layers
+        .add_layer(String::from("construction"), "B3/S23")
+        .unwrap();
+    layers
+        .place_pattern(soup, &input_spaceship(), 3, 3)
+        .unwrap();
+    layers.toggle_cell(build, 0, 0).unwrap();
+
+    // A locked layer can't be edited, and one that isn't ticking holds still.
+    layers.set_locked(build, true).unwrap();
+    assert!(layers.toggle_cell(build, 0, 1).is_err());
+    layers.set_ticking(build, false).unwrap();
+    layers.tick();
+    assert_eq!(layers.generation(soup).unwrap(), 1);
+    assert_eq!(layers.generation(build).unwrap(), 0);
+    assert_eq!(layers.population(build).unwrap(), 1);
+
+    let pixels = layers.render_rgba();
+    let construction = layers.color(build).unwrap().to_be_bytes();
+    assert_eq!(
+        unsafe { std::slice::from_raw_parts(pixels, 4) },
+        construction
+    );
+
+    // Hidden layers are left out of the merge.
+    let hidden = layers.add_layer(String::from("notes"), "B3/S23").unwrap();
+    layers.toggle_cell(hidden, 5, 5).unwrap();
+    layers.set_visible(hidden, false).unwrap();
+    assert_eq!(layers.merge_layers(), Some(soup));
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers.name(soup).unwrap(), "soup");
+    assert_eq!(layers.population(soup).unwrap(), 6);
+    assert_eq!(layers.name(1).unwrap(), "notes");
+
+    assert!(LayerStack::new(0, 6).is_err());
+    assert!(LayerStack::new(1 << 16, 1 << 16).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_topology() {
+    let blinker = |horizontal, vertical| {
+        let mut universe = Universe::new();
+        universe.set_width(5).unwrap();
+        universe.set_height(5).unwrap();
+        universe.set_topology(horizontal, vertical);
+        universe.set_cells(&[(0, 0), (1, 0), (2, 0)]).unwrap();
+        universe.tick();
+        let cells = universe.get_cells();
+        (0..25)
+            .filter(|&idx| cells[idx] == Cell::Alive)
+            .map(|idx| (idx / 5, idx % 5))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        blinker(Edge::Wrap, Edge::Wrap),
+        vec![(1, 0), (1, 1), (1, 4)]
+    );
+    assert_eq!(blinker(Edge::Bounded, Edge::Bounded), vec![(1, 0), (1, 1)]);
+    // On a Möbius strip the cell past the left edge is on the far side of
+    // the strip, upside down.
+    assert_eq!(
+        blinker(Edge::Flip, Edge::Bounded),
+        vec![(1, 0), (1, 1), (3, 4)]
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_topology_round_trips() {
+    let edges = |universe: &Universe| (universe.horizontal_edge(), universe.vertical_edge());
+    let klein = (Edge::Flip, Edge::Wrap);
+
+    let config = js_sys::JSON::parse(r#"{ "boundary": ["flip", "wrap"] }"#).unwrap();
+    let mut universe = Universe::with_config(config.unchecked_into()).unwrap();
+    assert_eq!(edges(&universe), klein);
+    let config = js_sys::JSON::parse(r#"{ "boundary": "bounded" }"#).unwrap();
+    let bounded = Universe::with_config(config.unchecked_into()).unwrap();
+    assert_eq!(edges(&bounded), (Edge::Bounded, Edge::Bounded));
+    let built = UniverseBuilder::new()
+        .topology(Edge::Flip, Edge::Wrap)
+        .build()
+        .unwrap();
+    assert_eq!(edges(&built), klein);
+
+    universe.toggle_cell(1, 1).unwrap();
+    let imported = Universe::import_transferable(&universe.export_transferable()).unwrap();
+    assert_eq!(edges(&imported), klein);
+
+    let mut synced = Universe::new();
+    synced.apply_sync(&universe.sync_snapshot()).unwrap();
+    assert_eq!(edges(&synced), klein);
+    assert_eq!(synced.get_cells(), universe.get_cells());
+
+    let fragment = universe.to_url_fragment();
+    assert!(fragment.ends_with("&edges=flip,wrap"));
+    let mut linked = Universe::new();
+    linked.apply_url_fragment(&fragment).unwrap();
+    assert_eq!(edges(&linked), klein);
+    // Links without edges are for a torus.
+    linked.apply_url_fragment("#size=4x4").unwrap();
+    assert_eq!(edges(&linked), (Edge::Wrap, Edge::Wrap));
+
+    universe.start_recording();
+    universe.set_topology(Edge::Bounded, Edge::Flip);
+    let replayed = Universe::replay(&universe.recording()).unwrap();
+    assert_eq!(edges(&replayed), (Edge::Bounded, Edge::Flip));
+}
+
+#[wasm_bindgen_test]
+pub fn test_universe3d() {
+    // A 2x2x2 block where each voxel survives with its 7 neighbors and
+    // nothing is born stays as it is.
+    let mut universe = Universe3D::new(4, 4, 4, "B/S7").unwrap();
+    for &(x, y, z) in &[
+        (1, 1, 1),
+        (2, 1, 1),
+        (1, 2, 1),
+        (2, 2, 1),
+        (1, 1, 2),
+        (2, 1, 2),
+        (1, 2, 2),
+        (2, 2, 2),
+    ] {
+        universe.set_alive(x, y, z, true).unwrap();
+    }
+    universe.tick();
+    assert_eq!(universe.generation(), 1);
+    assert_eq!(universe.population(), 8);
+    assert_eq!((universe.births(), universe.deaths()), (0, 0));
+
+    assert_eq!(universe.slice(Axis::Z, 0).unwrap(), vec![0; 16]);
+    let middle = universe.slice(Axis::X, 1).unwrap();
+    assert_eq!(&middle[4..8], &[0, 1, 1, 0]);
+    assert!(universe.slice(Axis::Y, 4).is_err());
+
+    // Four faces on each side of the block, two triangles each.
+    assert_eq!(universe.surface_mesh().len(), 24 * 2 * 3 * 3);
+
+    // Under 5766 a lone voxel dies.
+    universe.set_rule("5766").unwrap();
+    assert_eq!(universe.rule(), "B6/S5,6,7");
+    let mut lone = Universe3D::new(4, 4, 4, "5766").unwrap();
+    lone.set_alive(0, 0, 0, true).unwrap();
+    lone.tick();
+    assert_eq!((lone.population(), lone.deaths()), (0, 1));
+    assert!(lone.set_alive(4, 0, 0, true).is_err());
+
+    assert!(Universe3D::new(0, 4, 4, "5766").is_err());
+    assert!(Universe3D::new(4096, 4096, 4096, "5766").is_err());
+    assert!(Universe3D::new(u32::MAX, u32::MAX, u32::MAX, "5766").is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_find_predecessor() {
+    let mut universe = Universe::new();
+    universe.set_width(7).unwrap();
+    universe.set_height(7).unwrap();
+    universe.set_cells(&[(3, 2), (3, 3), (3, 4)]).unwrap();
+
+    // Whatever comes back ticks into the region, here a blinker.
+    let predecessor = universe
+        .find_predecessor(2, 1, 4, 5, 100_000)
+        .unwrap()
+        .unwrap();
+    assert_eq!((predecessor.width(), predecessor.height()), (7, 5));
+    let mut grown = Universe::new();
+    grown.set_width(9).unwrap();
+    grown.set_height(7).unwrap();
+    grown.paste(&predecessor, 1, 1);
+    grown.tick();
+    for row in 0..3 {
+        for col in 0..5 {
+            let alive = (row == 1 && (1..=3).contains(&col)) as u8;
+            assert_eq!(grown.get_cells()[(row + 2) * 9 + col + 2] as u8, alive);
+        }
+    }
+
+    // Nothing lives on under B/S, so a live cell has no predecessor.
+    universe.set_rule("B/S").unwrap();
+    assert!(universe
+        .find_predecessor(3, 3, 3, 3, 10_000)
+        .unwrap()
+        .is_none());
+    universe.set_rule("B3/S23").unwrap();
+    assert!(universe.find_predecessor(0, 0, 6, 6, 10).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_methuselah_search() {
+    let mut search = MethuselahSearch::new(4, 6, Some(7)).unwrap();
+    search.set_max_generations(200);
+    search.set_arena_size(24).unwrap();
+    assert!(MethuselahSearch::new(0, 6, None).is_err());
+    assert!(MethuselahSearch::new(1 << 16, 6, None).is_err());
+    assert!(search.set_arena_size(1 << 16).is_err());
+    assert_eq!(search.arena_size(), 24);
+
+    // With no time to spare each run scores a single seed.
+    while search.evaluations() < 13 {
+        assert_eq!(search.run(0.0), 1);
+    }
+    assert_eq!(search.generation(), 2);
+    assert!(search.best_count() > 0);
+    let scores: Vec<u32> = (0..search.best_count())
+        .map(|rank| search.best_score(rank).unwrap())
+        .collect();
+    assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert!(scores[0] <= 200);
+    let cells = search.best_cells(0);
+    assert_eq!(cells.len() % 2, 0);
+    assert!(cells.iter().all(|&coordinate| coordinate < 4));
+    assert!(search.best_cells(99).is_empty());
+
+    search.set_score(MethuselahScore::FinalPopulation);
+    assert_eq!(search.best_count(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_battle() {
+    let mut battle = Battle::new(12, 8, 3, 4).unwrap();
+    for &(row, col) in &[(2, 1), (2, 2), (2, 3)] {
+        battle.place(Player::One, row, col).unwrap();
+    }
+    // An L that grows into a block.
+    for &(row, col) in &[(5, 8), (5, 9), (6, 8)] {
+        battle.place(Player::Two, row, col).unwrap();
+    }
+
+    assert!(battle.place(Player::One, 0, 6).is_err());
+    assert!(battle.place(Player::One, 0, 0).is_err());
+    battle.remove(Player::One, 2, 3).unwrap();
+    assert_eq!(battle.remaining(Player::One), 1);
+    battle.place(Player::One, 2, 3).unwrap();
+    assert!(battle.advance(1).is_err());
+
+    battle.set_ready(Player::One);
+    assert!(battle.remove(Player::One, 2, 3).is_err());
+    assert_eq!(battle.phase(), BattlePhase::Seeding);
+    battle.set_ready(Player::Two);
+    assert_eq!(battle.phase(), BattlePhase::Running);
+    assert!(battle.set_rule("B36/S23").is_err());
+
+    assert_eq!(battle.advance(1).unwrap(), 1);
+    assert_eq!(battle.winner(), None);
+    battle.finish().unwrap();
+    assert_eq!(battle.phase(), BattlePhase::Finished);
+    assert_eq!(battle.generation(), 4);
+    assert_eq!(battle.population(Player::One), 3);
+    assert_eq!(battle.population(Player::Two), 4);
+    // The blinker has covered five cells, the block four.
+    assert_eq!(battle.territory(Player::One), 5);
+    assert_eq!(battle.winner(), Some(Player::Two));
+
+    assert!(Battle::new(1, 8, 3, 4).is_err());
+    assert!(Battle::new(1 << 16, 1 << 16, 3, 4).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_script() {
+    let mut universe = Universe::new();
+    universe.set_width(20).unwrap();
+    universe.set_height(20).unwrap();
+    universe.kill_universe();
+    let mut script = Script::from_json(
+        r#"[
+            { "type": "caption", "id": "intro" },
+            { "type": "place", "pattern": "glider", "row": 5, "column": 5 },
+            { "type": "pan", "row": 2, "column": 3 },
+            { "type": "wait", "generations": 10 },
+            { "type": "pause", "ms": 500 },
+            { "type": "caption" }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(script.len(), 6);
+
+    assert!(script.advance(&mut universe, 500.0));
+    assert_eq!(script.caption().as_deref(), Some("intro"));
+    assert_eq!(universe.population(), 5);
+    assert_eq!(universe.viewport_origin_col(), 3);
+    assert_eq!(universe.generation(), 5);
+
+    script.pause();
+    script.advance(&mut universe, 1000.0);
+    assert_eq!(universe.generation(), 5);
+
+    // The rest of the wait and part of the pause.
+    script.play();
+    script.advance(&mut universe, 700.0);
+    assert_eq!(universe.generation(), 10);
+    assert_eq!(script.current_step(), 4);
+
+    script.skip(&mut universe);
+    assert!(!script.advance(&mut universe, 0.0));
+    assert!(script.is_finished());
+    assert_eq!(script.caption(), None);
+
+    assert!(Script::from_json(r#"[{ "type": "dance" }]"#).is_err());
+    assert!(Script::from_json(r#"[{ "type": "wait", "generations": 1, "speed": 0 }]"#).is_err());
+    assert!(
+        Script::from_json(r#"[{ "type": "place", "pattern": "x", "row": 0, "column": 0 }]"#)
+            .is_err()
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_walls() {
+    let blinker = |walls_alive| {
+        let mut universe = Universe::new();
+        universe.set_width(5).unwrap();
+        universe.set_height(5).unwrap();
+        universe.set_cells(&[(1, 2), (2, 2), (3, 2)]).unwrap();
+        universe.toggle_wall(2, 1).unwrap();
+        universe.set_walls_count_as_alive(walls_alive);
+        universe.tick();
+        universe
+    };
+
+    // The wall stays put where a cell would have been born, and isn't a
+    // neighbor unless walls count as alive.
+    let mut universe = blinker(false);
+    assert_eq!(universe.get_cells()[11], Cell::Wall);
+    assert_eq!(universe.population(), 2);
+    assert_eq!(universe.wall_count(), 1);
+    assert_eq!(blinker(true).population(), 6);
+
+    // Walls outlast clearing and toggling, and go in sync messages and links.
+    universe.fill_walls(0, 0, 0, 4);
+    universe.kill_universe();
+    universe.toggle_cell(0, 0).unwrap();
+    assert_eq!(universe.wall_count(), 6);
+    assert_eq!(universe.population(), 0);
+
+    let mut guest = Universe::new();
+    guest.apply_sync(&universe.sync_snapshot()).unwrap();
+    assert_eq!(guest.get_cells(), universe.get_cells());
+    let mut linked = Universe::new();
+    linked
+        .apply_url_fragment(&universe.to_url_fragment())
+        .unwrap();
+    assert_eq!(linked.get_cells(), universe.get_cells());
+
+    universe.clear_walls(0, 0, 10, 10);
+    assert_eq!(universe.wall_count(), 0);
+    guest.apply_sync(&universe.sync_diff()).unwrap();
+    assert_eq!(guest.get_cells(), universe.get_cells());
+
+    // So does the setting, along with exports, configs and recordings.
+    let mut universe = blinker(true);
+    let mut guest = Universe::new();
+    guest.apply_sync(&universe.sync_snapshot()).unwrap();
+    assert!(guest.walls_count_as_alive());
+    linked
+        .apply_url_fragment(&universe.to_url_fragment())
+        .unwrap();
+    assert!(linked.walls_count_as_alive());
+    let imported = Universe::import_transferable(&universe.export_transferable()).unwrap();
+    assert!(imported.walls_count_as_alive());
+    let config = js_sys::JSON::parse(r#"{ "wallsAlive": true }"#).unwrap();
+    assert!(Universe::with_config(config.unchecked_into())
+        .unwrap()
+        .walls_count_as_alive());
+    assert!(UniverseBuilder::new()
+        .walls_alive(true)
+        .build()
+        .unwrap()
+        .walls_count_as_alive());
+
+    universe.start_recording();
+    universe.set_walls_count_as_alive(false);
+    let log = universe.recording();
+    assert!(log.contains("walls-alive 1\n"));
+    assert!(!Universe::replay(&log).unwrap().walls_count_as_alive());
+}
+
+#[wasm_bindgen_test]
+pub fn test_nutrients() {
+    let blinker = |level| {
+        let mut universe = Universe::new();
+        universe.set_width(5).unwrap();
+        universe.set_height(5).unwrap();
+        universe.set_cells(&[(1, 2), (2, 2), (3, 2)]).unwrap();
+        universe.enable_nutrients(level);
+        universe
+    };
+
+    // With plenty of nutrients the blinker blinks, and its births eat into
+    // the field.
+    let mut universe = blinker(1.0);
+    universe.tick();
+    assert_eq!(universe.population(), 3);
+    assert!(universe.nutrient_level(2, 1).unwrap() < universe.nutrient_level(0, 0).unwrap());
+    assert!(!universe.nutrients().is_null());
+
+    // On barren ground nothing is born or survives.
+    let mut universe = blinker(0.0);
+    universe.tick();
+    assert_eq!(universe.population(), 0);
+
+    // Nor where the options ask for more than the field can hold.
+    let mut universe = blinker(1.0);
+    universe.set_nutrient_options(NutrientOptions::new(0.5, 2.0, 0.2, 0.02, 1.0));
+    universe.tick();
+    assert_eq!(universe.population(), 2);
+
+    universe.disable_nutrients();
+    assert!(universe.nutrients().is_null());
+    assert_eq!(universe.nutrient_level(0, 0), None);
+
+    // The field and options go along with exports, sync messages, configs,
+    // links and recordings.
+    let options = NutrientOptions::new(0.25, 0.1, 0.5, 0.05, 2.0);
+    let mut universe = blinker(1.0);
+    universe.set_nutrient_options(options);
+    universe.tick();
+    let levels = |universe: &Universe| {
+        (0..25)
+            .map(|idx| universe.nutrient_level(idx / 5, idx % 5).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let imported = Universe::import_transferable(&universe.export_transferable()).unwrap();
+    assert_eq!(imported.nutrient_options(), options);
+    assert_eq!(levels(&imported), levels(&universe));
+    let mut synced = Universe::new();
+    synced.apply_sync(&universe.sync_snapshot()).unwrap();
+    assert_eq!(synced.nutrient_options(), options);
+    assert_eq!(levels(&synced), levels(&universe));
+
+    let config = js_sys::JSON::parse(r#"{ "nutrients": { "level": 0.5, "capacity": 2 } }"#);
+    let configured = Universe::with_config(config.unwrap().unchecked_into()).unwrap();
+    assert_eq!(configured.nutrient_level(0, 0), Some(0.5));
+    assert_eq!(configured.nutrient_options().capacity(), 2.0);
+    let built = UniverseBuilder::new().nutrients(0.5, &options).build().unwrap();
+    assert_eq!(built.nutrient_options(), options);
+    assert_eq!(built.nutrient_level(0, 0), Some(0.5));
+
+    let mut linked = Universe::new();
+    linked
+        .apply_url_fragment(&universe.to_url_fragment())
+        .unwrap();
+    assert_eq!(linked.nutrient_options(), options);
+    assert_eq!(linked.nutrient_level(0, 0), Some(2.0));
+
+    universe.start_recording();
+    universe.fill_nutrients(0, 0, 1, 1, 0.75);
+    let replayed = Universe::replay(&universe.recording()).unwrap();
+    assert_eq!(replayed.nutrient_options(), options);
+    assert_eq!(levels(&replayed), levels(&universe));
+}
+
+#[wasm_bindgen_test]
+pub fn test_timeline() {
+    let mut universe = input_spaceship();
+    universe.set_timeline_limit(3);
+    let mut frames = Vec::new();
+    for _ in 0..4 {
+        universe.tick();
+        frames.push(universe.get_cells().to_vec());
+    }
+    assert_eq!(universe.timeline_length(), 3);
+    assert_eq!(universe.timeline_position(), 2);
+
+    assert_eq!(universe.seek_relative(-1), -1);
+    assert_eq!(universe.generation(), 3);
+    assert_eq!(universe.get_cells(), &frames[2][..]);
+    assert_eq!(universe.seek_relative(-5), -1);
+    assert_eq!(universe.generation(), 2);
+    assert_eq!(universe.seek_relative(10), 2);
+    assert_eq!(universe.get_cells(), &frames[3][..]);
+
+    // Ticking from an earlier frame carries on from there.
+    universe.seek_relative(-1);
+    universe.tick();
+    assert_eq!(universe.timeline_length(), 3);
+    assert_eq!(universe.get_cells(), &frames[3][..]);
+
+    // Edits come back after scrubbing away from them.
+    universe.toggle_cell(0, 0).unwrap();
+    let edited = universe.get_cells().to_vec();
+    universe.seek_relative(-1);
+    assert_eq!(universe.get_cells(), &frames[3][..]);
+    universe.seek_relative(1);
+    assert_eq!(universe.get_cells(), &edited[..]);
+
+    universe.set_timeline_limit(0);
+    assert_eq!(universe.timeline_length(), 0);
+    assert_eq!(universe.seek_relative(-1), 0);
+
+    // Seeking takes the nutrient field back with it, and there's no undoing
+    // ticks from before the jump.
+    let mut universe = input_spaceship();
+    universe.enable_nutrients(1.0);
+    universe.set_history_limit(10);
+    universe.set_timeline_limit(10);
+    let field = |universe: &Universe| {
+        (0..36)
+            .map(|idx| universe.nutrient_level(idx / 6, idx % 6).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let start = field(&universe);
+    universe.tick();
+    let first = field(&universe);
+    universe.tick();
+    assert_eq!(universe.seek_relative(-2), -2);
+    assert_eq!(field(&universe), start);
+    assert_eq!(universe.history_len(), 0);
+    assert!(!universe.step_back());
+    universe.seek_relative(1);
+    assert_eq!(field(&universe), first);
+}